@@ -11,11 +11,11 @@ use {
     yellowstone_grpc_proto::prelude::{
         subscribe_request_filter_accounts_filter::Filter as AccountsFilterDataOneof,
         subscribe_request_filter_accounts_filter_memcmp::Data as AccountsFilterMemcmpOneof,
-        subscribe_update::UpdateOneof, CommitmentLevel, SubscribeRequest,
+        subscribe_update::UpdateOneof, AccountLifecycle, CommitmentLevel, SubscribeRequest,
         SubscribeRequestAccountsDataSlice, SubscribeRequestFilterAccounts,
-        SubscribeRequestFilterAccountsFilter, SubscribeRequestFilterAccountsFilterMemcmp,
-        SubscribeRequestFilterBlocks, SubscribeRequestFilterBlocksMeta,
-        SubscribeRequestFilterEntry, SubscribeRequestFilterSlots,
+        SubscribeRequestFilterAccountsChangedRange, SubscribeRequestFilterAccountsFilter,
+        SubscribeRequestFilterAccountsFilterMemcmp, SubscribeRequestFilterBlocks,
+        SubscribeRequestFilterBlocksMeta, SubscribeRequestFilterEntry, SubscribeRequestFilterSlots,
         SubscribeRequestFilterTransactions, SubscribeRequestPing, SubscribeUpdateAccount,
         SubscribeUpdateTransaction, SubscribeUpdateTransactionStatus,
     },
@@ -81,6 +81,23 @@ impl From<ArgsCommitment> for CommitmentLevel {
     }
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ArgsAccountLifecycle {
+    Modified,
+    Created,
+    Closed,
+}
+
+impl From<ArgsAccountLifecycle> for AccountLifecycle {
+    fn from(lifecycle: ArgsAccountLifecycle) -> Self {
+        match lifecycle {
+            ArgsAccountLifecycle::Modified => AccountLifecycle::Modified,
+            ArgsAccountLifecycle::Created => AccountLifecycle::Created,
+            ArgsAccountLifecycle::Closed => AccountLifecycle::Closed,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Subcommand)]
 enum Action {
     HealthCheck,
@@ -130,6 +147,26 @@ struct ActionSubscribe {
     #[clap(long)]
     accounts_token_account_state: bool,
 
+    /// Receive only first write of the account in the slot
+    #[clap(long)]
+    accounts_first_write_only: bool,
+
+    /// Include account data, data is included by default
+    #[clap(long)]
+    accounts_include_data: Option<bool>,
+
+    /// Receive account only when owner is changed
+    #[clap(long)]
+    accounts_owner_change_only: bool,
+
+    /// Filter accounts by lifecycle: created, modified or closed
+    #[clap(long)]
+    accounts_lifecycle: Vec<ArgsAccountLifecycle>,
+
+    /// Receive account only when bytes in the range are changed, format: `offset,length`
+    #[clap(long)]
+    accounts_changed_range: Option<String>,
+
     /// Receive only part of updated data account, format: `offset,size`
     #[clap(long)]
     accounts_data_slice: Vec<String>,
@@ -142,6 +179,18 @@ struct ActionSubscribe {
     #[clap(long)]
     slots_filter_by_commitment: bool,
 
+    /// Send slots starting from this slot
+    #[clap(long)]
+    slots_from_slot: Option<u64>,
+
+    /// Send slots up to this slot and close the stream after
+    #[clap(long)]
+    slots_to_slot: Option<u64>,
+
+    /// Receive processed slots which are not on the finalized fork
+    #[clap(long)]
+    slots_include_dead: bool,
+
     /// Subscribe on transactions updates
     #[clap(long)]
     transactions: bool,
@@ -170,6 +219,10 @@ struct ActionSubscribe {
     #[clap(long)]
     transactions_account_required: Vec<String>,
 
+    /// Include transaction message, message is included by default
+    #[clap(long)]
+    transactions_include_message: Option<bool>,
+
     /// Subscribe on transactions_status updates
     #[clap(long)]
     transactions_status: bool,
@@ -221,6 +274,10 @@ struct ActionSubscribe {
     #[clap(long)]
     blocks_include_entries: Option<bool>,
 
+    /// Include rewards to block message
+    #[clap(long)]
+    blocks_include_rewards: Option<bool>,
+
     /// Subscribe on block meta updates (without transactions)
     #[clap(long)]
     blocks_meta: bool,
@@ -272,6 +329,23 @@ impl Action {
                             _ => anyhow::bail!("invalid memcmp"),
                         }
                     }
+                    let changed_range = match args.accounts_changed_range.as_deref() {
+                        Some(range) => match range.split_once(',') {
+                            Some((offset, length)) => {
+                                Some(SubscribeRequestFilterAccountsChangedRange {
+                                    offset: offset
+                                        .parse()
+                                        .map_err(|_| anyhow::anyhow!("invalid offset"))?,
+                                    length: length
+                                        .trim()
+                                        .parse()
+                                        .map_err(|_| anyhow::anyhow!("invalid length"))?,
+                                })
+                            }
+                            None => anyhow::bail!("invalid changed range"),
+                        },
+                        None => None,
+                    };
                     if let Some(datasize) = args.accounts_datasize {
                         filters.push(SubscribeRequestFilterAccountsFilter {
                             filter: Some(AccountsFilterDataOneof::Datasize(datasize)),
@@ -289,6 +363,15 @@ impl Action {
                             account: accounts_account,
                             owner: args.accounts_owner.clone(),
                             filters,
+                            first_write_only: Some(args.accounts_first_write_only),
+                            include_account_data: args.accounts_include_data,
+                            owner_change_only: Some(args.accounts_owner_change_only),
+                            lifecycle: args
+                                .accounts_lifecycle
+                                .iter()
+                                .map(|lifecycle| AccountLifecycle::from(*lifecycle) as i32)
+                                .collect(),
+                            changed_range,
                         },
                     );
                 }
@@ -299,6 +382,9 @@ impl Action {
                         "client".to_owned(),
                         SubscribeRequestFilterSlots {
                             filter_by_commitment: Some(args.slots_filter_by_commitment),
+                            from_slot: args.slots_from_slot,
+                            to_slot: args.slots_to_slot,
+                            include_dead: Some(args.slots_include_dead),
                         },
                     );
                 }
@@ -314,6 +400,15 @@ impl Action {
                             account_include: args.transactions_account_include.clone(),
                             account_exclude: args.transactions_account_exclude.clone(),
                             account_required: args.transactions_account_required.clone(),
+                            include_transaction_message: args.transactions_include_message,
+                            signature_prefix: None,
+                            include_meta: None,
+                            include_logs: None,
+                            include_inner_instructions: None,
+                            include_account_keys: None,
+                            invoked_programs: vec![],
+                            include_balances: None,
+                            include_return_data: None,
                         },
                     );
                 }
@@ -329,6 +424,15 @@ impl Action {
                             account_include: args.transactions_status_account_include.clone(),
                             account_exclude: args.transactions_status_account_exclude.clone(),
                             account_required: args.transactions_status_account_required.clone(),
+                            include_transaction_message: None,
+                            signature_prefix: None,
+                            include_meta: None,
+                            include_logs: None,
+                            include_inner_instructions: None,
+                            include_account_keys: None,
+                            invoked_programs: vec![],
+                            include_balances: None,
+                            include_return_data: None,
                         },
                     );
                 }
@@ -347,6 +451,7 @@ impl Action {
                             include_transactions: args.blocks_include_transactions,
                             include_accounts: args.blocks_include_accounts,
                             include_entries: args.blocks_include_entries,
+                            include_rewards: args.blocks_include_rewards,
                         },
                     );
                 }
@@ -384,6 +489,14 @@ impl Action {
                         commitment: commitment.map(|x| x as i32),
                         accounts_data_slice,
                         ping,
+                        commit: None,
+                        accounts_coalesce_window_ms: None,
+                        accounts_chunking: None,
+                        accounts_sample_rate: None,
+                        filter_ack: None,
+                        resume_token: None,
+                        blocks_chunking: None,
+                        include_created_at: None,
                     },
                     args.resub.unwrap_or(0),
                 ))
@@ -406,6 +519,7 @@ pub struct AccountPretty {
     data: String,
     write_version: u64,
     txn_signature: String,
+    lifecycle: AccountLifecycle,
 }
 
 impl From<SubscribeUpdateAccount> for AccountPretty {
@@ -414,6 +528,7 @@ impl From<SubscribeUpdateAccount> for AccountPretty {
             is_startup,
             slot,
             account,
+            lifecycle,
         }: SubscribeUpdateAccount,
     ) -> Self {
         let account = account.expect("should be defined");
@@ -428,6 +543,7 @@ impl From<SubscribeUpdateAccount> for AccountPretty {
             data: hex::encode(account.data),
             write_version: account.write_version,
             txn_signature: bs58::encode(account.txn_signature.unwrap_or_default()).into_string(),
+            lifecycle: AccountLifecycle::try_from(lifecycle).unwrap_or_default(),
         }
     }
 }
@@ -671,6 +787,14 @@ async fn geyser_subscribe(
                     commitment: None,
                     accounts_data_slice: Vec::default(),
                     ping: None,
+                    commit: None,
+                    accounts_coalesce_window_ms: None,
+                    accounts_chunking: None,
+                    accounts_sample_rate: None,
+                    filter_ack: None,
+                    resume_token: None,
+                    blocks_chunking: None,
+                    include_created_at: None,
                 })
                 .await
                 .map_err(GeyserGrpcClientError::SubscribeSendError)?;
@@ -43,7 +43,7 @@ async fn main() -> anyhow::Result<()> {
         async move {
             subscribe_tx
             .send(SubscribeRequest {
-                slots: maplit::hashmap! { "".to_owned() => SubscribeRequestFilterSlots { filter_by_commitment: Some(true) } },
+                slots: maplit::hashmap! { "".to_owned() => SubscribeRequestFilterSlots { filter_by_commitment: Some(true), from_slot: None, to_slot: None, include_dead: None } },
                 commitment: Some(CommitmentLevel::Processed as i32),
                 ..Default::default()
             })
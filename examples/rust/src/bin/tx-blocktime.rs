@@ -102,6 +102,15 @@ async fn main() -> anyhow::Result<()> {
                 account_include: args.account_include,
                 account_exclude: args.account_exclude,
                 account_required: args.account_required,
+                include_transaction_message: None,
+                signature_prefix: None,
+                include_meta: None,
+                include_logs: None,
+                include_inner_instructions: None,
+                include_account_keys: None,
+                invoked_programs: vec![],
+                include_balances: None,
+                include_return_data: None,
             } },
             entry: HashMap::new(),
             blocks: HashMap::new(),
@@ -109,6 +118,14 @@ async fn main() -> anyhow::Result<()> {
             commitment: Some(commitment as i32),
             accounts_data_slice: vec![],
             ping: None,
+            commit: None,
+            accounts_coalesce_window_ms: None,
+            accounts_chunking: None,
+            accounts_sample_rate: None,
+            filter_ack: None,
+            resume_token: None,
+            blocks_chunking: None,
+            include_created_at: None,
         })
         .await?;
 
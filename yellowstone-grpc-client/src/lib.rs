@@ -15,12 +15,18 @@ use {
         Request, Response, Status,
     },
     tonic_health::pb::{health_client::HealthClient, HealthCheckRequest, HealthCheckResponse},
-    yellowstone_grpc_proto::prelude::{
-        geyser_client::GeyserClient, CommitmentLevel, GetBlockHeightRequest,
-        GetBlockHeightResponse, GetLatestBlockhashRequest, GetLatestBlockhashResponse,
-        GetSlotRequest, GetSlotResponse, GetVersionRequest, GetVersionResponse,
-        IsBlockhashValidRequest, IsBlockhashValidResponse, PingRequest, PongResponse,
-        SubscribeRequest, SubscribeUpdate,
+    yellowstone_grpc_proto::{
+        prelude::{
+            geyser_client::GeyserClient, CommitmentLevel, DropSubscriptionRequest,
+            DropSubscriptionResponse, GetBlockHeightRequest, GetBlockHeightResponse,
+            GetBlockRequest, GetBlockResponse, GetLatestBlockhashRequest,
+            GetLatestBlockhashResponse, GetSlotRequest, GetSlotResponse, GetVersionRequest,
+            GetVersionResponse, IsBlockhashValidRequest, IsBlockhashValidResponse,
+            ListSubscriptionsRequest, ListSubscriptionsResponse, PingRequest, PongResponse,
+            SubscribeRequest, SubscribeRequestFilterAccounts, SubscribeRequestFilterSlots,
+            SubscribeRequestFilterTransactions, SubscribeUpdate,
+        },
+        presets::FilterPreset,
     },
 };
 
@@ -195,6 +201,40 @@ impl<F: Interceptor> GeyserGrpcClient<F> {
         let response = self.geyser.get_version(request).await?;
         Ok(response.into_inner())
     }
+
+    pub async fn get_block(
+        &mut self,
+        request: GetBlockRequest,
+    ) -> GeyserGrpcClientResult<GetBlockResponse> {
+        let response = self.geyser.get_block(tonic::Request::new(request)).await?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn drop_subscription(
+        &mut self,
+        admin_x_token: AsciiMetadataValue,
+        id: u64,
+        reason: Option<String>,
+    ) -> GeyserGrpcClientResult<DropSubscriptionResponse> {
+        let mut request = tonic::Request::new(DropSubscriptionRequest { id, reason });
+        request
+            .metadata_mut()
+            .insert("x-admin-token", admin_x_token);
+        let response = self.geyser.drop_subscription(request).await?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn list_subscriptions(
+        &mut self,
+        admin_x_token: AsciiMetadataValue,
+    ) -> GeyserGrpcClientResult<ListSubscriptionsResponse> {
+        let mut request = tonic::Request::new(ListSubscriptionsRequest {});
+        request
+            .metadata_mut()
+            .insert("x-admin-token", admin_x_token);
+        let response = self.geyser.list_subscriptions(request).await?;
+        Ok(response.into_inner())
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -414,9 +454,136 @@ impl GeyserGrpcBuilder {
     }
 }
 
+/// Builder of `SubscribeRequest`, presets are shortcuts for common accounts filters
+#[derive(Debug, Default, Clone)]
+pub struct SubscribeRequestBuilder {
+    request: SubscribeRequest,
+}
+
+impl SubscribeRequestBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn accounts(
+        mut self,
+        name: impl Into<String>,
+        filter: SubscribeRequestFilterAccounts,
+    ) -> Self {
+        self.request.accounts.insert(name.into(), filter);
+        self
+    }
+
+    pub fn slots(mut self, name: impl Into<String>, filter: SubscribeRequestFilterSlots) -> Self {
+        self.request.slots.insert(name.into(), filter);
+        self
+    }
+
+    pub fn transactions(
+        mut self,
+        name: impl Into<String>,
+        filter: SubscribeRequestFilterTransactions,
+    ) -> Self {
+        self.request.transactions.insert(name.into(), filter);
+        self
+    }
+
+    pub fn commitment(mut self, commitment: CommitmentLevel) -> Self {
+        self.request.commitment = Some(commitment as i32);
+        self
+    }
+
+    /// Presets are only shortcuts for accounts filters, filter name is `preset:<name>`
+    pub fn preset(self, preset: FilterPreset) -> Self {
+        self.accounts(preset.filter_name(), preset.to_filter())
+    }
+
+    pub fn preset_spl_token(self) -> Self {
+        self.preset(FilterPreset::SplToken)
+    }
+
+    pub fn preset_spl_token_2022(self) -> Self {
+        self.preset(FilterPreset::SplToken2022)
+    }
+
+    pub fn preset_system(self) -> Self {
+        self.preset(FilterPreset::System)
+    }
+
+    /// Accounts owned by the program, not validated by the server
+    pub fn preset_program(self, name: impl Into<String>, program_id: impl Into<String>) -> Self {
+        self.accounts(
+            name,
+            SubscribeRequestFilterAccounts {
+                owner: vec![program_id.into()],
+                ..Default::default()
+            },
+        )
+    }
+
+    pub fn build(self) -> SubscribeRequest {
+        self.request
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{GeyserGrpcBuilderError, GeyserGrpcClient};
+    use {
+        super::{GeyserGrpcBuilderError, GeyserGrpcClient, SubscribeRequestBuilder},
+        yellowstone_grpc_proto::{
+            prelude::{
+                subscribe_request_filter_accounts_filter::Filter as AccountsFilterDataOneof,
+                CommitmentLevel, SubscribeRequestFilterAccounts,
+                SubscribeRequestFilterAccountsFilter,
+            },
+            presets::{SPL_TOKEN_2022_PROGRAM_ID, SPL_TOKEN_PROGRAM_ID, SYSTEM_PROGRAM_ID},
+        },
+    };
+
+    #[test]
+    fn test_subscribe_request_builder_presets() {
+        let program_id = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+        let request = SubscribeRequestBuilder::new()
+            .preset_spl_token()
+            .preset_spl_token_2022()
+            .preset_system()
+            .preset_program("whirlpool", program_id)
+            .commitment(CommitmentLevel::Confirmed)
+            .build();
+        assert_eq!(request.commitment, Some(CommitmentLevel::Confirmed as i32));
+        assert_eq!(request.accounts.len(), 4);
+
+        let filter =
+            |owner: &str, filter: Option<AccountsFilterDataOneof>| SubscribeRequestFilterAccounts {
+                owner: vec![owner.to_owned()],
+                filters: filter
+                    .map(|filter| SubscribeRequestFilterAccountsFilter {
+                        filter: Some(filter),
+                    })
+                    .into_iter()
+                    .collect(),
+                ..Default::default()
+            };
+        assert_eq!(
+            request.accounts["preset:spl_token"],
+            filter(
+                SPL_TOKEN_PROGRAM_ID,
+                Some(AccountsFilterDataOneof::Datasize(165))
+            )
+        );
+        assert_eq!(
+            request.accounts["preset:spl_token_2022"],
+            filter(
+                SPL_TOKEN_2022_PROGRAM_ID,
+                Some(AccountsFilterDataOneof::TokenAccountState(true))
+            )
+        );
+        assert_eq!(
+            request.accounts["preset:system"],
+            filter(SYSTEM_PROGRAM_ID, None)
+        );
+        assert_eq!(request.accounts["whirlpool"], filter(program_id, None));
+    }
 
     #[tokio::test]
     async fn test_channel_https_success() {
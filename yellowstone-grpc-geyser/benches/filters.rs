@@ -0,0 +1,140 @@
+//! Per-update cost of account filters matching, run with `cargo bench -p yellowstone-grpc-geyser`
+
+use {
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        collections::HashMap,
+        hint::black_box,
+        time::{Duration, Instant},
+    },
+    yellowstone_grpc_geyser::{
+        config::ConfigGrpcFilters,
+        filters::Filter,
+        grpc::{Message, MessageAccount, MessageAccountInfo, MessageCreatedAt},
+    },
+    yellowstone_grpc_proto::prelude::{
+        subscribe_request_filter_accounts_filter::Filter as AccountsFilterDataOneof,
+        subscribe_request_filter_accounts_filter_memcmp::Data as AccountsFilterMemcmpOneof,
+        AccountLifecycle, SubscribeRequest, SubscribeRequestFilterAccounts,
+        SubscribeRequestFilterAccountsFilter, SubscribeRequestFilterAccountsFilterMemcmp,
+    },
+};
+
+const FILTERS: usize = 50;
+const MINTS: usize = 5;
+const DATA_SIZE: usize = 165;
+const ITERATIONS: u32 = 200_000;
+
+fn memcmp(offset: u64, data: Vec<u8>) -> SubscribeRequestFilterAccountsFilter {
+    SubscribeRequestFilterAccountsFilter {
+        filter: Some(AccountsFilterDataOneof::Memcmp(
+            SubscribeRequestFilterAccountsFilterMemcmp {
+                offset,
+                data: Some(AccountsFilterMemcmpOneof::Bytes(data)),
+            },
+        )),
+    }
+}
+
+// Every filter watches the same program, filters share the discriminator and one of few mints,
+// but every filter has own wallet, so only one filter matches the update
+fn create_filter(program: Pubkey, mints: &[Pubkey], wallets: &[Pubkey]) -> Filter {
+    let accounts = wallets
+        .iter()
+        .enumerate()
+        .map(|(index, wallet)| {
+            let filter = SubscribeRequestFilterAccounts {
+                account: vec![],
+                owner: vec![program.to_string()],
+                filters: vec![
+                    SubscribeRequestFilterAccountsFilter {
+                        filter: Some(AccountsFilterDataOneof::Datasize(DATA_SIZE as u64)),
+                    },
+                    memcmp(0, vec![1; 8]),
+                    memcmp(8, mints[index % mints.len()].to_bytes().to_vec()),
+                    memcmp(40, wallet.to_bytes().to_vec()),
+                ],
+                first_write_only: None,
+                include_account_data: None,
+                owner_change_only: None,
+                lifecycle: vec![],
+                changed_range: None,
+            };
+            (format!("filter{index}"), filter)
+        })
+        .collect::<HashMap<_, _>>();
+
+    let config = SubscribeRequest {
+        accounts,
+        ..Default::default()
+    };
+    let mut limit = ConfigGrpcFilters::default();
+    limit.accounts.max = FILTERS;
+    limit.accounts.owner_max = FILTERS;
+    Filter::new(&config, &limit).expect("valid filter")
+}
+
+fn create_message(program: Pubkey, mint: Pubkey, wallet: Pubkey) -> Message {
+    let mut data = vec![0; DATA_SIZE];
+    data[0..8].copy_from_slice(&[1; 8]);
+    data[8..40].copy_from_slice(mint.as_ref());
+    data[40..72].copy_from_slice(wallet.as_ref());
+    Message::Account(MessageAccount {
+        account: MessageAccountInfo {
+            pubkey: Pubkey::new_unique(),
+            lamports: 0,
+            owner: program,
+            executable: false,
+            rent_epoch: 0,
+            data,
+            write_version: 0,
+            txn_signature: None,
+        },
+        slot: 0,
+        is_startup: false,
+        lifecycle: AccountLifecycle::Modified,
+        created_at: MessageCreatedAt::now(),
+    })
+}
+
+fn run(name: &str, filter: &Filter, message: &Message) {
+    let mut elapsed = Duration::ZERO;
+    let mut matched = 0;
+    for _ in 0..ITERATIONS {
+        let ts = Instant::now();
+        for (filters, _message) in filter.get_filters(black_box(message), None) {
+            matched += filters.len();
+        }
+        elapsed += ts.elapsed();
+    }
+    println!(
+        "{name}: {:?} per update, {} filters matched",
+        elapsed / ITERATIONS,
+        matched / ITERATIONS as usize
+    );
+}
+
+fn main() {
+    let program = Pubkey::new_unique();
+    let mints = (0..MINTS).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+    let wallets = (0..FILTERS)
+        .map(|_| Pubkey::new_unique())
+        .collect::<Vec<_>>();
+    let filter = create_filter(program, &mints, &wallets);
+
+    run(
+        "hot program, one filter matched",
+        &filter,
+        &create_message(program, mints[0], wallets[0]),
+    );
+    run(
+        "hot program, no filters matched",
+        &filter,
+        &create_message(program, Pubkey::new_unique(), Pubkey::new_unique()),
+    );
+    run(
+        "other program",
+        &filter,
+        &create_message(Pubkey::new_unique(), mints[0], wallets[0]),
+    );
+}
@@ -0,0 +1,222 @@
+use {
+    crate::{
+        config::ConfigGrpcAdmission,
+        prom::{SUBSCRIPTIONS_ADMISSION_QUEUE, SUBSCRIPTIONS_ADMISSION_REJECTED_TOTAL},
+    },
+    std::{
+        fmt,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    },
+    tokio::{
+        sync::{OwnedSemaphorePermit, Semaphore},
+        time::{timeout, Instant},
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdmissionError {
+    /// Too many subscriptions are already waiting
+    QueueFull,
+    /// No permit was released during the queue timeout
+    Timeout,
+}
+
+impl AdmissionError {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::QueueFull => "queue_full",
+            Self::Timeout => "timeout",
+        }
+    }
+}
+
+impl fmt::Display for AdmissionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::QueueFull => write!(f, "too many new subscriptions, admission queue is full"),
+            Self::Timeout => write!(f, "too many new subscriptions, admission timed out"),
+        }
+    }
+}
+
+/// Bounds number of subscriptions initialized at the same time, others wait in the queue
+#[derive(Debug)]
+pub struct AdmissionControl {
+    config: ConfigGrpcAdmission,
+    permits: Arc<Semaphore>,
+    queued: AtomicUsize,
+}
+
+impl AdmissionControl {
+    pub fn new(config: ConfigGrpcAdmission) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            config.max_concurrent > 0,
+            "`max_concurrent` should be greater than zero"
+        );
+        Ok(Self {
+            config,
+            permits: Arc::new(Semaphore::new(config.max_concurrent)),
+            queued: AtomicUsize::new(0),
+        })
+    }
+
+    /// Waits for a permit, subscription is initialized while permit is alive
+    pub async fn acquire(&self) -> Result<AdmissionPermit, AdmissionError> {
+        self.acquire_semaphore()
+            .await
+            .map(|permit| AdmissionPermit {
+                _permit: permit,
+                deadline: Instant::now() + Duration::from_millis(self.config.init_timeout_ms),
+            })
+    }
+
+    async fn acquire_semaphore(&self) -> Result<OwnedSemaphorePermit, AdmissionError> {
+        if let Ok(permit) = Arc::clone(&self.permits).try_acquire_owned() {
+            return Ok(permit);
+        }
+
+        let result = match AdmissionQueueGuard::new(&self.queued, self.config.queue_max) {
+            Some(_guard) => timeout(
+                Duration::from_millis(self.config.queue_timeout_ms),
+                Arc::clone(&self.permits).acquire_owned(),
+            )
+            .await
+            .map_err(|_elapsed| AdmissionError::Timeout)
+            .map(|permit| permit.expect("semaphore is never closed")),
+            None => Err(AdmissionError::QueueFull),
+        };
+        if let Err(error) = result {
+            SUBSCRIPTIONS_ADMISSION_REJECTED_TOTAL
+                .with_label_values(&[error.as_str()])
+                .inc();
+        }
+        result
+    }
+}
+
+/// Permit of the subscription, should be dropped once the subscription is initialized or
+/// `deadline` is reached, so idle subscriptions don't block others
+#[derive(Debug)]
+pub struct AdmissionPermit {
+    _permit: OwnedSemaphorePermit,
+    deadline: Instant,
+}
+
+impl AdmissionPermit {
+    pub const fn deadline(&self) -> Instant {
+        self.deadline
+    }
+}
+
+/// Place in the admission queue, released on drop, so cancelled subscriptions leave the queue
+struct AdmissionQueueGuard<'a> {
+    queued: &'a AtomicUsize,
+}
+
+impl<'a> AdmissionQueueGuard<'a> {
+    fn new(queued: &'a AtomicUsize, queue_max: usize) -> Option<Self> {
+        queued
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |size| {
+                (size < queue_max).then_some(size + 1)
+            })
+            .ok()?;
+        SUBSCRIPTIONS_ADMISSION_QUEUE.inc();
+        Some(Self { queued })
+    }
+}
+
+impl Drop for AdmissionQueueGuard<'_> {
+    fn drop(&mut self) {
+        self.queued.fetch_sub(1, Ordering::Relaxed);
+        SUBSCRIPTIONS_ADMISSION_QUEUE.dec();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{AdmissionControl, AdmissionError},
+        crate::config::ConfigGrpcAdmission,
+        std::{
+            sync::{
+                atomic::{AtomicUsize, Ordering},
+                Arc,
+            },
+            time::Duration,
+        },
+        tokio::time::sleep,
+    };
+
+    #[tokio::test]
+    async fn test_admission_burst() {
+        let admission = Arc::new(
+            AdmissionControl::new(ConfigGrpcAdmission {
+                max_concurrent: 2,
+                queue_max: 16,
+                queue_timeout_ms: 10_000,
+                init_timeout_ms: 5_000,
+            })
+            .unwrap(),
+        );
+        let active = Arc::new(AtomicUsize::new(0));
+        let active_max = Arc::new(AtomicUsize::new(0));
+
+        let tasks = (0..10)
+            .map(|_| {
+                let admission = Arc::clone(&admission);
+                let active = Arc::clone(&active);
+                let active_max = Arc::clone(&active_max);
+                tokio::spawn(async move {
+                    let _permit = admission.acquire().await.unwrap();
+                    let count = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    active_max.fetch_max(count, Ordering::SeqCst);
+                    sleep(Duration::from_millis(5)).await;
+                    active.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect::<Vec<_>>();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(active_max.load(Ordering::SeqCst), 2);
+        assert_eq!(admission.queued.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_admission_rejects() {
+        let admission = Arc::new(
+            AdmissionControl::new(ConfigGrpcAdmission {
+                max_concurrent: 1,
+                queue_max: 1,
+                queue_timeout_ms: 50,
+                init_timeout_ms: 5_000,
+            })
+            .unwrap(),
+        );
+        let permit = admission.acquire().await.unwrap();
+
+        // the only place in the queue is taken, next subscription is rejected immediately
+        let queued = tokio::spawn({
+            let admission = Arc::clone(&admission);
+            async move { admission.acquire().await.map(drop) }
+        });
+        sleep(Duration::from_millis(10)).await;
+        assert_eq!(
+            admission.acquire().await.err(),
+            Some(AdmissionError::QueueFull)
+        );
+        assert_eq!(queued.await.unwrap(), Err(AdmissionError::Timeout));
+
+        // released permit is handed to the waiting subscription
+        let queued = tokio::spawn({
+            let admission = Arc::clone(&admission);
+            async move { admission.acquire().await.map(drop) }
+        });
+        drop(permit);
+        assert_eq!(queued.await.unwrap(), Ok(()));
+    }
+}
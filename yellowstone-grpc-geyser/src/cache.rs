@@ -0,0 +1,50 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// Bounded map with two generations: once current generation is full previous one is dropped,
+/// so least recently updated keys are evicted first
+#[derive(Debug, Default)]
+pub struct GenerationCache<K, V> {
+    generation_max: usize,
+    current: HashMap<K, V>,
+    previous: HashMap<K, V>,
+}
+
+impl<K: Eq + Hash + Copy, V> GenerationCache<K, V> {
+    pub fn new(max: usize) -> Self {
+        Self {
+            generation_max: (max / 2).max(1),
+            current: HashMap::new(),
+            previous: HashMap::new(),
+        }
+    }
+
+    /// Saves new value and returns previous one, if known
+    pub fn replace(&mut self, key: K, value: V) -> Option<V> {
+        let previous_value = self
+            .current
+            .insert(key, value)
+            .or_else(|| self.previous.remove(&key));
+        if self.current.len() >= self.generation_max {
+            self.previous = std::mem::take(&mut self.current);
+        }
+        previous_value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GenerationCache;
+
+    #[test]
+    fn test_generation_cache_evicts_least_recent() {
+        let mut cache = GenerationCache::new(4);
+        assert_eq!(cache.replace(1, "a"), None);
+        assert_eq!(cache.replace(2, "b"), None);
+        // generation is full, `1` and `2` are in the previous generation now
+        assert_eq!(cache.replace(1, "c"), Some("a"));
+        assert_eq!(cache.replace(3, "d"), None);
+        // `2` was not updated since previous generation, so it is evicted
+        assert_eq!(cache.replace(2, "e"), None);
+        assert_eq!(cache.replace(1, "f"), Some("c"));
+    }
+}
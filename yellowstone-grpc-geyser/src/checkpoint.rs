@@ -0,0 +1,289 @@
+use {
+    crate::{
+        config::ConfigGrpcCheckpoint,
+        grpc::{Message, MessageCreatedAt, MessageSlot},
+        prom,
+    },
+    anyhow::Context,
+    log::{error, info, warn},
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::VecDeque,
+        io,
+        sync::{Arc, Mutex},
+        time::Duration,
+    },
+    tokio::{fs, time::sleep},
+    yellowstone_grpc_proto::prelude::CommitmentLevel,
+};
+
+/// Slot update saved to the checkpoint file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckpointSlot {
+    pub slot: u64,
+    pub parent: Option<u64>,
+    /// `CommitmentLevel` as number
+    pub status: i32,
+    pub dead: bool,
+}
+
+impl From<&MessageSlot> for CheckpointSlot {
+    fn from(msg: &MessageSlot) -> Self {
+        Self {
+            slot: msg.slot,
+            parent: msg.parent,
+            status: msg.status as i32,
+            dead: msg.dead,
+        }
+    }
+}
+
+impl CheckpointSlot {
+    fn to_message(self) -> anyhow::Result<MessageSlot> {
+        Ok(MessageSlot {
+            slot: self.slot,
+            parent: self.parent,
+            status: CommitmentLevel::try_from(self.status)
+                .map_err(|_| anyhow::anyhow!("invalid status of slot {}", self.slot))?,
+            dead: self.dead,
+            created_at: MessageCreatedAt::now(),
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct SlotCheckpointInner {
+    slots: VecDeque<CheckpointSlot>,
+    dirty: bool,
+}
+
+/// Last slot updates handled by the plugin, persisted to survive restarts
+#[derive(Debug)]
+pub struct SlotCheckpoint {
+    config: ConfigGrpcCheckpoint,
+    /// Slot updates from the previous run
+    restored: Vec<Arc<Message>>,
+    inner: Mutex<SlotCheckpointInner>,
+}
+
+impl SlotCheckpoint {
+    pub async fn load(config: ConfigGrpcCheckpoint) -> anyhow::Result<Arc<Self>> {
+        anyhow::ensure!(config.slots > 0, "checkpoint `slots` should be positive");
+        anyhow::ensure!(
+            config.save_interval > 0,
+            "checkpoint `save_interval` should be positive"
+        );
+
+        // corrupted or partially written file should not prevent plugin from start
+        let (mut slots, mut restored) = match fs::read(&config.path).await {
+            Ok(data) => Self::parse(&data).unwrap_or_else(|error| {
+                warn!("checkpoint {:?} is discarded: {error:?}", config.path);
+                Default::default()
+            }),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Default::default(),
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("failed to read checkpoint from {:?}", config.path))
+            }
+        };
+        let skip = slots.len().saturating_sub(config.slots);
+        slots.drain(..skip);
+        restored.drain(..skip);
+
+        // metrics show the position of the previous run until new slots are handled
+        for message in restored.iter() {
+            if let Message::Slot(msg) = message.as_ref() {
+                prom::update_slot_plugin_status(msg.status, msg.slot);
+            }
+        }
+
+        let checkpoint = Self {
+            config,
+            restored,
+            inner: Mutex::new(SlotCheckpointInner {
+                slots,
+                dirty: false,
+            }),
+        };
+        info!(
+            "checkpoint: restored {} slot updates, last processed slot: {:?}",
+            checkpoint.restored.len(),
+            checkpoint.get_last_slot(CommitmentLevel::Processed)
+        );
+        Ok(Arc::new(checkpoint))
+    }
+
+    fn parse(data: &[u8]) -> anyhow::Result<(VecDeque<CheckpointSlot>, Vec<Arc<Message>>)> {
+        let slots: VecDeque<CheckpointSlot> =
+            serde_json::from_slice(data).context("failed to parse checkpoint")?;
+        let restored = slots
+            .iter()
+            .map(|slot| Ok(Arc::new(Message::Slot(slot.to_message()?))))
+            .collect::<anyhow::Result<_>>()?;
+        Ok((slots, restored))
+    }
+
+    /// Saves slot update handled by the plugin
+    pub fn update(&self, msg: &MessageSlot) {
+        let mut inner = self.inner.lock().expect("alive mutex");
+        if inner.slots.len() == self.config.slots {
+            inner.slots.pop_front();
+        }
+        inner.slots.push_back(msg.into());
+        inner.dirty = true;
+    }
+
+    /// Latest slot with the status, including slots from the previous run
+    pub fn get_last_slot(&self, commitment: CommitmentLevel) -> Option<u64> {
+        let inner = self.inner.lock().expect("alive mutex");
+        inner
+            .slots
+            .iter()
+            .filter(|slot| slot.status == commitment as i32)
+            .map(|slot| slot.slot)
+            .max()
+    }
+
+    /// Slot updates from the previous run starting from `from_slot`
+    pub fn get_restored(&self, from_slot: u64) -> Vec<Arc<Message>> {
+        self.restored
+            .iter()
+            .filter(|message| message.get_slot() >= from_slot)
+            .cloned()
+            .collect()
+    }
+
+    pub async fn save(&self) -> anyhow::Result<()> {
+        let data = {
+            let mut inner = self.inner.lock().expect("alive mutex");
+            if !inner.dirty {
+                return Ok(());
+            }
+            inner.dirty = false;
+            serde_json::to_vec(&inner.slots).context("failed to serialize checkpoint")?
+        };
+
+        // write to temporary file first, so checkpoint is never partially written
+        let mut path_tmp = self.config.path.clone().into_os_string();
+        path_tmp.push(".tmp");
+        fs::write(&path_tmp, data)
+            .await
+            .with_context(|| format!("failed to write checkpoint to {path_tmp:?}"))?;
+        fs::rename(&path_tmp, &self.config.path)
+            .await
+            .with_context(|| format!("failed to rename {path_tmp:?}"))
+    }
+
+    pub async fn save_loop(self: Arc<Self>) {
+        loop {
+            sleep(Duration::from_secs(self.config.save_interval)).await;
+            if let Err(error) = self.save().await {
+                self.inner.lock().expect("alive mutex").dirty = true;
+                error!("failed to save checkpoint: {error:?}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::SlotCheckpoint,
+        crate::{
+            config::ConfigGrpcCheckpoint,
+            grpc::{MessageCreatedAt, MessageSlot},
+        },
+        yellowstone_grpc_proto::prelude::CommitmentLevel,
+    };
+
+    fn create_config(name: &str, slots: usize) -> ConfigGrpcCheckpoint {
+        let path = std::env::temp_dir().join(format!(
+            "yellowstone-grpc-geyser-checkpoint-{name}-{}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        ConfigGrpcCheckpoint {
+            path,
+            slots,
+            save_interval: 1,
+        }
+    }
+
+    fn create_slot_message(slot: u64, status: CommitmentLevel) -> MessageSlot {
+        MessageSlot {
+            slot,
+            parent: slot.checked_sub(1),
+            status,
+            dead: false,
+            created_at: MessageCreatedAt::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_persisted() {
+        let config = create_config("persisted", 3);
+        let checkpoint = SlotCheckpoint::load(config.clone()).await.unwrap();
+        assert_eq!(checkpoint.get_last_slot(CommitmentLevel::Processed), None);
+        for (slot, status) in [
+            (10, CommitmentLevel::Processed),
+            (11, CommitmentLevel::Processed),
+            (10, CommitmentLevel::Confirmed),
+            (12, CommitmentLevel::Processed),
+        ] {
+            checkpoint.update(&create_slot_message(slot, status));
+        }
+        checkpoint.save().await.unwrap();
+
+        let checkpoint = SlotCheckpoint::load(config.clone()).await.unwrap();
+        assert_eq!(
+            checkpoint.get_last_slot(CommitmentLevel::Processed),
+            Some(12)
+        );
+        assert_eq!(
+            checkpoint.get_last_slot(CommitmentLevel::Confirmed),
+            Some(10)
+        );
+        assert_eq!(checkpoint.get_last_slot(CommitmentLevel::Finalized), None);
+        let slots = checkpoint
+            .get_restored(11)
+            .iter()
+            .map(|message| message.get_slot())
+            .collect::<Vec<_>>();
+        assert_eq!(slots, vec![11, 12]);
+
+        // window is reduced on load
+        let config_reduced = ConfigGrpcCheckpoint {
+            slots: 1,
+            ..config.clone()
+        };
+        let checkpoint = SlotCheckpoint::load(config_reduced).await.unwrap();
+        assert_eq!(checkpoint.get_restored(0).len(), 1);
+        assert_eq!(checkpoint.get_last_slot(CommitmentLevel::Confirmed), None);
+        std::fs::remove_file(&config.path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_invalid_config() {
+        let mut config = create_config("invalid", 0);
+        assert!(SlotCheckpoint::load(config.clone()).await.is_err());
+        config.slots = 10;
+        config.save_interval = 0;
+        assert!(SlotCheckpoint::load(config).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_corrupted() {
+        let config = create_config("corrupted", 10);
+        for data in [
+            r#"[{"slot":10,"parent":9,"status":0,"dead":false},{"slot":11,"#,
+            r#"[{"slot":10,"parent":9,"status":7,"dead":false}]"#,
+            "",
+        ] {
+            std::fs::write(&config.path, data).unwrap();
+            let checkpoint = SlotCheckpoint::load(config.clone()).await.unwrap();
+            assert_eq!(checkpoint.get_last_slot(CommitmentLevel::Processed), None);
+            assert!(checkpoint.get_restored(0).is_empty());
+        }
+        std::fs::remove_file(&config.path).unwrap();
+    }
+}
@@ -0,0 +1,333 @@
+use {
+    crate::prom::ACCOUNTS_COALESCED_TOTAL,
+    std::{collections::HashMap, mem, time::Duration},
+    tokio::time::Instant,
+    yellowstone_grpc_proto::prelude::{
+        subscribe_update::UpdateOneof, AccountLifecycle, SubscribeUpdate,
+    },
+};
+
+/// (pubkey, filters) of the pending update
+type PendingKey = (Vec<u8>, Vec<String>);
+
+/// Account update and time when its message was received by the plugin
+pub type PendingUpdate = (SubscribeUpdate, Instant);
+
+/// Keeps only the latest account update per pubkey and filters until the window is closed
+#[derive(Debug, Default)]
+pub struct AccountsCoalesce {
+    deadline: Option<Instant>,
+    // one write can produce several updates with different filters (e.g. with and without data)
+    pending: HashMap<PendingKey, PendingUpdate>,
+    // number of updates replaced by newer state since the last `take_dropped`
+    dropped: usize,
+}
+
+impl AccountsCoalesce {
+    // (pubkey, slot, write_version) of the account update
+    fn get_key(update: &SubscribeUpdate) -> Option<(&[u8], u64, u64)> {
+        match &update.update_oneof {
+            Some(UpdateOneof::Account(msg)) => msg
+                .account
+                .as_ref()
+                .map(|account| (account.pubkey.as_slice(), msg.slot, account.write_version)),
+            _ => None,
+        }
+    }
+
+    const fn is_created(update: &SubscribeUpdate) -> bool {
+        match &update.update_oneof {
+            Some(UpdateOneof::Account(msg)) => msg.lifecycle == AccountLifecycle::Created as i32,
+            _ => false,
+        }
+    }
+
+    fn set_created(update: &mut SubscribeUpdate) {
+        if let Some(UpdateOneof::Account(msg)) = &mut update.update_oneof {
+            if msg.lifecycle == AccountLifecycle::Modified as i32 {
+                msg.lifecycle = AccountLifecycle::Created as i32;
+            }
+        }
+    }
+
+    fn get_order(update: &SubscribeUpdate) -> (u64, u64) {
+        Self::get_key(update).map_or((0, 0), |(_pubkey, slot, write_version)| {
+            (slot, write_version)
+        })
+    }
+
+    pub const fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Saves account update, returns update back if it's not an account update
+    pub fn push(
+        &mut self,
+        window: Duration,
+        update: SubscribeUpdate,
+        created_at: Instant,
+    ) -> Option<SubscribeUpdate> {
+        let Some((pubkey, slot, write_version)) = Self::get_key(&update) else {
+            return Some(update);
+        };
+
+        let key = (pubkey.to_vec(), update.filters.clone());
+        match self.pending.get_mut(&key) {
+            Some((pending, pending_created_at)) => {
+                // never replace newer state with older one
+                if Self::get_order(pending) < (slot, write_version) {
+                    let created = Self::is_created(pending);
+                    *pending = update;
+                    *pending_created_at = created_at;
+                    // account created within the window is still reported as created
+                    if created {
+                        Self::set_created(pending);
+                    }
+                }
+                ACCOUNTS_COALESCED_TOTAL.inc();
+                self.dropped += 1;
+            }
+            None => {
+                self.pending.insert(key, (update, created_at));
+            }
+        }
+        if self.deadline.is_none() {
+            self.deadline = Some(Instant::now() + window);
+        }
+        None
+    }
+
+    /// Drops pending update of the account, if newer state is sent bypassing coalescing
+    pub fn remove(&mut self, pubkey: &[u8], filters: &[String]) {
+        if self
+            .pending
+            .remove(&(pubkey.to_vec(), filters.to_vec()))
+            .is_some()
+        {
+            ACCOUNTS_COALESCED_TOTAL.inc();
+            self.dropped += 1;
+        }
+    }
+
+    /// Number of dropped updates, so the client loop can account them in `seq`
+    pub fn take_dropped(&mut self) -> usize {
+        mem::take(&mut self.dropped)
+    }
+
+    /// Removes all pending updates, called once window is closed
+    pub fn flush(&mut self) -> Vec<PendingUpdate> {
+        self.deadline = None;
+        Self::sorted(mem::take(&mut self.pending).into_values().collect())
+    }
+
+    /// Removes pending updates up to the slot, so accounts are always sent before slot status
+    pub fn flush_slot(&mut self, slot: u64) -> Vec<PendingUpdate> {
+        if self.pending.is_empty() {
+            return vec![];
+        }
+
+        let mut updates = vec![];
+        self.pending.retain(|_key, (update, created_at)| {
+            if Self::get_order(update).0 <= slot {
+                updates.push((mem::take(update), *created_at));
+                false
+            } else {
+                true
+            }
+        });
+        if self.pending.is_empty() {
+            self.deadline = None;
+        }
+        Self::sorted(updates)
+    }
+
+    fn sorted(mut updates: Vec<PendingUpdate>) -> Vec<PendingUpdate> {
+        updates.sort_by_key(|(update, _created_at)| Self::get_order(update));
+        updates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{AccountsCoalesce, PendingUpdate},
+        std::time::Duration,
+        tokio::time::Instant,
+        yellowstone_grpc_proto::prelude::{
+            subscribe_update::UpdateOneof, AccountLifecycle, SubscribeUpdate,
+            SubscribeUpdateAccount, SubscribeUpdateAccountInfo, SubscribeUpdateSlot,
+        },
+    };
+
+    fn create_update(pubkey: u8, slot: u64, write_version: u64) -> SubscribeUpdate {
+        create_update_filters(pubkey, slot, write_version, "accounts")
+    }
+
+    fn create_update_filters(
+        pubkey: u8,
+        slot: u64,
+        write_version: u64,
+        filter: &str,
+    ) -> SubscribeUpdate {
+        SubscribeUpdate {
+            filters: vec![filter.to_owned()],
+            update_oneof: Some(UpdateOneof::Account(SubscribeUpdateAccount {
+                account: Some(SubscribeUpdateAccountInfo {
+                    pubkey: vec![pubkey; 32],
+                    write_version,
+                    ..Default::default()
+                }),
+                slot,
+                is_startup: false,
+                lifecycle: AccountLifecycle::Modified as i32,
+            })),
+            resume_token: None,
+            created_at_us: None,
+            seq: 0,
+        }
+    }
+
+    fn get_lifecycles(updates: Vec<PendingUpdate>) -> Vec<i32> {
+        updates
+            .into_iter()
+            .map(|(update, _created_at)| match update.update_oneof {
+                Some(UpdateOneof::Account(msg)) => msg.lifecycle,
+                _ => panic!("expected account update"),
+            })
+            .collect()
+    }
+
+    fn get_write_versions(updates: Vec<PendingUpdate>) -> Vec<u64> {
+        updates
+            .iter()
+            .map(|(update, _created_at)| AccountsCoalesce::get_order(update).1)
+            .collect()
+    }
+
+    #[test]
+    fn test_coalesce_burst_to_latest() {
+        let window = Duration::from_millis(100);
+        let mut coalesce = AccountsCoalesce::default();
+        assert!(coalesce.deadline().is_none());
+
+        for write_version in 1..=10 {
+            assert!(coalesce
+                .push(window, create_update(1, 100, write_version), Instant::now())
+                .is_none());
+        }
+        assert!(coalesce
+            .push(window, create_update(2, 100, 11), Instant::now())
+            .is_none());
+        // older state is dropped
+        assert!(coalesce
+            .push(window, create_update(1, 100, 5), Instant::now())
+            .is_none());
+        assert!(coalesce.deadline().is_some());
+        assert_eq!(coalesce.take_dropped(), 10);
+        assert_eq!(coalesce.take_dropped(), 0);
+
+        // non account updates are not coalesced
+        let slot = SubscribeUpdate {
+            filters: vec![],
+            update_oneof: Some(UpdateOneof::Slot(SubscribeUpdateSlot::default())),
+            resume_token: None,
+            created_at_us: None,
+            seq: 0,
+        };
+        assert!(coalesce.push(window, slot, Instant::now()).is_some());
+
+        assert_eq!(get_write_versions(coalesce.flush()), vec![10, 11]);
+        assert!(coalesce.deadline().is_none());
+        assert!(coalesce.flush().is_empty());
+    }
+
+    #[test]
+    fn test_coalesce_keeps_created() {
+        let window = Duration::from_millis(100);
+        let mut coalesce = AccountsCoalesce::default();
+        let with_lifecycle = |mut update: SubscribeUpdate, lifecycle: AccountLifecycle| {
+            if let Some(UpdateOneof::Account(msg)) = &mut update.update_oneof {
+                msg.lifecycle = lifecycle as i32;
+            }
+            update
+        };
+
+        // created and modified within the window
+        let created = with_lifecycle(create_update(1, 100, 1), AccountLifecycle::Created);
+        assert!(coalesce.push(window, created, Instant::now()).is_none());
+        assert!(coalesce
+            .push(window, create_update(1, 100, 2), Instant::now())
+            .is_none());
+        assert_eq!(
+            get_lifecycles(coalesce.flush()),
+            vec![AccountLifecycle::Created as i32]
+        );
+
+        // created and closed within the window
+        let created = with_lifecycle(create_update(1, 101, 3), AccountLifecycle::Created);
+        let closed = with_lifecycle(create_update(1, 101, 4), AccountLifecycle::Closed);
+        assert!(coalesce.push(window, created, Instant::now()).is_none());
+        assert!(coalesce.push(window, closed, Instant::now()).is_none());
+        assert_eq!(
+            get_lifecycles(coalesce.flush()),
+            vec![AccountLifecycle::Closed as i32]
+        );
+    }
+
+    #[test]
+    fn test_coalesce_flush_slot() {
+        let window = Duration::from_millis(100);
+        let mut coalesce = AccountsCoalesce::default();
+        assert!(coalesce
+            .push(window, create_update(1, 100, 1), Instant::now())
+            .is_none());
+        assert!(coalesce
+            .push(window, create_update(2, 101, 2), Instant::now())
+            .is_none());
+
+        assert_eq!(get_write_versions(coalesce.flush_slot(100)), vec![1]);
+        assert!(coalesce.deadline().is_some());
+        assert_eq!(get_write_versions(coalesce.flush_slot(101)), vec![2]);
+        assert!(coalesce.deadline().is_none());
+    }
+
+    #[test]
+    fn test_coalesce_filters_groups() {
+        let window = Duration::from_millis(100);
+        let mut coalesce = AccountsCoalesce::default();
+
+        // same write matched by filters with and without data
+        for write_version in 1..=2 {
+            for filter in ["with_data", "without_data"] {
+                let update = create_update_filters(1, 100, write_version, filter);
+                assert!(coalesce.push(window, update, Instant::now()).is_none());
+            }
+        }
+        let mut filters = coalesce
+            .flush()
+            .into_iter()
+            .map(|(update, _created_at)| (AccountsCoalesce::get_order(&update).1, update.filters))
+            .collect::<Vec<_>>();
+        filters.sort();
+        assert_eq!(
+            filters,
+            vec![
+                (2, vec!["with_data".to_owned()]),
+                (2, vec!["without_data".to_owned()])
+            ]
+        );
+
+        // removed only for the same filters
+        for filter in ["with_data", "without_data"] {
+            let update = create_update_filters(1, 101, 3, filter);
+            assert!(coalesce.push(window, update, Instant::now()).is_none());
+        }
+        coalesce.remove(&[1; 32], &["with_data".to_owned()]);
+        let filters = coalesce
+            .flush()
+            .into_iter()
+            .map(|(update, _created_at)| update.filters)
+            .collect::<Vec<_>>();
+        assert_eq!(filters, vec![vec!["without_data".to_owned()]]);
+    }
+}
@@ -4,7 +4,12 @@ use {
     },
     serde::{de, Deserialize, Deserializer},
     solana_sdk::pubkey::Pubkey,
-    std::{collections::HashSet, fs::read_to_string, net::SocketAddr, path::Path},
+    std::{
+        collections::HashSet,
+        fs::read_to_string,
+        net::SocketAddr,
+        path::{Path, PathBuf},
+    },
     tokio::sync::Semaphore,
     tonic::codec::CompressionEncoding,
 };
@@ -65,7 +70,11 @@ impl ConfigLog {
 #[serde(deny_unknown_fields)]
 pub struct ConfigGrpc {
     /// Address of Grpc service.
-    pub address: SocketAddr,
+    #[serde(default)]
+    pub address: Option<SocketAddr>,
+    /// Unix domain socket of Grpc service, can be used with or without `address`
+    #[serde(default)]
+    pub unix_socket: Option<ConfigGrpcUnixSocket>,
     /// TLS config
     pub tls_config: Option<ConfigGrpcServerTls>,
     /// Possible compression options
@@ -77,6 +86,20 @@ pub struct ConfigGrpc {
         deserialize_with = "deserialize_usize_str"
     )]
     pub max_decoding_message_size: usize,
+    /// Limits the maximum size of an encoded message, oversized account updates are
+    /// split to chunks or dropped. Not limited by default
+    #[serde(
+        default = "ConfigGrpc::max_encoding_message_size_default",
+        deserialize_with = "deserialize_usize_str_maybe"
+    )]
+    pub max_encoding_message_size: Option<usize>,
+    /// Number of last messages kept for every commitment level to resume subscriptions
+    /// with `resume_token`. Disabled by default
+    #[serde(
+        default = "ConfigGrpc::replay_buffer_size_default",
+        deserialize_with = "deserialize_usize_str_maybe"
+    )]
+    pub replay_buffer_size: Option<usize>,
     /// Capacity of the channel used for accounts from snapshot,
     /// on reaching the limit Sender block validator startup.
     #[serde(
@@ -105,11 +128,52 @@ pub struct ConfigGrpc {
     /// Enable/disable unary methods
     #[serde(default)]
     pub unary_disabled: bool,
+    /// Number of slots before finalized slot for which full blocks are kept for `GetBlock`,
+    /// every kept block holds all transactions, account writes and entries of the slot in memory
+    /// (plus ~32 not finalized slots), `GetBlock` is disabled if not set
+    #[serde(
+        default = "ConfigGrpc::get_block_keep_slots_default",
+        deserialize_with = "deserialize_usize_str_maybe"
+    )]
+    pub get_block_keep_slots: Option<usize>,
+    /// Max number of last seen account states kept per commitment level to tag account updates
+    /// with lifecycle, updates are not tagged if not set
+    #[serde(
+        default = "ConfigGrpc::accounts_lifecycle_cache_max_default",
+        deserialize_with = "deserialize_usize_str_maybe"
+    )]
+    pub accounts_lifecycle_cache_max: Option<usize>,
     /// Limits for possible filters
     #[serde(default)]
     pub filters: ConfigGrpcFilters,
     /// x_token to enforce on connections
     pub x_token: Option<String>,
+    /// Token for admin methods passed in `x-admin-token` header, methods are disabled if not set
+    pub admin_x_token: Option<String>,
+    /// Bytes quotas per x-token, persisted across restarts
+    #[serde(default)]
+    pub quotas: Option<ConfigGrpcQuotas>,
+    /// Last processed slots persisted across restarts
+    #[serde(default)]
+    pub checkpoint: Option<ConfigGrpcCheckpoint>,
+    /// Limit number of new subscriptions initialized at the same time
+    #[serde(default)]
+    pub admission: Option<ConfigGrpcAdmission>,
+    /// Pause low priority subscriptions while plugin lags behind the validator
+    #[serde(default)]
+    pub shedding: Option<ConfigGrpcShedding>,
+    /// Warn when subscribers fall behind the validator
+    #[serde(default)]
+    pub slot_skew: Option<ConfigGrpcSlotSkew>,
+    /// Spread closing of subscriptions on plugin unload or reload to avoid reconnect storms
+    #[serde(default)]
+    pub reconnect: Option<ConfigGrpcReconnect>,
+    /// JSON over HTTP gateway, requires `json-gateway` feature
+    #[serde(default)]
+    pub json_gateway: Option<ConfigGrpcJsonGateway>,
+    /// Enable gRPC server reflection (for `grpcurl` and similar tools), disabled by default
+    #[serde(default)]
+    pub reflection: bool,
 }
 
 impl ConfigGrpc {
@@ -117,6 +181,14 @@ impl ConfigGrpc {
         4 * 1024 * 1024
     }
 
+    const fn max_encoding_message_size_default() -> Option<usize> {
+        None
+    }
+
+    const fn replay_buffer_size_default() -> Option<usize> {
+        None
+    }
+
     const fn snapshot_plugin_channel_capacity_default() -> Option<usize> {
         None
     }
@@ -132,6 +204,221 @@ impl ConfigGrpc {
     const fn unary_concurrency_limit_default() -> usize {
         Semaphore::MAX_PERMITS
     }
+
+    const fn get_block_keep_slots_default() -> Option<usize> {
+        None
+    }
+
+    const fn accounts_lifecycle_cache_max_default() -> Option<usize> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigGrpcUnixSocket {
+    /// Path to the socket file, stale file is removed on bind
+    pub path: PathBuf,
+    /// Socket file permissions as octal string, for example `"660"`
+    #[serde(default, deserialize_with = "deserialize_mode_str_maybe")]
+    pub mode: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigGrpcReconnect {
+    /// Subscriptions are closed at random moments within this window, in milliseconds
+    #[serde(deserialize_with = "deserialize_u64_str")]
+    pub close_window_ms: u64,
+    /// Min value of random `retry-after` hint sent with closing status, in milliseconds
+    #[serde(
+        default = "ConfigGrpcReconnect::retry_after_min_ms_default",
+        deserialize_with = "deserialize_u64_str"
+    )]
+    pub retry_after_min_ms: u64,
+    /// Max value of random `retry-after` hint sent with closing status, in milliseconds
+    #[serde(
+        default = "ConfigGrpcReconnect::retry_after_max_ms_default",
+        deserialize_with = "deserialize_u64_str"
+    )]
+    pub retry_after_max_ms: u64,
+}
+
+impl ConfigGrpcReconnect {
+    /// Plugin unload blocks the validator, so subscriptions should be closed quickly
+    pub const CLOSE_WINDOW_MS_MAX: u64 = 10_000;
+
+    const fn retry_after_min_ms_default() -> u64 {
+        1_000
+    }
+
+    const fn retry_after_max_ms_default() -> u64 {
+        30_000
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigGrpcJsonGateway {
+    /// Address of HTTP service
+    pub address: SocketAddr,
+    /// Encoding of binary fields (pubkeys, signatures, account data) in JSON
+    #[serde(default)]
+    pub bytes_encoding: ConfigGrpcJsonGatewayBytesEncoding,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigGrpcJsonGatewayBytesEncoding {
+    /// Base64 string
+    #[default]
+    Base64,
+    /// Array of numbers
+    Bytes,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigGrpcQuotas {
+    /// Path to the file with usage, created if not exists
+    pub path: PathBuf,
+    /// Max number of bytes sent to one x-token per period
+    #[serde(deserialize_with = "deserialize_u64_str")]
+    pub bytes: u64,
+    /// Period length in seconds, periods are aligned to unix epoch
+    #[serde(deserialize_with = "deserialize_u64_str")]
+    pub period: u64,
+    /// Interval in seconds between usage saves
+    #[serde(
+        default = "ConfigGrpcQuotas::save_interval_default",
+        deserialize_with = "deserialize_u64_str"
+    )]
+    pub save_interval: u64,
+}
+
+impl ConfigGrpcQuotas {
+    const fn save_interval_default() -> u64 {
+        10
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigGrpcCheckpoint {
+    /// Path to the file with slots, created if not exists, corrupted file is ignored
+    pub path: PathBuf,
+    /// Number of last slot updates kept in the file
+    #[serde(
+        default = "ConfigGrpcCheckpoint::slots_default",
+        deserialize_with = "deserialize_usize_str"
+    )]
+    pub slots: usize,
+    /// Interval in seconds between saves, file is also saved on shutdown
+    #[serde(
+        default = "ConfigGrpcCheckpoint::save_interval_default",
+        deserialize_with = "deserialize_u64_str"
+    )]
+    pub save_interval: u64,
+}
+
+impl ConfigGrpcCheckpoint {
+    const fn slots_default() -> usize {
+        1_000
+    }
+
+    const fn save_interval_default() -> u64 {
+        10
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigGrpcAdmission {
+    /// Max number of subscriptions waiting for the first filter at the same time
+    #[serde(deserialize_with = "deserialize_usize_str")]
+    pub max_concurrent: usize,
+    /// Max number of new subscriptions waiting for admission, others are rejected
+    #[serde(
+        default = "ConfigGrpcAdmission::queue_max_default",
+        deserialize_with = "deserialize_usize_str"
+    )]
+    pub queue_max: usize,
+    /// Max time to wait for admission, in milliseconds
+    #[serde(
+        default = "ConfigGrpcAdmission::queue_timeout_ms_default",
+        deserialize_with = "deserialize_u64_str"
+    )]
+    pub queue_timeout_ms: u64,
+    /// Max time subscription keeps the permit while waiting for the first filter, in milliseconds
+    #[serde(
+        default = "ConfigGrpcAdmission::init_timeout_ms_default",
+        deserialize_with = "deserialize_u64_str"
+    )]
+    pub init_timeout_ms: u64,
+}
+
+impl ConfigGrpcAdmission {
+    const fn queue_max_default() -> usize {
+        1_000
+    }
+
+    const fn queue_timeout_ms_default() -> u64 {
+        5_000
+    }
+
+    const fn init_timeout_ms_default() -> u64 {
+        5_000
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigGrpcSlotSkew {
+    /// Warn when processed slot sent to the fastest subscriber lags by more than this number of slots
+    #[serde(deserialize_with = "deserialize_u64_str")]
+    pub warn_slots: u64,
+    /// Warn only if skew stays over `warn_slots` for this time, in milliseconds
+    #[serde(
+        default = "ConfigGrpcSlotSkew::warn_grace_ms_default",
+        deserialize_with = "deserialize_u64_str"
+    )]
+    pub warn_grace_ms: u64,
+}
+
+impl ConfigGrpcSlotSkew {
+    const fn warn_grace_ms_default() -> u64 {
+        10_000
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigGrpcShedding {
+    /// Start shedding when processed slot in the plugin lags by more than this number of slots
+    #[serde(deserialize_with = "deserialize_u64_str")]
+    pub lag_slots: u64,
+    /// Stop shedding when lag is not more than this number of slots
+    #[serde(deserialize_with = "deserialize_u64_str")]
+    pub recover_lag_slots: u64,
+    /// Subscriptions with these x-tokens are never shed
+    #[serde(default)]
+    pub high_priority_x_tokens: HashSet<String>,
+    /// Low priority subscriptions are closed at random moments within this window, in
+    /// milliseconds
+    #[serde(
+        default = "ConfigGrpcShedding::close_window_ms_default",
+        deserialize_with = "deserialize_u64_str"
+    )]
+    pub close_window_ms: u64,
+}
+
+impl ConfigGrpcShedding {
+    /// Shedding should release the plugin quickly, otherwise it falls further behind
+    pub const CLOSE_WINDOW_MS_MAX: u64 = 10_000;
+
+    const fn close_window_ms_default() -> u64 {
+        1_000
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -198,9 +485,28 @@ pub struct ConfigGrpcFilters {
     pub blocks: ConfigGrpcFiltersBlocks,
     pub blocks_meta: ConfigGrpcFiltersBlocksMeta,
     pub entry: ConfigGrpcFiltersEntry,
+    /// Update types allowed for subscriptions, all types are enabled if not set
+    pub enabled_update_types: Option<HashSet<ConfigGrpcFiltersUpdateType>>,
 }
 
 impl ConfigGrpcFilters {
+    pub fn check_update_type(
+        &self,
+        update_type: ConfigGrpcFiltersUpdateType,
+        len: usize,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            len == 0
+                || self
+                    .enabled_update_types
+                    .as_ref()
+                    .map_or(true, |types| types.contains(&update_type)),
+            "`{}` updates are disabled by the server",
+            update_type.as_str()
+        );
+        Ok(())
+    }
+
     pub fn check_max(len: usize, max: usize) -> anyhow::Result<()> {
         anyhow::ensure!(
             len <= max,
@@ -237,6 +543,32 @@ impl ConfigGrpcFilters {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigGrpcFiltersUpdateType {
+    Accounts,
+    Slots,
+    Transactions,
+    TransactionsStatus,
+    Blocks,
+    BlocksMeta,
+    Entry,
+}
+
+impl ConfigGrpcFiltersUpdateType {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Accounts => "accounts",
+            Self::Slots => "slots",
+            Self::Transactions => "transactions",
+            Self::TransactionsStatus => "transactions_status",
+            Self::Blocks => "blocks",
+            Self::BlocksMeta => "blocks_meta",
+            Self::Entry => "entry",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct ConfigGrpcFiltersAccounts {
@@ -248,6 +580,29 @@ pub struct ConfigGrpcFiltersAccounts {
     pub owner_max: usize,
     #[serde(deserialize_with = "deserialize_pubkey_set")]
     pub owner_reject: HashSet<Pubkey>,
+    /// Max number of last seen owners kept per subscription for `owner_change_only`
+    #[serde(deserialize_with = "deserialize_usize_str")]
+    pub owner_change_cache_max: usize,
+    /// Max number of last sent values kept per filter for `changed_range`
+    #[serde(deserialize_with = "deserialize_usize_str")]
+    pub changed_range_cache_max: usize,
+    /// Max `length` of `changed_range`
+    #[serde(deserialize_with = "deserialize_usize_str")]
+    pub changed_range_length_max: usize,
+    /// Max window (in milliseconds) for coalescing account updates
+    #[serde(deserialize_with = "deserialize_u64_str")]
+    pub coalesce_window_max_ms: u64,
+    /// Validate filters named `preset:<name>` against known presets, names are not reserved if disabled
+    pub presets: bool,
+    /// Set from `grpc.accounts_lifecycle_cache_max`, `lifecycle` filter is rejected if updates
+    /// are not tagged
+    #[serde(skip)]
+    pub lifecycle: bool,
+}
+
+impl ConfigGrpcFiltersAccounts {
+    // explicit list of pubkeys is kept per filter, owner filters are not limited by default
+    pub const ACCOUNT_MAX_DEFAULT: usize = 10_000;
 }
 
 impl Default for ConfigGrpcFiltersAccounts {
@@ -255,10 +610,16 @@ impl Default for ConfigGrpcFiltersAccounts {
         Self {
             max: usize::MAX,
             any: true,
-            account_max: usize::MAX,
+            account_max: Self::ACCOUNT_MAX_DEFAULT,
             account_reject: HashSet::new(),
             owner_max: usize::MAX,
             owner_reject: HashSet::new(),
+            owner_change_cache_max: 100_000,
+            changed_range_cache_max: 100_000,
+            changed_range_length_max: 1_024,
+            coalesce_window_max_ms: 10_000,
+            presets: false,
+            lifecycle: true,
         }
     }
 }
@@ -290,6 +651,8 @@ pub struct ConfigGrpcFiltersTransactions {
     pub account_exclude_max: usize,
     #[serde(deserialize_with = "deserialize_usize_str")]
     pub account_required_max: usize,
+    #[serde(deserialize_with = "deserialize_usize_str")]
+    pub invoked_programs_max: usize,
 }
 
 impl Default for ConfigGrpcFiltersTransactions {
@@ -301,6 +664,7 @@ impl Default for ConfigGrpcFiltersTransactions {
             account_include_reject: HashSet::new(),
             account_exclude_max: usize::MAX,
             account_required_max: usize::MAX,
+            invoked_programs_max: usize::MAX,
         }
     }
 }
@@ -400,6 +764,26 @@ where
     }
 }
 
+fn deserialize_u64_str<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Value {
+        Integer(u64),
+        String(String),
+    }
+
+    match Value::deserialize(deserializer)? {
+        Value::Integer(value) => Ok(value),
+        Value::String(value) => value
+            .replace('_', "")
+            .parse::<u64>()
+            .map_err(de::Error::custom),
+    }
+}
+
 fn deserialize_usize_str_maybe<'de, D>(deserializer: D) -> Result<Option<usize>, D::Error>
 where
     D: Deserializer<'de>,
@@ -422,6 +806,18 @@ where
     }
 }
 
+fn deserialize_mode_str_maybe<'de, D>(deserializer: D) -> Result<Option<u32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|value| {
+            u32::from_str_radix(value.trim_start_matches("0o"), 8)
+                .map_err(|error| de::Error::custom(format!("Invalid mode: {value} ({error})")))
+        })
+        .transpose()
+}
+
 fn deserialize_pubkey_set<'de, D>(deserializer: D) -> Result<HashSet<Pubkey>, D::Error>
 where
     D: Deserializer<'de>,
@@ -1,30 +1,44 @@
 use {
     crate::{
+        cache::GenerationCache,
         config::{
             ConfigGrpcFilters, ConfigGrpcFiltersAccounts, ConfigGrpcFiltersBlocks,
             ConfigGrpcFiltersBlocksMeta, ConfigGrpcFiltersEntry, ConfigGrpcFiltersSlots,
-            ConfigGrpcFiltersTransactions,
+            ConfigGrpcFiltersTransactions, ConfigGrpcFiltersUpdateType,
         },
         grpc::{
-            Message, MessageAccount, MessageBlock, MessageBlockMeta, MessageEntry, MessageRef,
-            MessageSlot, MessageTransaction,
+            Message, MessageAccount, MessageAccountInfo, MessageBlock, MessageBlockMeta,
+            MessageBlockRef, MessageEntry, MessageRef, MessageSlot, MessageTransaction,
+            MessageTransactionFields, MessageTransactionInfo,
         },
+        replay::ResumeToken,
     },
     base64::{engine::general_purpose::STANDARD as base64_engine, Engine},
-    solana_sdk::{pubkey::Pubkey, signature::Signature},
+    solana_sdk::{
+        pubkey::Pubkey,
+        rent_collector::RENT_EXEMPT_RENT_EPOCH,
+        signature::{Signature, SIGNATURE_BYTES},
+    },
     spl_token_2022::{generic_token_account::GenericTokenAccount, state::Account as TokenAccount},
     std::{
-        collections::{HashMap, HashSet},
+        collections::{BTreeMap, HashMap, HashSet},
         str::FromStr,
+        sync::{Arc, Mutex},
+        time::{Duration, UNIX_EPOCH},
     },
-    yellowstone_grpc_proto::prelude::{
-        subscribe_request_filter_accounts_filter::Filter as AccountsFilterDataOneof,
-        subscribe_request_filter_accounts_filter_memcmp::Data as AccountsFilterMemcmpOneof,
-        subscribe_update::UpdateOneof, CommitmentLevel, SubscribeRequest,
-        SubscribeRequestAccountsDataSlice, SubscribeRequestFilterAccounts,
-        SubscribeRequestFilterAccountsFilter, SubscribeRequestFilterBlocks,
-        SubscribeRequestFilterBlocksMeta, SubscribeRequestFilterEntry, SubscribeRequestFilterSlots,
-        SubscribeRequestFilterTransactions, SubscribeUpdate, SubscribeUpdatePong,
+    yellowstone_grpc_proto::{
+        prelude::{
+            subscribe_request_filter_accounts_filter::Filter as AccountsFilterDataOneof,
+            subscribe_request_filter_accounts_filter_memcmp::Data as AccountsFilterMemcmpOneof,
+            subscribe_update::UpdateOneof, AccountLifecycle, CommitmentLevel, SubscribeRequest,
+            SubscribeRequestAccountsDataSlice, SubscribeRequestFilterAccounts,
+            SubscribeRequestFilterAccountsFilter, SubscribeRequestFilterAccountsFilterRentEpoch,
+            SubscribeRequestFilterBlocks, SubscribeRequestFilterBlocksMeta,
+            SubscribeRequestFilterEntry, SubscribeRequestFilterSlots,
+            SubscribeRequestFilterTransactions, SubscribeUpdate, SubscribeUpdateFilterAck,
+            SubscribeUpdatePong,
+        },
+        presets::{FilterPreset, FILTER_NAME_PREFIX},
     },
 };
 
@@ -39,14 +53,80 @@ pub struct Filter {
     blocks_meta: FilterBlocksMeta,
     commitment: CommitmentLevel,
     accounts_data_slice: Vec<FilterAccountsDataSlice>,
+    accounts_coalesce: Option<Duration>,
+    accounts_chunking: bool,
+    blocks_chunking: bool,
+    include_created_at: bool,
+    accounts_sample: Option<FilterAccountsSample>,
     ping: Option<i32>,
+    ack: Option<SubscribeUpdateFilterAck>,
+    resume_token: Option<ResumeToken>,
 }
 
 impl Filter {
     pub fn new(config: &SubscribeRequest, limit: &ConfigGrpcFilters) -> anyhow::Result<Self> {
+        for (update_type, len) in [
+            (ConfigGrpcFiltersUpdateType::Accounts, config.accounts.len()),
+            (ConfigGrpcFiltersUpdateType::Slots, config.slots.len()),
+            (
+                ConfigGrpcFiltersUpdateType::Transactions,
+                config.transactions.len(),
+            ),
+            (
+                ConfigGrpcFiltersUpdateType::TransactionsStatus,
+                config.transactions_status.len(),
+            ),
+            (ConfigGrpcFiltersUpdateType::Blocks, config.blocks.len()),
+            (
+                ConfigGrpcFiltersUpdateType::BlocksMeta,
+                config.blocks_meta.len(),
+            ),
+            (ConfigGrpcFiltersUpdateType::Entry, config.entry.len()),
+        ] {
+            limit.check_update_type(update_type, len)?;
+        }
+
+        let slots = FilterSlots::new(&config.slots, &limit.slots)?;
+        anyhow::ensure!(
+            !slots.has_range_end()
+                || (config.accounts.is_empty()
+                    && config.transactions.is_empty()
+                    && config.transactions_status.is_empty()
+                    && config.blocks.is_empty()
+                    && config.blocks_meta.is_empty()
+                    && config.entry.is_empty()),
+            "slots filters with `to_slot` can't be combined with other filters, subscription is closed once the range is ended"
+        );
+
+        let commitment = Self::decode_commitment(config.commitment)?;
+        let accounts = FilterAccounts::new(&config.accounts, &limit.accounts)?;
+        anyhow::ensure!(
+            accounts.first_write_only.is_empty() || commitment == CommitmentLevel::Processed,
+            "`first_write_only` is allowed only with `processed` commitment, for `confirmed` and `finalized` only last write is sent"
+        );
+        if let Some(window) = config.accounts_coalesce_window_ms {
+            anyhow::ensure!(
+                window > 0 && window <= limit.accounts.coalesce_window_max_ms,
+                "`accounts_coalesce_window_ms` should be between 1 and {}",
+                limit.accounts.coalesce_window_max_ms
+            );
+        }
+
+        let resume_token = config
+            .resume_token
+            .as_deref()
+            .map(ResumeToken::from_str)
+            .transpose()?;
+        if let Some(resume_token) = resume_token {
+            anyhow::ensure!(
+                resume_token.commitment == commitment,
+                "`resume_token` should be issued for the same commitment level"
+            );
+        }
+
         Ok(Self {
-            accounts: FilterAccounts::new(&config.accounts, &limit.accounts)?,
-            slots: FilterSlots::new(&config.slots, &limit.slots)?,
+            accounts,
+            slots,
             transactions: FilterTransactions::new(
                 &config.transactions,
                 &limit.transactions,
@@ -60,12 +140,136 @@ impl Filter {
             entry: FilterEntry::new(&config.entry, &limit.entry)?,
             blocks: FilterBlocks::new(&config.blocks, &limit.blocks)?,
             blocks_meta: FilterBlocksMeta::new(&config.blocks_meta, &limit.blocks_meta)?,
-            commitment: Self::decode_commitment(config.commitment)?,
+            commitment,
             accounts_data_slice: FilterAccountsDataSlice::create(&config.accounts_data_slice)?,
+            accounts_coalesce: config
+                .accounts_coalesce_window_ms
+                .map(Duration::from_millis),
+            accounts_chunking: config.accounts_chunking.unwrap_or(false),
+            blocks_chunking: config.blocks_chunking.unwrap_or(false),
+            include_created_at: config.include_created_at.unwrap_or(false),
+            accounts_sample: config
+                .accounts_sample_rate
+                .map(FilterAccountsSample::new)
+                .transpose()?,
             ping: config.ping.as_ref().map(|msg| msg.id),
+            ack: (config.filter_ack.unwrap_or(true) && config.ping.is_none())
+                .then(|| Self::create_ack(config, commitment)),
+            resume_token,
         })
     }
 
+    fn create_ack(
+        config: &SubscribeRequest,
+        commitment: CommitmentLevel,
+    ) -> SubscribeUpdateFilterAck {
+        let mut ignored = vec![];
+        let mut filter = config.clone();
+        filter.commitment = Some(commitment as i32);
+        filter.ping = None;
+        filter.commit = None;
+        filter.accounts_chunking.get_or_insert(false);
+        filter.blocks_chunking.get_or_insert(false);
+        filter.include_created_at.get_or_insert(false);
+        filter.filter_ack = Some(true);
+
+        for filter in filter.accounts.values_mut() {
+            filter.first_write_only.get_or_insert(false);
+            filter.include_account_data.get_or_insert(true);
+            filter.owner_change_only.get_or_insert(false);
+        }
+        if config.accounts.is_empty() {
+            for (field, is_set) in [
+                (
+                    "accounts_data_slice",
+                    !config.accounts_data_slice.is_empty(),
+                ),
+                (
+                    "accounts_coalesce_window_ms",
+                    config.accounts_coalesce_window_ms.is_some(),
+                ),
+                (
+                    "accounts_sample_rate",
+                    config.accounts_sample_rate.is_some(),
+                ),
+                ("accounts_chunking", config.accounts_chunking == Some(true)),
+            ] {
+                if is_set {
+                    ignored.push(format!("{field}: no accounts filters"));
+                }
+            }
+        } else if !config.accounts_data_slice.is_empty()
+            && config
+                .accounts
+                .values()
+                .all(|filter| filter.include_account_data == Some(false))
+        {
+            ignored.push(
+                "accounts_data_slice: account data is not included by any accounts filter"
+                    .to_owned(),
+            );
+        }
+
+        for (name, filter) in filter.slots.iter_mut() {
+            filter.filter_by_commitment.get_or_insert(false);
+            if filter.include_dead == Some(true) && commitment != CommitmentLevel::Processed {
+                ignored.push(format!(
+                    "slots.{name}.include_dead: dead slots are sent only with `processed` commitment"
+                ));
+            }
+            filter.include_dead.get_or_insert(false);
+        }
+
+        for filter in filter.transactions.values_mut() {
+            filter.include_transaction_message.get_or_insert(true);
+            filter.include_meta.get_or_insert(true);
+            filter.include_logs.get_or_insert(true);
+            filter.include_inner_instructions.get_or_insert(true);
+            filter.include_account_keys.get_or_insert(true);
+            filter.include_balances.get_or_insert(true);
+            filter.include_return_data.get_or_insert(true);
+        }
+        for (name, filter) in filter.transactions_status.iter() {
+            for (field, value) in [
+                (
+                    "include_transaction_message",
+                    filter.include_transaction_message,
+                ),
+                ("include_meta", filter.include_meta),
+                ("include_logs", filter.include_logs),
+                (
+                    "include_inner_instructions",
+                    filter.include_inner_instructions,
+                ),
+                ("include_account_keys", filter.include_account_keys),
+                ("include_balances", filter.include_balances),
+                ("include_return_data", filter.include_return_data),
+            ] {
+                if value.is_some() {
+                    ignored.push(format!(
+                        "transactions_status.{name}.{field}: status updates do not contain transaction"
+                    ));
+                }
+            }
+        }
+
+        for filter in filter.blocks.values_mut() {
+            filter.include_transactions.get_or_insert(true);
+            filter.include_accounts.get_or_insert(false);
+            filter.include_entries.get_or_insert(false);
+            filter.include_rewards.get_or_insert(true);
+        }
+        if config.blocks.is_empty() && config.blocks_chunking == Some(true) {
+            ignored.push("blocks_chunking: no blocks filters".to_owned());
+        }
+
+        ignored.sort();
+        SubscribeUpdateFilterAck {
+            filter: Some(filter),
+            ignored,
+        }
+    }
+
     fn decode_commitment(commitment: Option<i32>) -> anyhow::Result<CommitmentLevel> {
         let commitment = commitment.unwrap_or(CommitmentLevel::Processed as i32);
         CommitmentLevel::try_from(commitment).map_err(|_error| {
@@ -125,14 +329,49 @@ impl Filter {
         self.commitment
     }
 
+    pub const fn get_accounts_coalesce(&self) -> Option<Duration> {
+        self.accounts_coalesce
+    }
+
+    pub const fn get_resume_token(&self) -> Option<ResumeToken> {
+        self.resume_token
+    }
+
+    /// Min `from_slot` of slots filters, only if every slots filter has it
+    pub fn get_slots_from_slot(&self) -> Option<u64> {
+        self.slots.get_from_slot()
+    }
+
+    pub const fn get_accounts_chunking(&self) -> bool {
+        self.accounts_chunking
+    }
+
+    pub const fn get_blocks_chunking(&self) -> bool {
+        self.blocks_chunking
+    }
+
+    pub const fn get_include_created_at(&self) -> bool {
+        self.include_created_at
+    }
+
     pub fn get_filters<'a>(
         &'a self,
         message: &'a Message,
         commitment: Option<CommitmentLevel>,
     ) -> Box<dyn Iterator<Item = (Vec<String>, MessageRef<'a>)> + Send + 'a> {
         match message {
-            Message::Account(message) => self.accounts.get_filters(message),
-            Message::Slot(message) => self.slots.get_filters(message, commitment),
+            Message::Account(message) => match &self.accounts_sample {
+                Some(sample) if !sample.contains(&message.account.pubkey) => {
+                    Box::new(std::iter::empty())
+                }
+                _ => self.accounts.get_filters(message),
+            },
+            Message::Slot(message) => {
+                if message.status == CommitmentLevel::Finalized {
+                    self.accounts.remove_first_write_outdated(message.slot);
+                }
+                self.slots.get_filters(message, commitment)
+            }
             Message::Transaction(message) => Box::new(
                 self.transactions
                     .get_filters(message)
@@ -149,36 +388,120 @@ impl Filter {
         message: &'a Message,
         commitment: Option<CommitmentLevel>,
     ) -> Box<dyn Iterator<Item = SubscribeUpdate> + Send + 'a> {
+        let created_at_us = self.include_created_at.then(|| {
+            message
+                .get_created_at()
+                .system
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |since| since.as_micros() as u64)
+        });
         Box::new(
             self.get_filters(message, commitment)
-                .filter_map(|(filters, message)| {
+                .filter_map(move |(filters, message)| {
                     if filters.is_empty() {
                         None
                     } else {
                         Some(SubscribeUpdate {
                             filters,
                             update_oneof: Some(message.to_proto(&self.accounts_data_slice)),
+                            resume_token: None,
+                            created_at_us,
+                            seq: 0,
                         })
                     }
                 }),
         )
     }
 
+    /// Subscription should be closed once slots range is ended, such subscription has only slots
+    /// filters
+    pub fn is_slots_range_ended(&self, message: &Message) -> bool {
+        match message {
+            Message::Slot(message) => self.slots.is_range_ended(message),
+            _ => false,
+        }
+    }
+
+    pub fn get_ack_msg(&self, chunking_supported: bool) -> Option<SubscribeUpdate> {
+        self.ack.as_ref().map(|ack| {
+            let mut ack = ack.clone();
+            if self.accounts_chunking && !chunking_supported && !self.accounts.filters.is_empty() {
+                ack.ignored.push(
+                    "accounts_chunking: message size is not limited by the server".to_owned(),
+                );
+                ack.ignored.sort();
+            }
+            if self.blocks_chunking && !chunking_supported && !self.blocks.filters.is_empty() {
+                ack.ignored
+                    .push("blocks_chunking: message size is not limited by the server".to_owned());
+                ack.ignored.sort();
+            }
+            SubscribeUpdate {
+                filters: vec![],
+                update_oneof: Some(UpdateOneof::FilterAck(ack)),
+                resume_token: None,
+                created_at_us: None,
+                seq: 0,
+            }
+        })
+    }
+
     pub fn get_pong_msg(&self) -> Option<SubscribeUpdate> {
         self.ping.map(|id| SubscribeUpdate {
             filters: vec![],
             update_oneof: Some(UpdateOneof::Pong(SubscribeUpdatePong { id })),
+            resume_token: None,
+            created_at_us: None,
+            seq: 0,
         })
     }
 }
 
+// slot => pubkey => names of filters with already sent first write
+type FilterAccountsFirstWrite = BTreeMap<u64, HashMap<Pubkey, HashSet<String>>>;
+
+// pubkey => last seen owner
+type FilterAccountsOwners = GenerationCache<Pubkey, Pubkey>;
+
+// (pubkey, filter index) => data length and bytes of the range in the last sent update
+type FilterAccountsChangedRanges = GenerationCache<(Pubkey, usize), (usize, Vec<u8>)>;
+
+#[derive(Debug, Clone, Copy)]
+struct FilterAccountsChangedRange {
+    index: usize,
+    offset: usize,
+    length: usize,
+}
+
+impl FilterAccountsChangedRange {
+    // Data length is clamped to the range end, so only shrunk data which cuts the range is
+    // reported as changed, even if the range is already past the end of the data
+    fn get<'a>(&self, data: &'a [u8]) -> (usize, &'a [u8]) {
+        let len = data.len().min(self.offset.saturating_add(self.length));
+        let bytes = data
+            .get(self.offset..)
+            .map_or(&[][..], |data| &data[..data.len().min(self.length)]);
+        (len, bytes)
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 struct FilterAccounts {
     filters: Vec<(String, FilterAccountsData)>,
-    account: HashMap<Pubkey, HashSet<String>>,
-    account_required: HashSet<String>,
-    owner: HashMap<Pubkey, HashSet<String>>,
-    owner_required: HashSet<String>,
+    memcmp: FilterAccountsMemcmp,
+    // Filters are referenced by index in `filters`, names are not hashed on every update
+    account: HashMap<Pubkey, Vec<usize>>,
+    account_required: Vec<bool>,
+    owner: HashMap<Pubkey, Vec<usize>>,
+    owner_required: Vec<bool>,
+    first_write_only: HashSet<String>,
+    first_write_sent: Arc<Mutex<FilterAccountsFirstWrite>>,
+    without_data: HashSet<String>,
+    owner_change_only: HashSet<String>,
+    owners: Arc<Mutex<FilterAccountsOwners>>,
+    lifecycle: HashMap<String, HashSet<AccountLifecycle>>,
+    changed_range: HashMap<String, FilterAccountsChangedRange>,
+    changed_ranges: Arc<Mutex<FilterAccountsChangedRanges>>,
 }
 
 impl FilterAccounts {
@@ -190,6 +513,18 @@ impl FilterAccounts {
 
         let mut this = Self::default();
         for (name, filter) in configs {
+            if let Some(preset) = name
+                .strip_prefix(FILTER_NAME_PREFIX)
+                .filter(|_| limit.presets)
+            {
+                let preset = FilterPreset::from_name(preset)
+                    .ok_or_else(|| anyhow::anyhow!("unknown preset: {preset}"))?;
+                anyhow::ensure!(
+                    preset.is_match(filter),
+                    "filter `{name}` does not match the preset for {}",
+                    preset.program_id()
+                );
+            }
             ConfigGrpcFilters::check_any(
                 filter.account.is_empty() && filter.owner.is_empty(),
                 limit.any,
@@ -197,41 +532,92 @@ impl FilterAccounts {
             ConfigGrpcFilters::check_pubkey_max(filter.account.len(), limit.account_max)?;
             ConfigGrpcFilters::check_pubkey_max(filter.owner.len(), limit.owner_max)?;
 
-            Self::set(
+            let index = this.filters.len();
+            let required = Self::set(
                 &mut this.account,
-                &mut this.account_required,
-                name,
+                index,
                 Filter::decode_pubkeys(&filter.account, &limit.account_reject),
             )?;
+            this.account_required.push(required);
 
-            Self::set(
+            let required = Self::set(
                 &mut this.owner,
-                &mut this.owner_required,
-                name,
+                index,
                 Filter::decode_pubkeys(&filter.owner, &limit.owner_reject),
             )?;
+            this.owner_required.push(required);
+
+            if filter.first_write_only == Some(true) {
+                this.first_write_only.insert(name.clone());
+            }
+            if filter.include_account_data == Some(false) {
+                this.without_data.insert(name.clone());
+            }
+            if filter.owner_change_only == Some(true) {
+                this.owner_change_only.insert(name.clone());
+            }
+            if !filter.lifecycle.is_empty() {
+                anyhow::ensure!(
+                    limit.lifecycle,
+                    "lifecycle filter is disabled, `accounts_lifecycle_cache_max` is not set"
+                );
+                let lifecycle = filter
+                    .lifecycle
+                    .iter()
+                    .map(|value| {
+                        AccountLifecycle::try_from(*value)
+                            .map_err(|_| anyhow::anyhow!("invalid lifecycle: {value}"))
+                    })
+                    .collect::<anyhow::Result<_>>()?;
+                this.lifecycle.insert(name.clone(), lifecycle);
+            }
+            if let Some(range) = &filter.changed_range {
+                anyhow::ensure!(
+                    range.length > 0 && range.length <= limit.changed_range_length_max as u64,
+                    "changed_range length should be within 1..={}",
+                    limit.changed_range_length_max
+                );
+                this.changed_range.insert(
+                    name.clone(),
+                    FilterAccountsChangedRange {
+                        index,
+                        offset: usize::try_from(range.offset)
+                            .map_err(|_| anyhow::anyhow!("invalid changed_range offset"))?,
+                        length: range.length as usize,
+                    },
+                );
+            }
 
-            this.filters
-                .push((name.clone(), FilterAccountsData::new(&filter.filters)?));
+            this.filters.push((
+                name.clone(),
+                FilterAccountsData::new(&filter.filters, &mut this.memcmp)?,
+            ));
+        }
+        if !this.owner_change_only.is_empty() {
+            this.owners = Arc::new(Mutex::new(FilterAccountsOwners::new(
+                limit.owner_change_cache_max,
+            )));
+        }
+        if !this.changed_range.is_empty() {
+            this.changed_ranges = Arc::new(Mutex::new(FilterAccountsChangedRanges::new(
+                limit.changed_range_cache_max,
+            )));
         }
         Ok(this)
     }
 
     fn set(
-        map: &mut HashMap<Pubkey, HashSet<String>>,
-        map_required: &mut HashSet<String>,
-        name: &str,
+        map: &mut HashMap<Pubkey, Vec<usize>>,
+        index: usize,
         keys: impl Iterator<Item = anyhow::Result<Pubkey>>,
     ) -> anyhow::Result<bool> {
         let mut required = false;
         for maybe_key in keys {
-            if map.entry(maybe_key?).or_default().insert(name.to_string()) {
-                required = true;
+            let indexes = map.entry(maybe_key?).or_default();
+            if !indexes.contains(&index) {
+                indexes.push(index);
             }
-        }
-
-        if required {
-            map_required.insert(name.to_string());
+            required = true;
         }
         Ok(required)
     }
@@ -240,26 +626,152 @@ impl FilterAccounts {
         &'a self,
         message: &'a MessageAccount,
     ) -> Box<dyn Iterator<Item = (Vec<String>, MessageRef<'a>)> + Send + 'a> {
+        // Owner is tracked for all accounts, because previous owner may not match the filter
+        let owner_changed = !self.owner_change_only.is_empty() && {
+            let mut owners = self.owners.lock().expect("alive mutex");
+            owners
+                .replace(message.account.pubkey, message.account.owner)
+                .map_or(false, |owner| owner != message.account.owner)
+        };
+
         let mut filter = FilterAccountsMatch::new(self);
         filter.match_account(&message.account.pubkey);
         filter.match_owner(&message.account.owner);
-        filter.match_data(&message.account.data);
-        Box::new(std::iter::once((
-            filter.get_filters(),
-            MessageRef::Account(message),
-        )))
+        filter.match_data(&message.account);
+        let mut filters = filter.get_filters();
+        if !owner_changed {
+            filters.retain(|name| !self.owner_change_only.contains(name));
+        }
+        if !self.lifecycle.is_empty() {
+            filters.retain(|name| {
+                self.lifecycle
+                    .get(name)
+                    .map_or(true, |lifecycle| lifecycle.contains(&message.lifecycle))
+            });
+        }
+        // State is saved only for accounts matched by `first_write_only` filters
+        if filters
+            .iter()
+            .any(|name| self.first_write_only.contains(name))
+        {
+            // Messages for the same account in the slot come with increasing write_version
+            let mut first_write_sent = self.first_write_sent.lock().expect("alive mutex");
+            let sent = first_write_sent
+                .entry(message.slot)
+                .or_default()
+                .entry(message.account.pubkey)
+                .or_default();
+            filters
+                .retain(|name| !self.first_write_only.contains(name) || sent.insert(name.clone()));
+        }
+        if !self.changed_range.is_empty() {
+            // Checked last, so saved value is the value in the sent update
+            let mut changed_ranges = self.changed_ranges.lock().expect("alive mutex");
+            filters.retain(|name| match self.changed_range.get(name) {
+                Some(range) => {
+                    let (len, data) = range.get(&message.account.data);
+                    changed_ranges
+                        .replace((message.account.pubkey, range.index), (len, data.to_vec()))
+                        .map_or(true, |(previous_len, previous)| {
+                            previous_len != len || previous != data
+                        })
+                }
+                None => true,
+            });
+        }
+
+        // Data is removed only after match, so data filters always work with full data
+        let (filters, filters_without_data): (Vec<_>, Vec<_>) = filters
+            .into_iter()
+            .partition(|name| !self.without_data.contains(name));
+        let mut updates = vec![(filters, MessageRef::Account(message))];
+        if !filters_without_data.is_empty() {
+            updates.push((
+                filters_without_data,
+                MessageRef::AccountWithoutData(message),
+            ));
+        }
+        Box::new(updates.into_iter())
+    }
+
+    fn remove_first_write_outdated(&self, finalized_slot: u64) {
+        if !self.first_write_only.is_empty() {
+            let mut first_write_sent = self.first_write_sent.lock().expect("alive mutex");
+            *first_write_sent = first_write_sent.split_off(&(finalized_slot + 1));
+        }
+    }
+}
+
+/// Memcmp windows of all filters, the same window used by many filters is compared once per update
+#[derive(Debug, Default, Clone)]
+struct FilterAccountsMemcmp {
+    windows: Vec<(usize, Vec<u8>)>,
+    indexes: HashMap<(usize, Vec<u8>), usize>,
+}
+
+impl FilterAccountsMemcmp {
+    fn insert(&mut self, offset: usize, data: Vec<u8>) -> usize {
+        let windows = &mut self.windows;
+        *self
+            .indexes
+            .entry((offset, data))
+            .or_insert_with_key(|(offset, data)| {
+                windows.push((*offset, data.clone()));
+                windows.len() - 1
+            })
+    }
+
+    fn is_match(&self, index: usize, data: &[u8]) -> bool {
+        let (offset, bytes) = &self.windows[index];
+        data.get(*offset..)
+            .and_then(|data| data.get(..bytes.len()))
+            .map_or(false, |data| data == bytes)
+    }
+}
+
+/// Results of the data checks shared by all filters, evaluated lazily for one update
+#[derive(Debug)]
+struct FilterAccountsDataShared<'a> {
+    memcmp: &'a FilterAccountsMemcmp,
+    memcmp_match: Vec<Option<bool>>,
+    token_account_state: Option<bool>,
+}
+
+impl<'a> FilterAccountsDataShared<'a> {
+    fn new(memcmp: &'a FilterAccountsMemcmp) -> Self {
+        Self {
+            memcmp,
+            memcmp_match: vec![None; memcmp.windows.len()],
+            token_account_state: None,
+        }
+    }
+
+    fn is_memcmp_match(&mut self, index: usize, data: &[u8]) -> bool {
+        let memcmp = self.memcmp;
+        *self.memcmp_match[index].get_or_insert_with(|| memcmp.is_match(index, data))
+    }
+
+    fn is_token_account_state(&mut self, data: &[u8]) -> bool {
+        *self
+            .token_account_state
+            .get_or_insert_with(|| TokenAccount::valid_account_data(data))
     }
 }
 
 #[derive(Debug, Default, Clone)]
 struct FilterAccountsData {
-    memcmp: Vec<(usize, Vec<u8>)>,
+    memcmp: Vec<usize>,
     datasize: Option<usize>,
     token_account_state: bool,
+    executable: Option<bool>,
+    rent_epoch: Option<FilterAccountsRentEpoch>,
 }
 
 impl FilterAccountsData {
-    fn new(filters: &[SubscribeRequestFilterAccountsFilter]) -> anyhow::Result<Self> {
+    fn new(
+        filters: &[SubscribeRequestFilterAccountsFilter],
+        memcmp_shared: &mut FilterAccountsMemcmp,
+    ) -> anyhow::Result<Self> {
         const MAX_FILTERS: usize = 4;
         const MAX_DATA_SIZE: usize = 128;
         const MAX_DATA_BASE58_SIZE: usize = 175;
@@ -291,7 +803,8 @@ impl FilterAccountsData {
                         None => anyhow::bail!("data for memcmp should be defined"),
                     };
                     anyhow::ensure!(data.len() <= MAX_DATA_SIZE, "data too large");
-                    this.memcmp.push((memcmp.offset as usize, data));
+                    this.memcmp
+                        .push(memcmp_shared.insert(memcmp.offset as usize, data));
                 }
                 Some(AccountsFilterDataOneof::Datasize(datasize)) => {
                     anyhow::ensure!(
@@ -303,6 +816,19 @@ impl FilterAccountsData {
                     anyhow::ensure!(value, "token_account_state only allowed to be true");
                     this.token_account_state = true;
                 }
+                Some(AccountsFilterDataOneof::Executable(value)) => {
+                    anyhow::ensure!(
+                        this.executable.replace(*value).is_none(),
+                        "executable used more than once",
+                    );
+                }
+                Some(AccountsFilterDataOneof::RentEpoch(rent_epoch)) => {
+                    let rent_epoch = FilterAccountsRentEpoch::new(rent_epoch)?;
+                    anyhow::ensure!(
+                        this.rent_epoch.replace(rent_epoch).is_none(),
+                        "rent_epoch used more than once",
+                    );
+                }
                 None => {
                     anyhow::bail!("filter should be defined");
                 }
@@ -312,51 +838,133 @@ impl FilterAccountsData {
     }
 
     fn is_empty(&self) -> bool {
-        self.memcmp.is_empty() && self.datasize.is_none() && !self.token_account_state
+        self.memcmp.is_empty()
+            && self.datasize.is_none()
+            && !self.token_account_state
+            && self.executable.is_none()
+            && self.rent_epoch.is_none()
     }
 
-    fn is_match(&self, data: &[u8]) -> bool {
+    fn is_match(
+        &self,
+        account: &MessageAccountInfo,
+        shared: &mut FilterAccountsDataShared<'_>,
+    ) -> bool {
+        // scalar checks first, they are cheaper than data checks
+        if matches!(self.executable, Some(executable) if account.executable != executable) {
+            return false;
+        }
+        if matches!(&self.rent_epoch, Some(rent_epoch) if !rent_epoch.is_match(account.rent_epoch))
+        {
+            return false;
+        }
+
+        let data = account.data.as_slice();
         if matches!(self.datasize, Some(datasize) if data.len() != datasize) {
             return false;
         }
-        if self.token_account_state && !TokenAccount::valid_account_data(data) {
+        if self.token_account_state && !shared.is_token_account_state(data) {
             return false;
         }
-        for (offset, bytes) in self.memcmp.iter() {
-            if data.len() < *offset + bytes.len() {
-                return false;
-            }
-            let data = &data[*offset..*offset + bytes.len()];
-            if data != bytes {
-                return false;
-            }
+        self.memcmp
+            .iter()
+            .all(|index| shared.is_memcmp_match(*index, data))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FilterAccountsRentEpoch {
+    min: Option<u64>,
+    max: Option<u64>,
+    rent_exempt: bool,
+}
+
+impl FilterAccountsRentEpoch {
+    fn new(config: &SubscribeRequestFilterAccountsFilterRentEpoch) -> anyhow::Result<Self> {
+        let this = Self {
+            min: config.min,
+            max: config.max,
+            rent_exempt: config.rent_exempt.unwrap_or(false),
+        };
+        anyhow::ensure!(
+            this.min.is_some() || this.max.is_some() || this.rent_exempt,
+            "rent_epoch should have min, max or rent_exempt"
+        );
+        if let (Some(min), Some(max)) = (this.min, this.max) {
+            anyhow::ensure!(min <= max, "rent_epoch min should not exceed max");
+        }
+        Ok(this)
+    }
+
+    fn is_match(&self, rent_epoch: u64) -> bool {
+        // Rent exempt accounts use sentinel epoch, it is not a real epoch for the range
+        if rent_epoch == RENT_EXEMPT_RENT_EPOCH {
+            return self.rent_exempt;
         }
-        true
+        if self.min.is_none() && self.max.is_none() {
+            return false;
+        }
+        self.min.map_or(true, |min| rent_epoch >= min)
+            && self.max.map_or(true, |max| rent_epoch <= max)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FilterAccountsSample {
+    threshold: u64,
+}
+
+impl FilterAccountsSample {
+    fn new(rate: f64) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            (0.0..=1.0).contains(&rate),
+            "`accounts_sample_rate` should be between 0.0 and 1.0"
+        );
+        Ok(Self {
+            // `as` saturates, so rate `1.0` includes every hash
+            threshold: (rate * (u64::MAX as f64 + 1.0)) as u64,
+        })
+    }
+
+    // Stable hash (splitmix64 over pubkey words), membership never changes between restarts
+    fn hash(pubkey: &Pubkey) -> u64 {
+        pubkey.as_ref().chunks_exact(8).fold(0, |hash: u64, chunk| {
+            let word = u64::from_le_bytes(chunk.try_into().expect("8 bytes chunk"));
+            let mut z = (hash ^ word).wrapping_add(0x9e3779b97f4a7c15);
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^ (z >> 31)
+        })
+    }
+
+    fn contains(self, pubkey: &Pubkey) -> bool {
+        self.threshold == u64::MAX || Self::hash(pubkey) < self.threshold
     }
 }
 
 #[derive(Debug)]
 pub struct FilterAccountsMatch<'a> {
     filter: &'a FilterAccounts,
-    account: HashSet<&'a str>,
-    owner: HashSet<&'a str>,
-    data: HashSet<&'a str>,
+    account: Vec<bool>,
+    owner: Vec<bool>,
+    data: Vec<bool>,
 }
 
 impl<'a> FilterAccountsMatch<'a> {
     fn new(filter: &'a FilterAccounts) -> Self {
+        let len = filter.filters.len();
         Self {
             filter,
-            account: Default::default(),
-            owner: Default::default(),
-            data: Default::default(),
+            account: vec![false; len],
+            owner: vec![false; len],
+            data: vec![false; len],
         }
     }
 
-    fn extend(set: &mut HashSet<&'a str>, map: &'a HashMap<Pubkey, HashSet<String>>, key: &Pubkey) {
-        if let Some(names) = map.get(key) {
-            for name in names {
-                set.insert(name);
+    fn extend(matched: &mut [bool], map: &HashMap<Pubkey, Vec<usize>>, key: &Pubkey) {
+        if let Some(indexes) = map.get(key) {
+            for index in indexes {
+                matched[*index] = true;
             }
         }
     }
@@ -369,34 +977,38 @@ impl<'a> FilterAccountsMatch<'a> {
         Self::extend(&mut self.owner, &self.filter.owner, pubkey)
     }
 
-    pub fn match_data(&mut self, data: &[u8]) {
-        for (name, filter) in self.filter.filters.iter() {
-            if filter.is_match(data) {
-                self.data.insert(name);
+    // Should be called after `match_account` and `match_owner`, data of filters which already
+    // failed account or owner requirement is not checked
+    pub fn match_data(&mut self, account: &MessageAccountInfo) {
+        let mut shared = FilterAccountsDataShared::new(&self.filter.memcmp);
+        for (index, (_name, filter)) in self.filter.filters.iter().enumerate() {
+            if !filter.is_empty() && self.is_required_match(index) {
+                self.data[index] = filter.is_match(account, &mut shared);
             }
         }
     }
 
+    fn is_required_match(&self, index: usize) -> bool {
+        let af = &self.filter;
+        (!af.account_required[index] || self.account[index])
+            && (!af.owner_required[index] || self.owner[index])
+    }
+
     pub fn get_filters(&self) -> Vec<String> {
         self.filter
             .filters
             .iter()
-            .filter_map(|(name, filter)| {
-                let name = name.as_str();
-                let af = &self.filter;
-
-                // If filter name in required but not in matched => return `false`
-                if af.account_required.contains(name) && !self.account.contains(name) {
+            .enumerate()
+            .filter_map(|(index, (name, filter))| {
+                // If filter in required but not in matched => return `false`
+                if !self.is_required_match(index) {
                     return None;
                 }
-                if af.owner_required.contains(name) && !self.owner.contains(name) {
-                    return None;
-                }
-                if !filter.is_empty() && !self.data.contains(name) {
+                if !filter.is_empty() && !self.data[index] {
                     return None;
                 }
 
-                Some(name.to_string())
+                Some(name.clone())
             })
             .collect()
     }
@@ -405,13 +1017,31 @@ impl<'a> FilterAccountsMatch<'a> {
 #[derive(Debug, Default, Clone, Copy)]
 struct FilterSlotsInner {
     filter_by_commitment: bool,
+    from_slot: Option<u64>,
+    to_slot: Option<u64>,
+    include_dead: bool,
 }
 
 impl FilterSlotsInner {
-    fn new(filter: &SubscribeRequestFilterSlots) -> Self {
-        Self {
-            filter_by_commitment: filter.filter_by_commitment.unwrap_or_default(),
+    fn new(filter: &SubscribeRequestFilterSlots) -> anyhow::Result<Self> {
+        if let (Some(from_slot), Some(to_slot)) = (filter.from_slot, filter.to_slot) {
+            anyhow::ensure!(
+                from_slot <= to_slot,
+                "`from_slot` ({from_slot}) should not be greater than `to_slot` ({to_slot})"
+            );
         }
+
+        Ok(Self {
+            filter_by_commitment: filter.filter_by_commitment.unwrap_or_default(),
+            from_slot: filter.from_slot,
+            to_slot: filter.to_slot,
+            include_dead: filter.include_dead.unwrap_or_default(),
+        })
+    }
+
+    fn is_in_range(&self, slot: u64) -> bool {
+        self.from_slot.map_or(true, |from_slot| slot >= from_slot)
+            && self.to_slot.map_or(true, |to_slot| slot <= to_slot)
     }
 }
 
@@ -430,11 +1060,35 @@ impl FilterSlots {
         Ok(Self {
             filters: configs
                 .iter()
-                .map(|(name, filter)| (name.clone(), FilterSlotsInner::new(filter)))
-                .collect(),
+                .map(|(name, filter)| Ok((name.clone(), FilterSlotsInner::new(filter)?)))
+                .collect::<anyhow::Result<_>>()?,
         })
     }
 
+    fn get_from_slot(&self) -> Option<u64> {
+        self.filters
+            .values()
+            .map(|inner| inner.from_slot)
+            .min()
+            .flatten()
+    }
+
+    // All filters have `to_slot`, so subscription is closed once the range is ended
+    fn has_range_end(&self) -> bool {
+        !self.filters.is_empty() && self.filters.values().all(|inner| inner.to_slot.is_some())
+    }
+
+    // Slot after `to_slot` of every filter is finalized, so all statuses in range were sent
+    fn is_range_ended(&self, message: &MessageSlot) -> bool {
+        self.has_range_end()
+            && message.status == CommitmentLevel::Finalized
+            && self.filters.values().all(|inner| {
+                inner
+                    .to_slot
+                    .map_or(false, |to_slot| message.slot > to_slot)
+            })
+    }
+
     fn get_filters<'a>(
         &'a self,
         message: &'a MessageSlot,
@@ -444,7 +1098,10 @@ impl FilterSlots {
             self.filters
                 .iter()
                 .filter_map(|(name, inner)| {
-                    if !inner.filter_by_commitment || commitment == Some(message.status) {
+                    if (!inner.filter_by_commitment || commitment == Some(message.status))
+                        && inner.is_in_range(message.slot)
+                        && (!message.dead || inner.include_dead)
+                    {
                         Some(name.clone())
                     } else {
                         None
@@ -467,9 +1124,12 @@ pub struct FilterTransactionsInner {
     vote: Option<bool>,
     failed: Option<bool>,
     signature: Option<Signature>,
+    signature_prefix: Option<Vec<u8>>,
     account_include: Vec<Pubkey>,
     account_exclude: Vec<Pubkey>,
     account_required: Vec<Pubkey>,
+    invoked_programs: Vec<Pubkey>,
+    fields: MessageTransactionFields,
 }
 
 #[derive(Debug, Clone)]
@@ -493,7 +1153,8 @@ impl FilterTransactions {
                     && filter.failed.is_none()
                     && filter.account_include.is_empty()
                     && filter.account_exclude.is_empty()
-                    && filter.account_required.is_empty(),
+                    && filter.account_required.is_empty()
+                    && filter.invoked_programs.is_empty(),
                 limit.any,
             )?;
             ConfigGrpcFilters::check_pubkey_max(
@@ -508,6 +1169,16 @@ impl FilterTransactions {
                 filter.account_required.len(),
                 limit.account_required_max,
             )?;
+            ConfigGrpcFilters::check_pubkey_max(
+                filter.invoked_programs.len(),
+                limit.invoked_programs_max,
+            )?;
+            if let Some(signature_prefix) = &filter.signature_prefix {
+                anyhow::ensure!(
+                    signature_prefix.len() <= SIGNATURE_BYTES,
+                    "`signature_prefix` length should not exceed {SIGNATURE_BYTES} bytes"
+                );
+            }
 
             filters.insert(
                 name.clone(),
@@ -523,6 +1194,7 @@ impl FilterTransactions {
                                 .map_err(|error| anyhow::anyhow!("invalid signature: {error}"))
                         })
                         .transpose()?,
+                    signature_prefix: filter.signature_prefix.clone(),
                     account_include: Filter::decode_pubkeys_into_vec(
                         &filter.account_include,
                         &limit.account_include_reject,
@@ -535,6 +1207,19 @@ impl FilterTransactions {
                         &filter.account_required,
                         &HashSet::new(),
                     )?,
+                    invoked_programs: Filter::decode_pubkeys_into_vec(
+                        &filter.invoked_programs,
+                        &HashSet::new(),
+                    )?,
+                    fields: MessageTransactionFields {
+                        message: filter.include_transaction_message.unwrap_or(true),
+                        meta: filter.include_meta.unwrap_or(true),
+                        logs: filter.include_logs.unwrap_or(true),
+                        inner_instructions: filter.include_inner_instructions.unwrap_or(true),
+                        account_keys: filter.include_account_keys.unwrap_or(true),
+                        balances: filter.include_balances.unwrap_or(true),
+                        return_data: filter.include_return_data.unwrap_or(true),
+                    },
                 },
             );
         }
@@ -570,6 +1255,18 @@ impl FilterTransactions {
                     }
                 }
 
+                if let Some(signature_prefix) = &inner.signature_prefix {
+                    if !message
+                        .transaction
+                        .transaction
+                        .signature()
+                        .as_ref()
+                        .starts_with(signature_prefix)
+                    {
+                        return None;
+                    }
+                }
+
                 if !inner.account_include.is_empty()
                     && message
                         .transaction
@@ -618,14 +1315,58 @@ impl FilterTransactions {
                     }
                 }
 
-                Some(name.clone())
+                if !inner.invoked_programs.is_empty()
+                    && !Self::is_invoked(&inner.invoked_programs, &message.transaction)
+                {
+                    return None;
+                }
+
+                Some((name.clone(), inner.fields))
             })
-            .collect();
-        let message = match self.filter_type {
-            FilterTransactionsType::Transaction => MessageRef::Transaction(message),
-            FilterTransactionsType::TransactionStatus => MessageRef::TransactionStatus(message),
-        };
-        Box::new(std::iter::once((filters, message)))
+            .collect::<Vec<_>>();
+        match self.filter_type {
+            FilterTransactionsType::Transaction => {
+                // filters with the same projection share one update
+                let mut projections = vec![(MessageTransactionFields::FULL, vec![])];
+                for (name, fields) in filters {
+                    match projections.iter_mut().find(|(value, _)| *value == fields) {
+                        Some((_, names)) => names.push(name),
+                        None => projections.push((fields, vec![name])),
+                    }
+                }
+                Box::new(projections.into_iter().map(move |(fields, names)| {
+                    let message = if fields == MessageTransactionFields::FULL {
+                        MessageRef::Transaction(message)
+                    } else {
+                        MessageRef::TransactionProjection(message, fields)
+                    };
+                    (names, message)
+                }))
+            }
+            FilterTransactionsType::TransactionStatus => Box::new(std::iter::once((
+                filters.into_iter().map(|(name, _)| name).collect(),
+                MessageRef::TransactionStatus(message),
+            ))),
+        }
+    }
+
+    // Program is invoked if it's a program of top-level or inner (CPI) instruction
+    fn is_invoked(programs: &[Pubkey], transaction: &MessageTransactionInfo) -> bool {
+        let message = transaction.transaction.message();
+        let account_keys = message.account_keys();
+        let inner_instructions = transaction
+            .meta
+            .inner_instructions
+            .iter()
+            .flatten()
+            .flat_map(|inner| inner.instructions.iter())
+            .map(|inner| &inner.instruction);
+        message
+            .instructions()
+            .iter()
+            .chain(inner_instructions)
+            .filter_map(|instruction| account_keys.get(instruction.program_id_index as usize))
+            .any(|program_id| programs.binary_search(program_id).is_ok())
     }
 }
 
@@ -667,6 +1408,7 @@ pub struct FilterBlocksInner {
     include_transactions: Option<bool>,
     include_accounts: Option<bool>,
     include_entries: Option<bool>,
+    include_rewards: Option<bool>,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -714,6 +1456,7 @@ impl FilterBlocks {
                     include_transactions: filter.include_transactions,
                     include_accounts: filter.include_accounts,
                     include_entries: filter.include_entries,
+                    include_rewards: filter.include_rewards,
                 },
             );
         }
@@ -777,10 +1520,12 @@ impl FilterBlocks {
                 vec![]
             };
 
-            (
-                vec![filter.clone()],
-                MessageRef::Block((message, transactions, accounts, entries).into()),
-            )
+            let mut block = MessageBlockRef::from((message, transactions, accounts, entries));
+            if inner.include_rewards == Some(false) {
+                block.rewards = None;
+            }
+
+            (vec![filter.clone()], MessageRef::Block(block))
         }))
     }
 }
@@ -858,27 +1603,82 @@ impl FilterAccountsDataSlice {
 mod tests {
     use {
         crate::{
-            config::ConfigGrpcFilters,
-            filters::Filter,
-            grpc::{Message, MessageRef, MessageTransaction, MessageTransactionInfo},
+            config::{ConfigGrpcFilters, ConfigGrpcFiltersAccounts},
+            filters::{Filter, FilterAccountsSample},
+            grpc::{
+                Message, MessageAccount, MessageAccountInfo, MessageCreatedAt, MessageRef,
+                MessageSlot, MessageTransaction, MessageTransactionInfo,
+            },
         },
         solana_sdk::{
             hash::Hash,
+            instruction::CompiledInstruction,
             message::{v0::LoadedAddresses, Message as SolMessage, MessageHeader},
             pubkey::Pubkey,
             signer::{keypair::Keypair, Signer},
             transaction::{SanitizedTransaction, Transaction},
+            transaction_context::TransactionReturnData,
         },
-        solana_transaction_status::TransactionStatusMeta,
-        std::collections::HashMap,
-        yellowstone_grpc_proto::geyser::{
-            SubscribeRequest, SubscribeRequestFilterAccounts, SubscribeRequestFilterTransactions,
+        solana_transaction_status::{InnerInstruction, InnerInstructions, TransactionStatusMeta},
+        std::{
+            collections::HashMap,
+            str::FromStr,
+            time::{Duration, SystemTime},
+        },
+        yellowstone_grpc_proto::{
+            geyser::{
+                subscribe_request_filter_accounts_filter::Filter as AccountsFilterDataOneof,
+                subscribe_request_filter_accounts_filter_memcmp::Data as AccountsFilterMemcmpOneof,
+                subscribe_update::UpdateOneof, AccountLifecycle, CommitmentLevel, SubscribeRequest,
+                SubscribeRequestAccountsDataSlice, SubscribeRequestFilterAccounts,
+                SubscribeRequestFilterAccountsChangedRange, SubscribeRequestFilterAccountsFilter,
+                SubscribeRequestFilterAccountsFilterMemcmp,
+                SubscribeRequestFilterAccountsFilterRentEpoch, SubscribeRequestFilterBlocks,
+                SubscribeRequestFilterSlots, SubscribeRequestFilterTransactions,
+                SubscribeRequestPing,
+            },
+            presets::{
+                FilterPreset, SPL_TOKEN_2022_PROGRAM_ID, SPL_TOKEN_PROGRAM_ID, SYSTEM_PROGRAM_ID,
+            },
         },
     };
 
+    fn create_message_account(
+        pubkey: Pubkey,
+        slot: u64,
+        write_version: u64,
+        data: Vec<u8>,
+    ) -> Message {
+        Message::Account(MessageAccount {
+            account: MessageAccountInfo {
+                pubkey,
+                lamports: 0,
+                owner: Pubkey::new_unique(),
+                executable: false,
+                rent_epoch: 0,
+                data,
+                write_version,
+                txn_signature: None,
+            },
+            slot,
+            is_startup: false,
+            lifecycle: AccountLifecycle::Modified,
+            created_at: MessageCreatedAt::now(),
+        })
+    }
+
     fn create_message_transaction(
         keypair: &Keypair,
         account_keys: Vec<Pubkey>,
+    ) -> MessageTransaction {
+        create_message_transaction_with_instructions(keypair, account_keys, vec![], None)
+    }
+
+    fn create_message_transaction_with_instructions(
+        keypair: &Keypair,
+        account_keys: Vec<Pubkey>,
+        instructions: Vec<CompiledInstruction>,
+        inner_instructions: Option<Vec<InnerInstructions>>,
     ) -> MessageTransaction {
         let message = SolMessage {
             header: MessageHeader {
@@ -886,6 +1686,7 @@ mod tests {
                 ..MessageHeader::default()
             },
             account_keys,
+            instructions,
             ..SolMessage::default()
         };
         let recent_blockhash = Hash::default();
@@ -897,7 +1698,7 @@ mod tests {
             fee: 0,
             pre_balances: vec![],
             post_balances: vec![],
-            inner_instructions: None,
+            inner_instructions,
             log_messages: None,
             pre_token_balances: None,
             post_token_balances: None,
@@ -916,6 +1717,7 @@ mod tests {
                 index: 1,
             },
             slot: 100,
+            created_at: MessageCreatedAt::now(),
         }
     }
 
@@ -933,6 +1735,14 @@ mod tests {
             commitment: None,
             accounts_data_slice: Vec::new(),
             ping: None,
+            commit: None,
+            accounts_coalesce_window_ms: None,
+            accounts_chunking: None,
+            accounts_sample_rate: None,
+            filter_ack: None,
+            resume_token: None,
+            blocks_chunking: None,
+            include_created_at: None,
         };
         let limit = ConfigGrpcFilters::default();
         let filter = Filter::new(&config, &limit);
@@ -949,6 +1759,11 @@ mod tests {
                 account: vec![],
                 owner: vec![],
                 filters: vec![],
+                first_write_only: None,
+                include_account_data: None,
+                owner_change_only: None,
+                lifecycle: vec![],
+                changed_range: None,
             },
         );
 
@@ -963,6 +1778,14 @@ mod tests {
             commitment: None,
             accounts_data_slice: Vec::new(),
             ping: None,
+            commit: None,
+            accounts_coalesce_window_ms: None,
+            accounts_chunking: None,
+            accounts_sample_rate: None,
+            filter_ack: None,
+            resume_token: None,
+            blocks_chunking: None,
+            include_created_at: None,
         };
         let mut limit = ConfigGrpcFilters::default();
         limit.accounts.any = false;
@@ -984,6 +1807,15 @@ mod tests {
                 account_include: vec![],
                 account_exclude: vec![],
                 account_required: vec![],
+                include_transaction_message: None,
+                signature_prefix: None,
+                include_meta: None,
+                include_logs: None,
+                include_inner_instructions: None,
+                include_account_keys: None,
+                invoked_programs: vec![],
+                include_balances: None,
+                include_return_data: None,
             },
         );
 
@@ -998,6 +1830,14 @@ mod tests {
             commitment: None,
             accounts_data_slice: Vec::new(),
             ping: None,
+            commit: None,
+            accounts_coalesce_window_ms: None,
+            accounts_chunking: None,
+            accounts_sample_rate: None,
+            filter_ack: None,
+            resume_token: None,
+            blocks_chunking: None,
+            include_created_at: None,
         };
         let mut limit = ConfigGrpcFilters::default();
         limit.transactions.any = false;
@@ -1018,6 +1858,15 @@ mod tests {
                 account_include: vec![],
                 account_exclude: vec![],
                 account_required: vec![],
+                include_transaction_message: None,
+                signature_prefix: None,
+                include_meta: None,
+                include_logs: None,
+                include_inner_instructions: None,
+                include_account_keys: None,
+                invoked_programs: vec![],
+                include_balances: None,
+                include_return_data: None,
             },
         );
 
@@ -1032,6 +1881,14 @@ mod tests {
             commitment: None,
             accounts_data_slice: Vec::new(),
             ping: None,
+            commit: None,
+            accounts_coalesce_window_ms: None,
+            accounts_chunking: None,
+            accounts_sample_rate: None,
+            filter_ack: None,
+            resume_token: None,
+            blocks_chunking: None,
+            include_created_at: None,
         };
         let mut limit = ConfigGrpcFilters::default();
         limit.transactions.any = false;
@@ -1058,6 +1915,15 @@ mod tests {
                 account_include,
                 account_exclude: vec![],
                 account_required: vec![],
+                include_transaction_message: None,
+                signature_prefix: None,
+                include_meta: None,
+                include_logs: None,
+                include_inner_instructions: None,
+                include_account_keys: None,
+                invoked_programs: vec![],
+                include_balances: None,
+                include_return_data: None,
             },
         );
 
@@ -1072,6 +1938,14 @@ mod tests {
             commitment: None,
             accounts_data_slice: Vec::new(),
             ping: None,
+            commit: None,
+            accounts_coalesce_window_ms: None,
+            accounts_chunking: None,
+            accounts_sample_rate: None,
+            filter_ack: None,
+            resume_token: None,
+            blocks_chunking: None,
+            include_created_at: None,
         };
         let limit = ConfigGrpcFilters::default();
         let filter = Filter::new(&config, &limit).unwrap();
@@ -1105,6 +1979,15 @@ mod tests {
                 account_include,
                 account_exclude: vec![],
                 account_required: vec![],
+                include_transaction_message: None,
+                signature_prefix: None,
+                include_meta: None,
+                include_logs: None,
+                include_inner_instructions: None,
+                include_account_keys: None,
+                invoked_programs: vec![],
+                include_balances: None,
+                include_return_data: None,
             },
         );
 
@@ -1119,6 +2002,14 @@ mod tests {
             commitment: None,
             accounts_data_slice: Vec::new(),
             ping: None,
+            commit: None,
+            accounts_coalesce_window_ms: None,
+            accounts_chunking: None,
+            accounts_sample_rate: None,
+            filter_ack: None,
+            resume_token: None,
+            blocks_chunking: None,
+            include_created_at: None,
         };
         let limit = ConfigGrpcFilters::default();
         let filter = Filter::new(&config, &limit).unwrap();
@@ -1152,6 +2043,15 @@ mod tests {
                 account_include: vec![],
                 account_exclude,
                 account_required: vec![],
+                include_transaction_message: None,
+                signature_prefix: None,
+                include_meta: None,
+                include_logs: None,
+                include_inner_instructions: None,
+                include_account_keys: None,
+                invoked_programs: vec![],
+                include_balances: None,
+                include_return_data: None,
             },
         );
 
@@ -1166,6 +2066,14 @@ mod tests {
             commitment: None,
             accounts_data_slice: Vec::new(),
             ping: None,
+            commit: None,
+            accounts_coalesce_window_ms: None,
+            accounts_chunking: None,
+            accounts_sample_rate: None,
+            filter_ack: None,
+            resume_token: None,
+            blocks_chunking: None,
+            include_created_at: None,
         };
         let limit = ConfigGrpcFilters::default();
         let filter = Filter::new(&config, &limit).unwrap();
@@ -1202,6 +2110,15 @@ mod tests {
                 account_include,
                 account_exclude: vec![],
                 account_required,
+                include_transaction_message: None,
+                signature_prefix: None,
+                include_meta: None,
+                include_logs: None,
+                include_inner_instructions: None,
+                include_account_keys: None,
+                invoked_programs: vec![],
+                include_balances: None,
+                include_return_data: None,
             },
         );
 
@@ -1216,6 +2133,14 @@ mod tests {
             commitment: None,
             accounts_data_slice: Vec::new(),
             ping: None,
+            commit: None,
+            accounts_coalesce_window_ms: None,
+            accounts_chunking: None,
+            accounts_sample_rate: None,
+            filter_ack: None,
+            resume_token: None,
+            blocks_chunking: None,
+            include_created_at: None,
         };
         let limit = ConfigGrpcFilters::default();
         let filter = Filter::new(&config, &limit).unwrap();
@@ -1257,6 +2182,15 @@ mod tests {
                 account_include,
                 account_exclude: vec![],
                 account_required,
+                include_transaction_message: None,
+                signature_prefix: None,
+                include_meta: None,
+                include_logs: None,
+                include_inner_instructions: None,
+                include_account_keys: None,
+                invoked_programs: vec![],
+                include_balances: None,
+                include_return_data: None,
             },
         );
 
@@ -1271,6 +2205,14 @@ mod tests {
             commitment: None,
             accounts_data_slice: Vec::new(),
             ping: None,
+            commit: None,
+            accounts_coalesce_window_ms: None,
+            accounts_chunking: None,
+            accounts_sample_rate: None,
+            filter_ack: None,
+            resume_token: None,
+            blocks_chunking: None,
+            include_created_at: None,
         };
         let limit = ConfigGrpcFilters::default();
         let filter = Filter::new(&config, &limit).unwrap();
@@ -1282,4 +2224,1287 @@ mod tests {
             assert!(filters.is_empty());
         }
     }
+
+    #[test]
+    fn test_transaction_signature_prefix() {
+        let keypair = Keypair::new();
+        let message_transaction = create_message_transaction(&keypair, vec![keypair.pubkey()]);
+        let signature = message_transaction
+            .transaction
+            .transaction
+            .signature()
+            .as_ref()[..4]
+            .to_vec();
+        let mut signature_other = signature.clone();
+        signature_other[0] = signature_other[0].wrapping_add(1);
+
+        let create_config = |prefixes: Vec<(&str, Vec<u8>)>| SubscribeRequest {
+            accounts: HashMap::new(),
+            slots: HashMap::new(),
+            transactions: prefixes
+                .into_iter()
+                .map(|(name, signature_prefix)| {
+                    (
+                        name.to_owned(),
+                        SubscribeRequestFilterTransactions {
+                            vote: None,
+                            failed: None,
+                            signature: None,
+                            account_include: vec![],
+                            account_exclude: vec![],
+                            account_required: vec![],
+                            include_transaction_message: None,
+                            signature_prefix: Some(signature_prefix),
+                            include_meta: None,
+                            include_logs: None,
+                            include_inner_instructions: None,
+                            include_account_keys: None,
+                            invoked_programs: vec![],
+                            include_balances: None,
+                            include_return_data: None,
+                        },
+                    )
+                })
+                .collect(),
+            transactions_status: HashMap::new(),
+            blocks: HashMap::new(),
+            blocks_meta: HashMap::new(),
+            entry: HashMap::new(),
+            commitment: None,
+            accounts_data_slice: Vec::new(),
+            ping: None,
+            commit: None,
+            accounts_coalesce_window_ms: None,
+            accounts_chunking: None,
+            accounts_sample_rate: None,
+            filter_ack: None,
+            resume_token: None,
+            blocks_chunking: None,
+            include_created_at: None,
+        };
+        let limit = ConfigGrpcFilters::default();
+        assert!(Filter::new(&create_config(vec![("long", vec![0; 65])]), &limit).is_err());
+
+        let config = create_config(vec![("match", signature), ("mismatch", signature_other)]);
+        let filter = Filter::new(&config, &limit).unwrap();
+        let message = Message::Transaction(message_transaction);
+        let updates = filter.get_filters(&message, None).collect::<Vec<_>>();
+        assert_eq!(updates[0].0, vec!["match"]);
+    }
+
+    #[test]
+    fn test_transaction_invoked_programs() {
+        let keypair = Keypair::new();
+        let router = Pubkey::new_unique();
+        let target = Pubkey::new_unique();
+        let account_keys = vec![keypair.pubkey(), router, target];
+        // transaction invokes only `router`, `target` is invoked by `router` with CPI
+        let instructions = vec![CompiledInstruction::new_from_raw_parts(1, vec![], vec![2])];
+        let inner_instructions = vec![InnerInstructions {
+            index: 0,
+            instructions: vec![InnerInstruction {
+                instruction: CompiledInstruction::new_from_raw_parts(2, vec![], vec![0]),
+                stack_height: Some(2),
+            }],
+        }];
+
+        let create_config = |filters: Vec<(&str, Vec<Pubkey>, Vec<Pubkey>)>| SubscribeRequest {
+            transactions: filters
+                .into_iter()
+                .map(|(name, account_include, invoked_programs)| {
+                    (
+                        name.to_owned(),
+                        SubscribeRequestFilterTransactions {
+                            account_include: account_include
+                                .iter()
+                                .map(|pubkey| pubkey.to_string())
+                                .collect(),
+                            invoked_programs: invoked_programs
+                                .iter()
+                                .map(|pubkey| pubkey.to_string())
+                                .collect(),
+                            ..Default::default()
+                        },
+                    )
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let config = create_config(vec![
+            ("router", vec![], vec![router]),
+            ("target", vec![], vec![target]),
+            ("other", vec![], vec![Pubkey::new_unique()]),
+            ("target_account", vec![target], vec![]),
+        ]);
+        let filter = Filter::new(&config, &ConfigGrpcFilters::default()).unwrap();
+        let get_names = |message: MessageTransaction| {
+            let message = Message::Transaction(message);
+            let mut names = filter
+                .get_filters(&message, None)
+                .flat_map(|(names, _message)| names)
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+
+        let message = create_message_transaction_with_instructions(
+            &keypair,
+            account_keys.clone(),
+            instructions.clone(),
+            Some(inner_instructions),
+        );
+        assert_eq!(
+            get_names(message),
+            vec!["router", "target", "target_account"]
+        );
+
+        // inner instructions are not recorded, CPI can not be detected
+        let message = create_message_transaction_with_instructions(
+            &keypair,
+            account_keys.clone(),
+            instructions,
+            None,
+        );
+        assert_eq!(get_names(message), vec!["router", "target_account"]);
+
+        // `target` is only an account of the transaction, it's not invoked
+        let message = create_message_transaction_with_instructions(
+            &keypair,
+            account_keys,
+            vec![CompiledInstruction::new_from_raw_parts(1, vec![], vec![2])],
+            Some(vec![]),
+        );
+        assert_eq!(get_names(message), vec!["router", "target_account"]);
+
+        let mut limit = ConfigGrpcFilters::default();
+        limit.transactions.invoked_programs_max = 0;
+        assert!(Filter::new(
+            &create_config(vec![("target", vec![], vec![target])]),
+            &limit
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_filters_account_max_default() {
+        let create_config = |count| SubscribeRequest {
+            accounts: HashMap::from([(
+                "accounts".to_owned(),
+                SubscribeRequestFilterAccounts {
+                    account: (0..count)
+                        .map(|_| Pubkey::new_unique().to_string())
+                        .collect(),
+                    owner: vec![],
+                    filters: vec![],
+                    first_write_only: None,
+                    include_account_data: None,
+                    owner_change_only: None,
+                    lifecycle: vec![],
+                    changed_range: None,
+                },
+            )]),
+            slots: HashMap::new(),
+            transactions: HashMap::new(),
+            transactions_status: HashMap::new(),
+            blocks: HashMap::new(),
+            blocks_meta: HashMap::new(),
+            entry: HashMap::new(),
+            commitment: None,
+            accounts_data_slice: Vec::new(),
+            ping: None,
+            commit: None,
+            accounts_coalesce_window_ms: None,
+            accounts_chunking: None,
+            accounts_sample_rate: None,
+            filter_ack: None,
+            resume_token: None,
+            blocks_chunking: None,
+            include_created_at: None,
+        };
+        let limit = ConfigGrpcFilters::default();
+        let max = ConfigGrpcFiltersAccounts::ACCOUNT_MAX_DEFAULT;
+        assert!(Filter::new(&create_config(max), &limit).is_ok());
+        let error = Filter::new(&create_config(max + 1), &limit).unwrap_err();
+        assert!(error.to_string().contains(&max.to_string()));
+    }
+
+    #[test]
+    fn test_filters_account_first_write_only() {
+        let pubkey = Pubkey::new_unique();
+        let mut accounts = HashMap::new();
+        for (name, first_write_only) in [("first", Some(true)), ("all", None)] {
+            accounts.insert(
+                name.to_owned(),
+                SubscribeRequestFilterAccounts {
+                    account: vec![pubkey.to_string()],
+                    owner: vec![],
+                    filters: vec![],
+                    first_write_only,
+                    include_account_data: None,
+                    owner_change_only: None,
+                    lifecycle: vec![],
+                    changed_range: None,
+                },
+            );
+        }
+
+        let config = SubscribeRequest {
+            accounts,
+            slots: HashMap::new(),
+            transactions: HashMap::new(),
+            transactions_status: HashMap::new(),
+            blocks: HashMap::new(),
+            blocks_meta: HashMap::new(),
+            entry: HashMap::new(),
+            commitment: None,
+            accounts_data_slice: Vec::new(),
+            ping: None,
+            commit: None,
+            accounts_coalesce_window_ms: None,
+            accounts_chunking: None,
+            accounts_sample_rate: None,
+            filter_ack: None,
+            resume_token: None,
+            blocks_chunking: None,
+            include_created_at: None,
+        };
+        let limit = ConfigGrpcFilters::default();
+        let filter = Filter::new(&config, &limit).unwrap();
+
+        let get_filters = |message: &Message| {
+            let mut updates = filter.get_filters(message, None).collect::<Vec<_>>();
+            assert_eq!(updates.len(), 1);
+            let (mut filters, _message) = updates.remove(0);
+            filters.sort();
+            filters
+        };
+
+        assert_eq!(
+            get_filters(&create_message_account(pubkey, 100, 1, vec![])),
+            vec!["all", "first"]
+        );
+        assert_eq!(
+            get_filters(&create_message_account(pubkey, 100, 2, vec![])),
+            vec!["all"]
+        );
+        assert_eq!(
+            get_filters(&create_message_account(pubkey, 101, 3, vec![])),
+            vec!["all", "first"]
+        );
+
+        // state is not saved for accounts without matched `first_write_only` filters
+        let message = create_message_account(Pubkey::new_unique(), 102, 4, vec![]);
+        let _ = filter.get_filters(&message, None).count();
+
+        // state for finalized slots is removed
+        let message = Message::Slot(MessageSlot {
+            slot: 100,
+            parent: None,
+            status: CommitmentLevel::Finalized,
+            dead: false,
+            created_at: MessageCreatedAt::now(),
+        });
+        let _ = filter.get_filters(&message, None).count();
+        let first_write_sent = filter.accounts.first_write_sent.lock().unwrap();
+        assert_eq!(
+            first_write_sent.keys().copied().collect::<Vec<_>>(),
+            vec![101]
+        );
+    }
+
+    #[test]
+    fn test_filters_account_owner_change_only() {
+        let pubkey = Pubkey::new_unique();
+        let mut accounts = HashMap::new();
+        for (name, owner_change_only) in [("owner_change", Some(true)), ("all", None)] {
+            accounts.insert(
+                name.to_owned(),
+                SubscribeRequestFilterAccounts {
+                    account: vec![pubkey.to_string()],
+                    owner: vec![],
+                    filters: vec![],
+                    first_write_only: None,
+                    include_account_data: None,
+                    owner_change_only,
+                    lifecycle: vec![],
+                    changed_range: None,
+                },
+            );
+        }
+
+        let config = SubscribeRequest {
+            accounts,
+            slots: HashMap::new(),
+            transactions: HashMap::new(),
+            transactions_status: HashMap::new(),
+            blocks: HashMap::new(),
+            blocks_meta: HashMap::new(),
+            entry: HashMap::new(),
+            commitment: None,
+            accounts_data_slice: Vec::new(),
+            ping: None,
+            commit: None,
+            accounts_coalesce_window_ms: None,
+            accounts_chunking: None,
+            accounts_sample_rate: None,
+            filter_ack: None,
+            resume_token: None,
+            blocks_chunking: None,
+            include_created_at: None,
+        };
+        let limit = ConfigGrpcFilters::default();
+        let filter = Filter::new(&config, &limit).unwrap();
+
+        let get_filters = |write_version: u64, owner: Pubkey| {
+            let mut message = create_message_account(pubkey, 100, write_version, vec![]);
+            if let Message::Account(message) = &mut message {
+                message.account.owner = owner;
+            }
+            let mut updates = filter.get_filters(&message, None).collect::<Vec<_>>();
+            assert_eq!(updates.len(), 1);
+            let (mut filters, _message) = updates.remove(0);
+            filters.sort();
+            filters
+        };
+
+        let owner_a = Pubkey::new_unique();
+        let owner_b = Pubkey::new_unique();
+        // first seen owner is not a change
+        assert_eq!(get_filters(1, owner_a), vec!["all"]);
+        assert_eq!(get_filters(2, owner_a), vec!["all"]);
+        assert_eq!(get_filters(3, owner_b), vec!["all", "owner_change"]);
+        assert_eq!(get_filters(4, owner_b), vec!["all"]);
+        assert_eq!(get_filters(5, owner_a), vec!["all", "owner_change"]);
+    }
+
+    #[test]
+    fn test_filters_account_lifecycle() {
+        let create_config = |lifecycle: Vec<i32>| SubscribeRequest {
+            accounts: HashMap::from([
+                (
+                    "lifecycle".to_owned(),
+                    SubscribeRequestFilterAccounts {
+                        lifecycle,
+                        ..Default::default()
+                    },
+                ),
+                ("all".to_owned(), SubscribeRequestFilterAccounts::default()),
+            ]),
+            ..Default::default()
+        };
+        let limit = ConfigGrpcFilters::default();
+        assert!(Filter::new(&create_config(vec![3]), &limit).is_err());
+
+        // updates are not tagged
+        let mut limit_disabled = ConfigGrpcFilters::default();
+        limit_disabled.accounts.lifecycle = false;
+        let request = create_config(vec![AccountLifecycle::Created as i32]);
+        assert!(Filter::new(&request, &limit_disabled).is_err());
+
+        let filter = Filter::new(
+            &create_config(vec![
+                AccountLifecycle::Created as i32,
+                AccountLifecycle::Closed as i32,
+            ]),
+            &limit,
+        )
+        .unwrap();
+        let get_filters = |lifecycle| {
+            let mut message = create_message_account(Pubkey::new_unique(), 100, 1, vec![]);
+            if let Message::Account(message) = &mut message {
+                message.lifecycle = lifecycle;
+            }
+            let mut filters = filter
+                .get_filters(&message, None)
+                .flat_map(|(filters, _message)| filters)
+                .collect::<Vec<_>>();
+            filters.sort();
+            filters
+        };
+        assert_eq!(get_filters(AccountLifecycle::Modified), vec!["all"]);
+        assert_eq!(
+            get_filters(AccountLifecycle::Created),
+            vec!["all", "lifecycle"]
+        );
+        assert_eq!(
+            get_filters(AccountLifecycle::Closed),
+            vec!["all", "lifecycle"]
+        );
+    }
+
+    #[test]
+    fn test_filters_account_changed_range() {
+        let create_config = |offset, length| SubscribeRequest {
+            accounts: HashMap::from([
+                (
+                    "changed".to_owned(),
+                    SubscribeRequestFilterAccounts {
+                        changed_range: Some(SubscribeRequestFilterAccountsChangedRange {
+                            offset,
+                            length,
+                        }),
+                        ..Default::default()
+                    },
+                ),
+                ("all".to_owned(), SubscribeRequestFilterAccounts::default()),
+            ]),
+            ..Default::default()
+        };
+        let limit = ConfigGrpcFilters::default();
+        assert!(Filter::new(&create_config(0, 0), &limit).is_err());
+        assert!(Filter::new(&create_config(0, 1_025), &limit).is_err());
+
+        let filter = Filter::new(&create_config(2, 2), &limit).unwrap();
+        let pubkey = Pubkey::new_unique();
+        let pubkey_other = Pubkey::new_unique();
+        let get_filters = |pubkey, data| {
+            let message = create_message_account(pubkey, 100, 1, data);
+            let mut filters = filter
+                .get_filters(&message, None)
+                .flat_map(|(filters, _message)| filters)
+                .collect::<Vec<_>>();
+            filters.sort();
+            filters
+        };
+        // cold cache, every pubkey is tracked separately
+        assert_eq!(
+            get_filters(pubkey, vec![0, 0, 1, 1]),
+            vec!["all", "changed"]
+        );
+        assert_eq!(
+            get_filters(pubkey_other, vec![0, 0, 1, 1]),
+            vec!["all", "changed"]
+        );
+        // bytes out of the range are changed
+        assert_eq!(get_filters(pubkey, vec![9, 9, 1, 1, 9]), vec!["all"]);
+        assert_eq!(
+            get_filters(pubkey, vec![0, 0, 1, 2]),
+            vec!["all", "changed"]
+        );
+        assert_eq!(get_filters(pubkey, vec![0, 0, 1, 2]), vec!["all"]);
+        // shrunk data is a change, even if the range is past the end of the data
+        assert_eq!(get_filters(pubkey, vec![0, 0, 1]), vec!["all", "changed"]);
+        assert_eq!(get_filters(pubkey, vec![0]), vec!["all", "changed"]);
+        assert_eq!(get_filters(pubkey, vec![]), vec!["all", "changed"]);
+        assert_eq!(get_filters(pubkey, vec![]), vec!["all"]);
+        // grown data is a change only while the range is not complete
+        assert_eq!(get_filters(pubkey, vec![0]), vec!["all", "changed"]);
+        assert_eq!(
+            get_filters(pubkey, vec![0, 0, 1, 2]),
+            vec!["all", "changed"]
+        );
+        assert_eq!(get_filters(pubkey, vec![0, 0, 1, 2, 3]), vec!["all"]);
+    }
+
+    #[test]
+    fn test_filters_account_executable_rent_epoch() {
+        let pubkey = Pubkey::new_unique();
+        let rent_epoch = |min, max, rent_exempt| SubscribeRequestFilterAccountsFilter {
+            filter: Some(AccountsFilterDataOneof::RentEpoch(
+                SubscribeRequestFilterAccountsFilterRentEpoch {
+                    min,
+                    max,
+                    rent_exempt,
+                },
+            )),
+        };
+        let mut accounts = HashMap::new();
+        for (name, filters) in [
+            (
+                "executable",
+                vec![SubscribeRequestFilterAccountsFilter {
+                    filter: Some(AccountsFilterDataOneof::Executable(true)),
+                }],
+            ),
+            ("range", vec![rent_epoch(Some(10), Some(20), None)]),
+            ("from", vec![rent_epoch(Some(10), None, None)]),
+            ("exempt", vec![rent_epoch(None, None, Some(true))]),
+            (
+                "executable_exempt",
+                vec![
+                    SubscribeRequestFilterAccountsFilter {
+                        filter: Some(AccountsFilterDataOneof::Executable(true)),
+                    },
+                    rent_epoch(None, Some(5), Some(true)),
+                ],
+            ),
+        ] {
+            accounts.insert(
+                name.to_owned(),
+                SubscribeRequestFilterAccounts {
+                    account: vec![pubkey.to_string()],
+                    owner: vec![],
+                    filters,
+                    first_write_only: None,
+                    include_account_data: None,
+                    owner_change_only: None,
+                    lifecycle: vec![],
+                    changed_range: None,
+                },
+            );
+        }
+
+        let config = SubscribeRequest {
+            accounts,
+            slots: HashMap::new(),
+            transactions: HashMap::new(),
+            transactions_status: HashMap::new(),
+            blocks: HashMap::new(),
+            blocks_meta: HashMap::new(),
+            entry: HashMap::new(),
+            commitment: None,
+            accounts_data_slice: Vec::new(),
+            ping: None,
+            commit: None,
+            accounts_coalesce_window_ms: None,
+            accounts_chunking: None,
+            accounts_sample_rate: None,
+            filter_ack: None,
+            resume_token: None,
+            blocks_chunking: None,
+            include_created_at: None,
+        };
+        let limit = ConfigGrpcFilters::default();
+        let filter = Filter::new(&config, &limit).unwrap();
+
+        let get_filters = |executable: bool, rent_epoch: u64| {
+            let mut message = create_message_account(pubkey, 100, 1, vec![]);
+            if let Message::Account(message) = &mut message {
+                message.account.executable = executable;
+                message.account.rent_epoch = rent_epoch;
+            }
+            let mut filters = filter
+                .get_filters(&message, None)
+                .flat_map(|(filters, _message)| filters)
+                .collect::<Vec<_>>();
+            filters.sort();
+            filters
+        };
+
+        assert_eq!(get_filters(false, 5), Vec::<String>::new());
+        assert_eq!(
+            get_filters(true, 5),
+            vec!["executable", "executable_exempt"]
+        );
+        assert_eq!(get_filters(false, 15), vec!["from", "range"]);
+        assert_eq!(get_filters(true, 25), vec!["executable", "from"]);
+        // sentinel epoch of rent exempt accounts is not a part of any range
+        assert_eq!(get_filters(false, u64::MAX), vec!["exempt"]);
+        assert_eq!(
+            get_filters(true, u64::MAX),
+            vec!["executable", "executable_exempt", "exempt"]
+        );
+
+        for (min, max, rent_exempt) in [
+            (None, None, None),
+            (None, None, Some(false)),
+            (Some(2), Some(1), None),
+        ] {
+            let mut config = config.clone();
+            config.accounts = HashMap::from([(
+                "invalid".to_owned(),
+                SubscribeRequestFilterAccounts {
+                    filters: vec![rent_epoch(min, max, rent_exempt)],
+                    ..Default::default()
+                },
+            )]);
+            assert!(Filter::new(&config, &limit).is_err());
+        }
+    }
+
+    #[test]
+    fn test_filters_account_presets() {
+        let config = SubscribeRequest {
+            accounts: FilterPreset::ALL
+                .into_iter()
+                .map(|preset| (preset.filter_name(), preset.to_filter()))
+                .collect(),
+            ..Default::default()
+        };
+        let limit = ConfigGrpcFilters::default();
+        let filter = Filter::new(&config, &limit).unwrap();
+
+        let get_filters = |owner: &str, data: Vec<u8>| {
+            let mut message = create_message_account(Pubkey::new_unique(), 100, 1, data);
+            if let Message::Account(message) = &mut message {
+                message.account.owner = Pubkey::from_str(owner).unwrap();
+            }
+            filter
+                .get_filters(&message, None)
+                .flat_map(|(filters, _message)| filters)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            get_filters(SPL_TOKEN_PROGRAM_ID, vec![0; 165]),
+            vec!["preset:spl_token"]
+        );
+        // mint
+        assert!(get_filters(SPL_TOKEN_PROGRAM_ID, vec![0; 82]).is_empty());
+        assert_eq!(
+            get_filters(SYSTEM_PROGRAM_ID, vec![]),
+            vec!["preset:system"]
+        );
+        // token account with initialized state and extensions
+        let mut data = vec![0; 170];
+        data[108] = 1;
+        data[165] = 2;
+        assert_eq!(
+            get_filters(SPL_TOKEN_2022_PROGRAM_ID, data),
+            vec!["preset:spl_token_2022"]
+        );
+
+        // preset names are reserved for filters expanded from presets only with enabled validation
+        let mut invalid = FilterPreset::SplToken.to_filter();
+        invalid.owner = vec![SPL_TOKEN_2022_PROGRAM_ID.to_owned()];
+        let mut limit_presets = ConfigGrpcFilters::default();
+        limit_presets.accounts.presets = true;
+        for (name, filter) in [
+            ("preset:spl_token", invalid),
+            ("preset:unknown", FilterPreset::System.to_filter()),
+        ] {
+            let config = SubscribeRequest {
+                accounts: HashMap::from([(name.to_owned(), filter)]),
+                ..Default::default()
+            };
+            assert!(Filter::new(&config, &limit).is_ok());
+            assert!(Filter::new(&config, &limit_presets).is_err());
+        }
+
+        // other options can be changed
+        let config = SubscribeRequest {
+            accounts: HashMap::from([(
+                FilterPreset::SplToken.filter_name(),
+                SubscribeRequestFilterAccounts {
+                    include_account_data: Some(false),
+                    ..FilterPreset::SplToken.to_filter()
+                },
+            )]),
+            ..Default::default()
+        };
+        assert!(Filter::new(&config, &limit_presets).is_ok());
+    }
+
+    #[test]
+    fn test_filters_account_sample() {
+        // membership depends only on the pubkey, values are fixed to catch hash changes
+        let pubkey = Pubkey::from_str("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap();
+        assert_eq!(FilterAccountsSample::hash(&pubkey), 9909040844142038055);
+
+        let pubkeys = (0..1000).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let sample = FilterAccountsSample::new(0.2).unwrap();
+        let included = pubkeys
+            .iter()
+            .filter(|pubkey| sample.contains(pubkey))
+            .collect::<Vec<_>>();
+        assert!((100..300).contains(&included.len()));
+        for pubkey in included.iter() {
+            assert!(FilterAccountsSample::new(0.2).unwrap().contains(pubkey));
+            assert!(FilterAccountsSample::new(0.5).unwrap().contains(pubkey));
+        }
+        assert!(pubkeys
+            .iter()
+            .all(|pubkey| FilterAccountsSample::new(1.0).unwrap().contains(pubkey)));
+        assert!(!pubkeys
+            .iter()
+            .any(|pubkey| FilterAccountsSample::new(0.0).unwrap().contains(pubkey)));
+        for rate in [-0.1, 1.1, f64::NAN] {
+            assert!(FilterAccountsSample::new(rate).is_err());
+        }
+
+        let mut config = SubscribeRequest {
+            accounts: HashMap::from([(
+                "all".to_owned(),
+                SubscribeRequestFilterAccounts::default(),
+            )]),
+            accounts_sample_rate: Some(0.2),
+            ..Default::default()
+        };
+        let limit = ConfigGrpcFilters::default();
+        let filter = Filter::new(&config, &limit).unwrap();
+        for pubkey in pubkeys.iter() {
+            let message = create_message_account(*pubkey, 100, 1, vec![]);
+            let updates = filter.get_update(&message, None).count();
+            assert_eq!(updates, usize::from(included.contains(&pubkey)));
+        }
+
+        config.accounts_sample_rate = Some(2.0);
+        assert!(Filter::new(&config, &limit).is_err());
+    }
+
+    #[test]
+    fn test_filters_enabled_update_types() {
+        let limit: ConfigGrpcFilters =
+            serde_json::from_str(r#"{"enabled_update_types": ["slots", "transactions_status"]}"#)
+                .unwrap();
+        let mut config = SubscribeRequest {
+            slots: HashMap::from([("slots".to_owned(), SubscribeRequestFilterSlots::default())]),
+            transactions_status: HashMap::from([(
+                "status".to_owned(),
+                SubscribeRequestFilterTransactions::default(),
+            )]),
+            ..Default::default()
+        };
+        assert!(Filter::new(&config, &limit).is_ok());
+
+        config.accounts = HashMap::from([(
+            "accounts".to_owned(),
+            SubscribeRequestFilterAccounts::default(),
+        )]);
+        let error = Filter::new(&config, &limit).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "`accounts` updates are disabled by the server"
+        );
+        assert!(Filter::new(&config, &ConfigGrpcFilters::default()).is_ok());
+
+        assert!(serde_json::from_str::<ConfigGrpcFilters>(
+            r#"{"enabled_update_types": ["account"]}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_filters_ack() {
+        let limit = ConfigGrpcFilters::default();
+        let get_ack =
+            |config: &SubscribeRequest, chunking_supported: bool| match Filter::new(config, &limit)
+                .unwrap()
+                .get_ack_msg(chunking_supported)
+                .map(|msg| msg.update_oneof)
+            {
+                Some(Some(UpdateOneof::FilterAck(ack))) => Some(ack),
+                None => None,
+                _ => panic!("expected filter ack"),
+            };
+
+        let mut config = SubscribeRequest {
+            accounts: HashMap::from([(
+                "accounts".to_owned(),
+                SubscribeRequestFilterAccounts {
+                    include_account_data: Some(false),
+                    ..Default::default()
+                },
+            )]),
+            slots: HashMap::from([(
+                "slots".to_owned(),
+                SubscribeRequestFilterSlots {
+                    include_dead: Some(true),
+                    ..Default::default()
+                },
+            )]),
+            transactions_status: HashMap::from([(
+                "status".to_owned(),
+                SubscribeRequestFilterTransactions {
+                    include_logs: Some(false),
+                    ..Default::default()
+                },
+            )]),
+            commitment: Some(CommitmentLevel::Confirmed as i32),
+            accounts_data_slice: vec![SubscribeRequestAccountsDataSlice {
+                offset: 0,
+                length: 8,
+            }],
+            accounts_chunking: Some(true),
+            ..Default::default()
+        };
+        let ack = get_ack(&config, false).unwrap();
+        let filter = ack.filter.unwrap();
+        assert_eq!(filter.commitment, Some(CommitmentLevel::Confirmed as i32));
+        assert_eq!(filter.filter_ack, Some(true));
+        let accounts = &filter.accounts["accounts"];
+        assert_eq!(accounts.include_account_data, Some(false));
+        assert_eq!(accounts.first_write_only, Some(false));
+        assert_eq!(accounts.owner_change_only, Some(false));
+        assert_eq!(filter.slots["slots"].filter_by_commitment, Some(false));
+        assert_eq!(
+            ack.ignored,
+            vec![
+                "accounts_chunking: message size is not limited by the server",
+                "accounts_data_slice: account data is not included by any accounts filter",
+                "slots.slots.include_dead: dead slots are sent only with `processed` commitment",
+                "transactions_status.status.include_logs: status updates do not contain transaction",
+            ]
+        );
+        assert_eq!(get_ack(&config, true).unwrap().ignored.len(), 3);
+
+        config.accounts.clear();
+        config.slots.clear();
+        config.transactions_status.clear();
+        config.transactions = HashMap::from([(
+            "transactions".to_owned(),
+            SubscribeRequestFilterTransactions::default(),
+        )]);
+        config.blocks =
+            HashMap::from([("blocks".to_owned(), SubscribeRequestFilterBlocks::default())]);
+        let ack = get_ack(&config, true).unwrap();
+        let filter = ack.filter.unwrap();
+        let transactions = &filter.transactions["transactions"];
+        assert_eq!(transactions.include_transaction_message, Some(true));
+        assert_eq!(transactions.include_meta, Some(true));
+        assert_eq!(transactions.vote, None);
+        let blocks = &filter.blocks["blocks"];
+        assert_eq!(blocks.include_transactions, Some(true));
+        assert_eq!(blocks.include_accounts, Some(false));
+        assert_eq!(blocks.include_entries, Some(false));
+        assert_eq!(blocks.include_rewards, Some(true));
+        assert_eq!(
+            ack.ignored,
+            vec![
+                "accounts_chunking: no accounts filters",
+                "accounts_data_slice: no accounts filters"
+            ]
+        );
+
+        config.filter_ack = Some(false);
+        assert!(get_ack(&config, true).is_none());
+        config.filter_ack = None;
+        config.ping = Some(SubscribeRequestPing { id: 1 });
+        assert!(get_ack(&config, true).is_none());
+    }
+
+    #[test]
+    fn test_filters_account_first_write_only_commitment() {
+        let mut accounts = HashMap::new();
+        accounts.insert(
+            "first".to_owned(),
+            SubscribeRequestFilterAccounts {
+                account: vec![],
+                owner: vec![],
+                filters: vec![],
+                first_write_only: Some(true),
+                include_account_data: None,
+                owner_change_only: None,
+                lifecycle: vec![],
+                changed_range: None,
+            },
+        );
+
+        let config = SubscribeRequest {
+            accounts,
+            slots: HashMap::new(),
+            transactions: HashMap::new(),
+            transactions_status: HashMap::new(),
+            blocks: HashMap::new(),
+            blocks_meta: HashMap::new(),
+            entry: HashMap::new(),
+            commitment: Some(CommitmentLevel::Confirmed as i32),
+            accounts_data_slice: Vec::new(),
+            ping: None,
+            commit: None,
+            accounts_coalesce_window_ms: None,
+            accounts_chunking: None,
+            accounts_sample_rate: None,
+            filter_ack: None,
+            resume_token: None,
+            blocks_chunking: None,
+            include_created_at: None,
+        };
+        let limit = ConfigGrpcFilters::default();
+        // only last write is sent for confirmed commitment
+        assert!(Filter::new(&config, &limit).is_err());
+    }
+
+    #[test]
+    fn test_filters_slots_range() {
+        let create_config = |from_slot, to_slot| SubscribeRequest {
+            accounts: HashMap::new(),
+            slots: HashMap::from([(
+                "range".to_owned(),
+                SubscribeRequestFilterSlots {
+                    filter_by_commitment: None,
+                    from_slot,
+                    to_slot,
+                    include_dead: None,
+                },
+            )]),
+            transactions: HashMap::new(),
+            transactions_status: HashMap::new(),
+            blocks: HashMap::new(),
+            blocks_meta: HashMap::new(),
+            entry: HashMap::new(),
+            commitment: None,
+            accounts_data_slice: Vec::new(),
+            ping: None,
+            commit: None,
+            accounts_coalesce_window_ms: None,
+            accounts_chunking: None,
+            accounts_sample_rate: None,
+            filter_ack: None,
+            resume_token: None,
+            blocks_chunking: None,
+            include_created_at: None,
+        };
+        let limit = ConfigGrpcFilters::default();
+        assert!(Filter::new(&create_config(Some(11), Some(10)), &limit).is_err());
+
+        let filter = Filter::new(&create_config(Some(5), Some(10)), &limit).unwrap();
+        let create_message = |slot, status| {
+            Message::Slot(MessageSlot {
+                slot,
+                parent: None,
+                status,
+                dead: false,
+                created_at: MessageCreatedAt::now(),
+            })
+        };
+        for (slot, in_range) in [(4, false), (5, true), (10, true), (11, false)] {
+            let message = create_message(slot, CommitmentLevel::Processed);
+            assert_eq!(filter.get_update(&message, None).count() == 1, in_range);
+        }
+
+        assert!(!filter.is_slots_range_ended(&create_message(11, CommitmentLevel::Processed)));
+        assert!(!filter.is_slots_range_ended(&create_message(10, CommitmentLevel::Finalized)));
+        assert!(filter.is_slots_range_ended(&create_message(11, CommitmentLevel::Finalized)));
+
+        // subscription without `to_slot` is never ended
+        let filter = Filter::new(&create_config(Some(5), None), &limit).unwrap();
+        assert!(!filter.is_slots_range_ended(&create_message(11, CommitmentLevel::Finalized)));
+        assert_eq!(filter.get_slots_from_slot(), Some(5));
+        let filter = Filter::new(&create_config(None, Some(10)), &limit).unwrap();
+        assert_eq!(filter.get_slots_from_slot(), None);
+
+        // subscription with range end can't have other filters
+        let mut config = create_config(Some(5), Some(10));
+        config.accounts.insert(
+            "accounts".to_owned(),
+            SubscribeRequestFilterAccounts::default(),
+        );
+        assert!(Filter::new(&config, &limit).is_err());
+        config.slots.get_mut("range").unwrap().to_slot = None;
+        assert!(Filter::new(&config, &limit).is_ok());
+    }
+
+    #[test]
+    fn test_filters_include_created_at() {
+        let create_config = |include_created_at| SubscribeRequest {
+            slots: HashMap::from([("slots".to_owned(), SubscribeRequestFilterSlots::default())]),
+            include_created_at,
+            ..Default::default()
+        };
+        let limit = ConfigGrpcFilters::default();
+        let message = Message::Slot(MessageSlot {
+            slot: 1,
+            parent: None,
+            status: CommitmentLevel::Processed,
+            dead: false,
+            created_at: MessageCreatedAt {
+                system: SystemTime::UNIX_EPOCH + Duration::from_micros(1_700_000_000_123_456),
+                ..MessageCreatedAt::now()
+            },
+        });
+
+        for include_created_at in [None, Some(false)] {
+            let filter = Filter::new(&create_config(include_created_at), &limit).unwrap();
+            let updates = filter.get_update(&message, None).collect::<Vec<_>>();
+            assert_eq!(updates.len(), 1);
+            assert_eq!(updates[0].created_at_us, None);
+        }
+
+        let filter = Filter::new(&create_config(Some(true)), &limit).unwrap();
+        let updates = filter.get_update(&message, None).collect::<Vec<_>>();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].created_at_us, Some(1_700_000_000_123_456));
+    }
+
+    #[test]
+    fn test_filters_account_include_data() {
+        let pubkey = Pubkey::new_unique();
+        let mut accounts = HashMap::new();
+        for (name, include_account_data) in [("with_data", None), ("without_data", Some(false))] {
+            accounts.insert(
+                name.to_owned(),
+                SubscribeRequestFilterAccounts {
+                    account: vec![],
+                    owner: vec![],
+                    filters: vec![SubscribeRequestFilterAccountsFilter {
+                        filter: Some(AccountsFilterDataOneof::Memcmp(
+                            SubscribeRequestFilterAccountsFilterMemcmp {
+                                offset: 1,
+                                data: Some(AccountsFilterMemcmpOneof::Bytes(vec![2, 3])),
+                            },
+                        )),
+                    }],
+                    first_write_only: None,
+                    include_account_data,
+                    owner_change_only: None,
+                    lifecycle: vec![],
+                    changed_range: None,
+                },
+            );
+        }
+
+        let config = SubscribeRequest {
+            accounts,
+            slots: HashMap::new(),
+            transactions: HashMap::new(),
+            transactions_status: HashMap::new(),
+            blocks: HashMap::new(),
+            blocks_meta: HashMap::new(),
+            entry: HashMap::new(),
+            commitment: None,
+            accounts_data_slice: Vec::new(),
+            ping: None,
+            commit: None,
+            accounts_coalesce_window_ms: None,
+            accounts_chunking: None,
+            accounts_sample_rate: None,
+            filter_ack: None,
+            resume_token: None,
+            blocks_chunking: None,
+            include_created_at: None,
+        };
+        let limit = ConfigGrpcFilters::default();
+        let filter = Filter::new(&config, &limit).unwrap();
+
+        // data filters are matched against full data
+        let message = create_message_account(pubkey, 100, 1, vec![1, 2, 3, 4]);
+        let updates = filter.get_filters(&message, None).collect::<Vec<_>>();
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[0].0, vec!["with_data"]);
+        assert!(matches!(updates[0].1, MessageRef::Account(_)));
+        assert_eq!(updates[1].0, vec!["without_data"]);
+        assert!(matches!(updates[1].1, MessageRef::AccountWithoutData(_)));
+
+        let data = updates
+            .iter()
+            .map(|(_filters, message)| match message.to_proto(&[]) {
+                UpdateOneof::Account(msg) => msg.account.unwrap().data,
+                _ => panic!("expected account update"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(data, vec![vec![1, 2, 3, 4], vec![]]);
+
+        let message = create_message_account(pubkey, 100, 2, vec![1, 2, 4, 4]);
+        for (filters, _message) in filter.get_filters(&message, None) {
+            assert!(filters.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_filters_account_memcmp_shared() {
+        let owner = Pubkey::new_unique();
+        let memcmp = |offset, data| SubscribeRequestFilterAccountsFilter {
+            filter: Some(AccountsFilterDataOneof::Memcmp(
+                SubscribeRequestFilterAccountsFilterMemcmp {
+                    offset,
+                    data: Some(AccountsFilterMemcmpOneof::Bytes(data)),
+                },
+            )),
+        };
+        let mut accounts = HashMap::new();
+        for (name, owner, filters) in [
+            ("a", vec![], vec![memcmp(0, vec![1, 2])]),
+            ("b", vec![], vec![memcmp(0, vec![1, 2]), memcmp(2, vec![3])]),
+            ("c", vec![owner.to_string()], vec![memcmp(0, vec![1, 2])]),
+            ("d", vec![], vec![memcmp(2, vec![3]), memcmp(10, vec![1])]),
+        ] {
+            accounts.insert(
+                name.to_owned(),
+                SubscribeRequestFilterAccounts {
+                    account: vec![],
+                    owner,
+                    filters,
+                    first_write_only: None,
+                    include_account_data: None,
+                    owner_change_only: None,
+                    lifecycle: vec![],
+                    changed_range: None,
+                },
+            );
+        }
+
+        let config = SubscribeRequest {
+            accounts,
+            slots: HashMap::new(),
+            transactions: HashMap::new(),
+            transactions_status: HashMap::new(),
+            blocks: HashMap::new(),
+            blocks_meta: HashMap::new(),
+            entry: HashMap::new(),
+            commitment: None,
+            accounts_data_slice: Vec::new(),
+            ping: None,
+            commit: None,
+            accounts_coalesce_window_ms: None,
+            accounts_chunking: None,
+            accounts_sample_rate: None,
+            filter_ack: None,
+            resume_token: None,
+            blocks_chunking: None,
+            include_created_at: None,
+        };
+        let limit = ConfigGrpcFilters::default();
+        let filter = Filter::new(&config, &limit).unwrap();
+        assert_eq!(filter.accounts.memcmp.windows.len(), 3);
+
+        let get_filters = |data, account_owner| {
+            let mut message = create_message_account(Pubkey::new_unique(), 100, 1, data);
+            if let (Message::Account(message), Some(owner)) = (&mut message, account_owner) {
+                message.account.owner = owner;
+            }
+            let mut names = filter
+                .get_filters(&message, None)
+                .flat_map(|(filters, _message)| filters)
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+        assert_eq!(get_filters(vec![1, 2, 3], None), vec!["a", "b"]);
+        assert_eq!(get_filters(vec![1, 2, 4], None), vec!["a"]);
+        assert_eq!(get_filters(vec![1, 2, 3], Some(owner)), vec!["a", "b", "c"]);
+        assert!(get_filters(vec![1], Some(owner)).is_empty());
+    }
+
+    #[test]
+    fn test_filters_transaction_include_message() {
+        let keypair_a = Keypair::new();
+        let account_key_a = keypair_a.pubkey();
+        let mut transactions = HashMap::new();
+        transactions.insert(
+            "serum".to_string(),
+            SubscribeRequestFilterTransactions {
+                vote: None,
+                failed: None,
+                signature: None,
+                account_include: vec![account_key_a.to_string()],
+                account_exclude: vec![],
+                account_required: vec![],
+                include_transaction_message: Some(false),
+                signature_prefix: None,
+                include_meta: None,
+                include_logs: None,
+                include_inner_instructions: None,
+                include_account_keys: None,
+                invoked_programs: vec![],
+                include_balances: None,
+                include_return_data: None,
+            },
+        );
+
+        let config = SubscribeRequest {
+            accounts: HashMap::new(),
+            slots: HashMap::new(),
+            transactions,
+            transactions_status: HashMap::new(),
+            blocks: HashMap::new(),
+            blocks_meta: HashMap::new(),
+            entry: HashMap::new(),
+            commitment: None,
+            accounts_data_slice: Vec::new(),
+            ping: None,
+            commit: None,
+            accounts_coalesce_window_ms: None,
+            accounts_chunking: None,
+            accounts_sample_rate: None,
+            filter_ack: None,
+            resume_token: None,
+            blocks_chunking: None,
+            include_created_at: None,
+        };
+        let limit = ConfigGrpcFilters::default();
+        let filter = Filter::new(&config, &limit).unwrap();
+
+        let message_transaction = create_message_transaction(&keypair_a, vec![account_key_a]);
+        let message = Message::Transaction(message_transaction);
+        let updates = filter
+            .get_filters(&message, None)
+            .filter(|(filters, _message)| !filters.is_empty())
+            .collect::<Vec<_>>();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].0, vec!["serum"]);
+        match updates[0].1.to_proto(&[]) {
+            UpdateOneof::Transaction(msg) => {
+                let tx = msg.transaction.unwrap();
+                assert!(tx.transaction.is_none());
+                assert!(tx.meta.is_some());
+                assert_eq!(tx.signature.len(), 64);
+            }
+            _ => panic!("expected transaction update"),
+        }
+    }
+
+    #[test]
+    fn test_filters_transaction_fields() {
+        let keypair_a = Keypair::new();
+        let account_key_a = keypair_a.pubkey();
+        let mut transactions = HashMap::new();
+        for (name, include) in [("full", None), ("light", Some(false))] {
+            transactions.insert(
+                name.to_owned(),
+                SubscribeRequestFilterTransactions {
+                    vote: None,
+                    failed: None,
+                    signature: None,
+                    account_include: vec![account_key_a.to_string()],
+                    account_exclude: vec![],
+                    account_required: vec![],
+                    include_transaction_message: None,
+                    signature_prefix: None,
+                    include_meta: None,
+                    include_logs: include,
+                    include_inner_instructions: include,
+                    include_account_keys: include,
+                    invoked_programs: vec![],
+                    include_balances: include,
+                    include_return_data: include,
+                },
+            );
+        }
+
+        let config = SubscribeRequest {
+            transactions,
+            ..Default::default()
+        };
+        let limit = ConfigGrpcFilters::default();
+        let filter = Filter::new(&config, &limit).unwrap();
+
+        let mut message_transaction = create_message_transaction(&keypair_a, vec![account_key_a]);
+        message_transaction.transaction.meta.log_messages = Some(vec!["log".to_owned()]);
+        message_transaction.transaction.meta.inner_instructions = Some(vec![]);
+        message_transaction.transaction.meta.pre_balances = vec![10];
+        message_transaction.transaction.meta.post_balances = vec![5];
+        message_transaction.transaction.meta.return_data = Some(TransactionReturnData {
+            program_id: account_key_a,
+            data: vec![1],
+        });
+        let message = Message::Transaction(message_transaction);
+        let updates = filter
+            .get_filters(&message, None)
+            .filter(|(filters, _message)| !filters.is_empty())
+            .collect::<Vec<_>>();
+        assert_eq!(updates.len(), 2);
+
+        let get_transaction = |message: &MessageRef| match message.to_proto(&[]) {
+            UpdateOneof::Transaction(msg) => msg.transaction.unwrap(),
+            _ => panic!("expected transaction update"),
+        };
+
+        assert_eq!(updates[0].0, vec!["full"]);
+        let tx = get_transaction(&updates[0].1);
+        let meta = tx.meta.unwrap();
+        assert_eq!(meta.log_messages, vec!["log".to_owned()]);
+        assert!(!meta.log_messages_none && !meta.inner_instructions_none);
+        assert_eq!((meta.pre_balances, meta.post_balances), (vec![10], vec![5]));
+        assert!(meta.return_data.is_some() && !meta.return_data_none);
+        assert_eq!(
+            tx.transaction.unwrap().message.unwrap().account_keys.len(),
+            1
+        );
+
+        // omitted parts are cleared and marked as absent
+        assert_eq!(updates[1].0, vec!["light"]);
+        let tx = get_transaction(&updates[1].1);
+        let meta = tx.meta.unwrap();
+        assert!(meta.log_messages.is_empty() && meta.log_messages_none);
+        assert!(meta.inner_instructions.is_empty() && meta.inner_instructions_none);
+        assert!(meta.pre_balances.is_empty() && meta.post_balances.is_empty());
+        assert!(meta.return_data.is_none() && meta.return_data_none);
+        assert!(tx
+            .transaction
+            .unwrap()
+            .message
+            .unwrap()
+            .account_keys
+            .is_empty());
+    }
 }
@@ -1,8 +1,21 @@
 use {
     crate::{
-        config::{ConfigBlockFailAction, ConfigGrpc, ConfigGrpcFilters},
+        admission::{AdmissionControl, AdmissionPermit},
+        cache::GenerationCache,
+        checkpoint::SlotCheckpoint,
+        coalesce::AccountsCoalesce,
+        config::{
+            ConfigBlockFailAction, ConfigGrpc, ConfigGrpcFilters, ConfigGrpcFiltersBlocks,
+            ConfigGrpcReconnect, ConfigGrpcShedding, ConfigGrpcUnixSocket,
+        },
         filters::{Filter, FilterAccountsDataSlice},
-        prom::{self, DebugClientMessage, CONNECTIONS_TOTAL, MESSAGE_QUEUE_SIZE},
+        prom::{
+            self, DebugClientMessage, ACCOUNTS_OVERSIZED_TOTAL, BLOCKS_OVERSIZED_TOTAL,
+            CONNECTIONS_TOTAL, MESSAGE_QUEUE_SIZE, RECONNECT_STORM_SIZE, SUBSCRIPTIONS_SHED_TOTAL,
+        },
+        quota::QuotaTracker,
+        replay::{ReplayBuffer, ReplayError, ResumeToken},
+        skew::SlotSkew,
         version::GrpcVersionInfo,
     },
     agave_geyser_plugin_interface::geyser_plugin_interface::{
@@ -10,36 +23,47 @@ use {
         SlotStatus,
     },
     anyhow::Context,
+    futures::future::FutureExt,
     log::{error, info},
+    rand::Rng,
     solana_sdk::{
         clock::{UnixTimestamp, MAX_RECENT_BLOCKHASHES},
         pubkey::Pubkey,
         signature::Signature,
+        system_program,
         transaction::SanitizedTransaction,
     },
     solana_transaction_status::{Reward, TransactionStatusMeta},
     std::{
-        collections::{BTreeMap, HashMap},
+        collections::{BTreeMap, HashMap, HashSet, VecDeque},
+        fs::Permissions,
+        io,
+        net::SocketAddr,
+        os::unix::fs::{FileTypeExt, PermissionsExt},
+        path::PathBuf,
         sync::{
-            atomic::{AtomicUsize, Ordering},
-            Arc,
+            atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+            Arc, Mutex as StdMutex,
         },
+        time::SystemTime,
     },
     tokio::{
         fs,
+        net::UnixListener,
         runtime::Builder,
-        sync::{broadcast, mpsc, Mutex, Notify, RwLock, Semaphore},
+        sync::{broadcast, mpsc, oneshot, Mutex, Notify, RwLock, Semaphore},
         task::spawn_blocking,
-        time::{sleep, Duration, Instant},
+        time::{sleep, sleep_until, Duration, Instant},
     },
-    tokio_stream::wrappers::ReceiverStream,
+    tokio_stream::wrappers::{ReceiverStream, UnixListenerStream},
     tonic::{
+        metadata::MetadataValue,
         service::interceptor::interceptor,
         transport::{
             server::{Server, TcpIncoming},
             Identity, ServerTlsConfig,
         },
-        Request, Response, Result as TonicResult, Status, Streaming,
+        Code, Request, Response, Result as TonicResult, Status, Streaming,
     },
     tonic_health::server::health_reporter,
     yellowstone_grpc_proto::{
@@ -47,18 +71,40 @@ use {
         prelude::{
             geyser_server::{Geyser, GeyserServer},
             subscribe_update::UpdateOneof,
-            CommitmentLevel, GetBlockHeightRequest, GetBlockHeightResponse,
+            AccountLifecycle, CommitmentLevel, DropSubscriptionRequest, DropSubscriptionResponse,
+            GetBlockHeightRequest, GetBlockHeightResponse, GetBlockRequest, GetBlockResponse,
             GetLatestBlockhashRequest, GetLatestBlockhashResponse, GetSlotRequest, GetSlotResponse,
             GetVersionRequest, GetVersionResponse, IsBlockhashValidRequest,
-            IsBlockhashValidResponse, PingRequest, PongResponse, SubscribeRequest, SubscribeUpdate,
-            SubscribeUpdateAccount, SubscribeUpdateAccountInfo, SubscribeUpdateBlock,
+            IsBlockhashValidResponse, ListSubscriptionsRequest, ListSubscriptionsResponse,
+            PingRequest, PongResponse, SlotStatus as SlotStatusProto, SubscribeRejectionReason,
+            SubscribeRequest, SubscribeUpdate, SubscribeUpdateAccount, SubscribeUpdateAccountChunk,
+            SubscribeUpdateAccountInfo, SubscribeUpdateBlock, SubscribeUpdateBlockChunk,
             SubscribeUpdateBlockMeta, SubscribeUpdateEntry, SubscribeUpdatePing,
-            SubscribeUpdateSlot, SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo,
-            SubscribeUpdateTransactionStatus, TransactionError as SubscribeUpdateTransactionError,
+            SubscribeUpdateSlot, SubscribeUpdateSubscription, SubscribeUpdateTransaction,
+            SubscribeUpdateTransactionInfo, SubscribeUpdateTransactionStatus, SubscriptionInfo,
+            TransactionError as SubscribeUpdateTransactionError,
         },
+        prost::{encoding::encoded_len_varint, Message as _},
     },
 };
 
+/// Time when the message was received by the plugin: wall clock for `created_at_us` of updates
+/// and monotonic clock for latency metrics
+#[derive(Debug, Clone, Copy)]
+pub struct MessageCreatedAt {
+    pub system: SystemTime,
+    pub instant: Instant,
+}
+
+impl MessageCreatedAt {
+    pub fn now() -> Self {
+        Self {
+            system: SystemTime::now(),
+            instant: Instant::now(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct MessageAccountInfo {
     pub pubkey: Pubkey,
@@ -75,8 +121,11 @@ impl MessageAccountInfo {
     fn to_proto(
         &self,
         accounts_data_slice: &[FilterAccountsDataSlice],
+        include_data: bool,
     ) -> SubscribeUpdateAccountInfo {
-        let data = if accounts_data_slice.is_empty() {
+        let data = if !include_data {
+            vec![]
+        } else if accounts_data_slice.is_empty() {
             self.data.clone()
         } else {
             let mut data = Vec::with_capacity(accounts_data_slice.iter().map(|ds| ds.length).sum());
@@ -105,6 +154,10 @@ pub struct MessageAccount {
     pub account: MessageAccountInfo,
     pub slot: u64,
     pub is_startup: bool,
+    /// Set by the geyser loop for every commitment stream if enabled, updates from the
+    /// snapshot are always `Modified`
+    pub lifecycle: AccountLifecycle,
+    pub created_at: MessageCreatedAt,
 }
 
 impl<'a> From<(&'a ReplicaAccountInfoV3<'a>, u64, bool)> for MessageAccount {
@@ -122,6 +175,8 @@ impl<'a> From<(&'a ReplicaAccountInfoV3<'a>, u64, bool)> for MessageAccount {
             },
             slot,
             is_startup,
+            lifecycle: AccountLifecycle::Modified,
+            created_at: MessageCreatedAt::now(),
         }
     }
 }
@@ -131,6 +186,9 @@ pub struct MessageSlot {
     pub slot: u64,
     pub parent: Option<u64>,
     pub status: CommitmentLevel,
+    /// Slot was processed but it's not an ancestor of the finalized slot
+    pub dead: bool,
+    pub created_at: MessageCreatedAt,
 }
 
 impl From<(u64, Option<u64>, SlotStatus)> for MessageSlot {
@@ -143,6 +201,8 @@ impl From<(u64, Option<u64>, SlotStatus)> for MessageSlot {
                 SlotStatus::Confirmed => CommitmentLevel::Confirmed,
                 SlotStatus::Rooted => CommitmentLevel::Finalized,
             },
+            dead: false,
+            created_at: MessageCreatedAt::now(),
         }
     }
 }
@@ -156,13 +216,69 @@ pub struct MessageTransactionInfo {
     pub index: usize,
 }
 
+/// Parts of the transaction update sent to the client, omitted parts are cleared
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageTransactionFields {
+    pub message: bool,
+    pub meta: bool,
+    pub logs: bool,
+    pub inner_instructions: bool,
+    pub account_keys: bool,
+    pub balances: bool,
+    pub return_data: bool,
+}
+
+impl MessageTransactionFields {
+    pub const FULL: Self = Self {
+        message: true,
+        meta: true,
+        logs: true,
+        inner_instructions: true,
+        account_keys: true,
+        balances: true,
+        return_data: true,
+    };
+}
+
 impl MessageTransactionInfo {
-    fn to_proto(&self) -> SubscribeUpdateTransactionInfo {
+    fn to_proto(&self, fields: MessageTransactionFields) -> SubscribeUpdateTransactionInfo {
+        let transaction = fields.message.then(|| {
+            let mut transaction = convert_to::create_transaction(&self.transaction);
+            if !fields.account_keys {
+                if let Some(message) = transaction.message.as_mut() {
+                    message.account_keys = Vec::new();
+                }
+            }
+            transaction
+        });
+        let meta = fields.meta.then(|| {
+            let mut meta = convert_to::create_transaction_meta(&self.meta);
+            if !fields.logs {
+                meta.log_messages = Vec::new();
+                meta.log_messages_none = true;
+            }
+            if !fields.inner_instructions {
+                meta.inner_instructions = Vec::new();
+                meta.inner_instructions_none = true;
+            }
+            if !fields.balances {
+                meta.pre_balances = Vec::new();
+                meta.post_balances = Vec::new();
+                meta.pre_token_balances = Vec::new();
+                meta.post_token_balances = Vec::new();
+            }
+            if !fields.return_data {
+                meta.return_data = None;
+                meta.return_data_none = true;
+            }
+            meta
+        });
+
         SubscribeUpdateTransactionInfo {
             signature: self.signature.as_ref().into(),
             is_vote: self.is_vote,
-            transaction: Some(convert_to::create_transaction(&self.transaction)),
-            meta: Some(convert_to::create_transaction_meta(&self.meta)),
+            transaction,
+            meta,
             index: self.index as u64,
         }
     }
@@ -172,6 +288,7 @@ impl MessageTransactionInfo {
 pub struct MessageTransaction {
     pub transaction: MessageTransactionInfo,
     pub slot: u64,
+    pub created_at: MessageCreatedAt,
 }
 
 impl<'a> From<(&'a ReplicaTransactionInfoV2<'a>, u64)> for MessageTransaction {
@@ -185,6 +302,7 @@ impl<'a> From<(&'a ReplicaTransactionInfoV2<'a>, u64)> for MessageTransaction {
                 index: transaction.index,
             },
             slot,
+            created_at: MessageCreatedAt::now(),
         }
     }
 }
@@ -197,6 +315,7 @@ pub struct MessageEntry {
     pub hash: Vec<u8>,
     pub executed_transaction_count: u64,
     pub starting_transaction_index: u64,
+    pub created_at: MessageCreatedAt,
 }
 
 impl From<&ReplicaEntryInfoV2<'_>> for MessageEntry {
@@ -211,6 +330,7 @@ impl From<&ReplicaEntryInfoV2<'_>> for MessageEntry {
                 .starting_transaction_index
                 .try_into()
                 .expect("failed convert usize to u64"),
+            created_at: MessageCreatedAt::now(),
         }
     }
 }
@@ -243,6 +363,7 @@ pub struct MessageBlock {
     pub accounts: Vec<MessageAccountInfo>,
     pub entries_count: u64,
     pub entries: Vec<MessageEntry>,
+    pub created_at: MessageCreatedAt,
 }
 
 impl
@@ -275,6 +396,7 @@ impl
             accounts,
             entries_count: entries.len() as u64,
             entries,
+            created_at: MessageCreatedAt::now(),
         }
     }
 }
@@ -290,6 +412,7 @@ pub struct MessageBlockMeta {
     pub block_height: Option<u64>,
     pub executed_transaction_count: u64,
     pub entries_count: u64,
+    pub created_at: MessageCreatedAt,
 }
 
 impl<'a> From<&'a ReplicaBlockInfoV3<'a>> for MessageBlockMeta {
@@ -304,6 +427,7 @@ impl<'a> From<&'a ReplicaBlockInfoV3<'a>> for MessageBlockMeta {
             block_height: blockinfo.block_height,
             executed_transaction_count: blockinfo.executed_transaction_count,
             entries_count: blockinfo.entry_count,
+            created_at: MessageCreatedAt::now(),
         }
     }
 }
@@ -331,6 +455,18 @@ impl Message {
         }
     }
 
+    /// Time when the message was received by the plugin, blocks are created once all parts are received
+    pub const fn get_created_at(&self) -> MessageCreatedAt {
+        match self {
+            Self::Slot(msg) => msg.created_at,
+            Self::Account(msg) => msg.created_at,
+            Self::Transaction(msg) => msg.created_at,
+            Self::Entry(msg) => msg.created_at,
+            Self::Block(msg) => msg.created_at,
+            Self::BlockMeta(msg) => msg.created_at,
+        }
+    }
+
     pub const fn kind(&self) -> &'static str {
         match self {
             Self::Slot(_) => "Slot",
@@ -349,7 +485,7 @@ pub struct MessageBlockRef<'a> {
     pub slot: u64,
     pub parent_blockhash: &'a String,
     pub blockhash: &'a String,
-    pub rewards: &'a Vec<Reward>,
+    pub rewards: Option<&'a Vec<Reward>>,
     pub block_time: Option<UnixTimestamp>,
     pub block_height: Option<u64>,
     pub executed_transaction_count: u64,
@@ -381,7 +517,7 @@ impl<'a>
             slot: block.slot,
             parent_blockhash: &block.parent_blockhash,
             blockhash: &block.blockhash,
-            rewards: &block.rewards,
+            rewards: Some(&block.rewards),
             block_time: block.block_time,
             block_height: block.block_height,
             executed_transaction_count: block.executed_transaction_count,
@@ -394,12 +530,44 @@ impl<'a>
     }
 }
 
+impl<'a> MessageBlockRef<'a> {
+    fn to_proto(&self, accounts_data_slice: &[FilterAccountsDataSlice]) -> SubscribeUpdateBlock {
+        SubscribeUpdateBlock {
+            slot: self.slot,
+            blockhash: self.blockhash.clone(),
+            rewards: self
+                .rewards
+                .map(|rewards| convert_to::create_rewards_obj(rewards.as_slice(), None)),
+            block_time: self.block_time.map(convert_to::create_timestamp),
+            block_height: self.block_height.map(convert_to::create_block_height),
+            parent_slot: self.parent_slot,
+            parent_blockhash: self.parent_blockhash.clone(),
+            executed_transaction_count: self.executed_transaction_count,
+            transactions: self
+                .transactions
+                .iter()
+                .map(|tx| tx.to_proto(MessageTransactionFields::FULL))
+                .collect(),
+            updated_account_count: self.updated_account_count,
+            accounts: self
+                .accounts
+                .iter()
+                .map(|acc| acc.to_proto(accounts_data_slice, true))
+                .collect(),
+            entries_count: self.entries_count,
+            entries: self.entries.iter().map(|entry| entry.to_proto()).collect(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 #[allow(clippy::large_enum_variant)]
 pub enum MessageRef<'a> {
     Slot(&'a MessageSlot),
     Account(&'a MessageAccount),
+    AccountWithoutData(&'a MessageAccount),
     Transaction(&'a MessageTransaction),
+    TransactionProjection(&'a MessageTransaction, MessageTransactionFields),
     TransactionStatus(&'a MessageTransaction),
     Entry(&'a MessageEntry),
     Block(MessageBlockRef<'a>),
@@ -413,16 +581,35 @@ impl<'a> MessageRef<'a> {
                 slot: message.slot,
                 parent: message.parent,
                 status: message.status as i32,
+                slot_status: match (message.dead, message.status) {
+                    (true, _) => SlotStatusProto::SlotDead,
+                    (false, CommitmentLevel::Processed) => SlotStatusProto::SlotProcessed,
+                    (false, CommitmentLevel::Confirmed) => SlotStatusProto::SlotConfirmed,
+                    (false, CommitmentLevel::Finalized) => SlotStatusProto::SlotFinalized,
+                } as i32,
             }),
             Self::Account(message) => UpdateOneof::Account(SubscribeUpdateAccount {
-                account: Some(message.account.to_proto(accounts_data_slice)),
+                account: Some(message.account.to_proto(accounts_data_slice, true)),
+                slot: message.slot,
+                is_startup: message.is_startup,
+                lifecycle: message.lifecycle as i32,
+            }),
+            Self::AccountWithoutData(message) => UpdateOneof::Account(SubscribeUpdateAccount {
+                account: Some(message.account.to_proto(accounts_data_slice, false)),
                 slot: message.slot,
                 is_startup: message.is_startup,
+                lifecycle: message.lifecycle as i32,
             }),
             Self::Transaction(message) => UpdateOneof::Transaction(SubscribeUpdateTransaction {
-                transaction: Some(message.transaction.to_proto()),
+                transaction: Some(message.transaction.to_proto(MessageTransactionFields::FULL)),
                 slot: message.slot,
             }),
+            Self::TransactionProjection(message, fields) => {
+                UpdateOneof::Transaction(SubscribeUpdateTransaction {
+                    transaction: Some(message.transaction.to_proto(*fields)),
+                    slot: message.slot,
+                })
+            }
             Self::TransactionStatus(message) => {
                 UpdateOneof::TransactionStatus(SubscribeUpdateTransactionStatus {
                     slot: message.slot,
@@ -439,36 +626,7 @@ impl<'a> MessageRef<'a> {
                 })
             }
             Self::Entry(message) => UpdateOneof::Entry(message.to_proto()),
-            Self::Block(message) => UpdateOneof::Block(SubscribeUpdateBlock {
-                slot: message.slot,
-                blockhash: message.blockhash.clone(),
-                rewards: Some(convert_to::create_rewards_obj(
-                    message.rewards.as_slice(),
-                    None,
-                )),
-                block_time: message.block_time.map(convert_to::create_timestamp),
-                block_height: message.block_height.map(convert_to::create_block_height),
-                parent_slot: message.parent_slot,
-                parent_blockhash: message.parent_blockhash.clone(),
-                executed_transaction_count: message.executed_transaction_count,
-                transactions: message
-                    .transactions
-                    .iter()
-                    .map(|tx| tx.to_proto())
-                    .collect(),
-                updated_account_count: message.updated_account_count,
-                accounts: message
-                    .accounts
-                    .iter()
-                    .map(|acc| acc.to_proto(accounts_data_slice))
-                    .collect(),
-                entries_count: message.entries_count,
-                entries: message
-                    .entries
-                    .iter()
-                    .map(|entry| entry.to_proto())
-                    .collect(),
-            }),
+            Self::Block(message) => UpdateOneof::Block(message.to_proto(accounts_data_slice)),
             Self::BlockMeta(message) => UpdateOneof::BlockMeta(SubscribeUpdateBlockMeta {
                 slot: message.slot,
                 blockhash: message.blockhash.clone(),
@@ -509,6 +667,8 @@ impl BlockhashStatus {
 #[derive(Debug, Default)]
 struct BlockMetaStorageInner {
     blocks: HashMap<u64, MessageBlockMeta>,
+    blocks_full: HashMap<u64, Arc<Message>>,
+    slots_status: HashMap<u64, CommitmentLevel>,
     blockhashes: HashMap<String, BlockhashStatus>,
     processed: Option<u64>,
     confirmed: Option<u64>,
@@ -518,13 +678,17 @@ struct BlockMetaStorageInner {
 #[derive(Debug)]
 struct BlockMetaStorage {
     read_sem: Semaphore,
+    get_block_keep_slots: Option<usize>,
     inner: Arc<RwLock<BlockMetaStorageInner>>,
 }
 
 impl BlockMetaStorage {
-    fn new(unary_concurrency_limit: usize) -> (Self, mpsc::UnboundedSender<Message>) {
+    fn new(
+        unary_concurrency_limit: usize,
+        get_block_keep_slots: Option<usize>,
+    ) -> (Self, mpsc::UnboundedSender<Arc<Message>>) {
         let inner = Arc::new(RwLock::new(BlockMetaStorageInner::default()));
-        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (tx, mut rx) = mpsc::unbounded_channel::<Arc<Message>>();
 
         let storage = Arc::clone(&inner);
         tokio::spawn(async move {
@@ -532,8 +696,14 @@ impl BlockMetaStorage {
 
             while let Some(message) = rx.recv().await {
                 let mut storage = storage.write().await;
-                match message {
+                match message.as_ref() {
                     Message::Slot(msg) => {
+                        storage
+                            .slots_status
+                            .entry(msg.slot)
+                            .and_modify(|status| *status = (*status).max(msg.status))
+                            .or_insert(msg.status);
+
                         match msg.status {
                             CommitmentLevel::Processed => &mut storage.processed,
                             CommitmentLevel::Confirmed => &mut storage.confirmed,
@@ -571,10 +741,28 @@ impl BlockMetaStorage {
                                     .blockhashes
                                     .retain(|_blockhash, status| status.slot >= keep_slot);
                             }
+
+                            if let Some(keep_slot) = msg
+                                .slot
+                                .checked_sub(get_block_keep_slots.unwrap_or_default() as u64)
+                            {
+                                storage
+                                    .blocks_full
+                                    .retain(|slot, _block| *slot >= keep_slot);
+                                storage
+                                    .slots_status
+                                    .retain(|slot, _status| *slot >= keep_slot);
+                            }
                         }
                     }
                     Message::BlockMeta(msg) => {
-                        storage.blocks.insert(msg.slot, msg);
+                        storage.blocks.insert(msg.slot, msg.clone());
+                    }
+                    // full blocks are big, kept only if `GetBlock` is enabled
+                    Message::Block(msg) => {
+                        if get_block_keep_slots.is_some() {
+                            storage.blocks_full.insert(msg.slot, Arc::clone(&message));
+                        }
                     }
                     msg => {
                         error!("invalid message in BlockMetaStorage: {msg:?}");
@@ -586,6 +774,7 @@ impl BlockMetaStorage {
         (
             Self {
                 read_sem: Semaphore::new(unary_concurrency_limit),
+                get_block_keep_slots,
                 inner,
             },
             tx,
@@ -659,6 +848,75 @@ impl BlockMetaStorage {
 
         Ok(Response::new(IsBlockhashValidResponse { valid, slot }))
     }
+
+    async fn get_full_block(
+        &self,
+        request: &GetBlockRequest,
+        limit: &ConfigGrpcFiltersBlocks,
+    ) -> Result<Response<GetBlockResponse>, Status> {
+        if self.get_block_keep_slots.is_none() {
+            return Err(Status::unimplemented(
+                "`get_block_keep_slots` is not set in the config",
+            ));
+        }
+        let commitment = Self::parse_commitment(request.commitment)?;
+        if !(request.include_transactions == Some(false) || limit.include_transactions) {
+            return Err(Status::invalid_argument(
+                "`include_transactions` is not allowed",
+            ));
+        }
+        if !(matches!(request.include_accounts, None | Some(false)) || limit.include_accounts) {
+            return Err(Status::invalid_argument(
+                "`include_accounts` is not allowed",
+            ));
+        }
+        if !(matches!(request.include_entries, None | Some(false)) || limit.include_entries) {
+            return Err(Status::invalid_argument("`include_entries` is not allowed"));
+        }
+
+        let _permit = self.read_sem.acquire().await;
+        let storage = self.inner.read().await;
+
+        let block = match storage
+            .blocks_full
+            .get(&request.slot)
+            .map(|msg| msg.as_ref())
+        {
+            Some(Message::Block(block)) => block,
+            _ => return Err(Status::not_found("block is not available")),
+        };
+        let status = storage
+            .slots_status
+            .get(&request.slot)
+            .copied()
+            .unwrap_or(CommitmentLevel::Processed);
+        if status < commitment {
+            return Err(Status::unavailable(format!(
+                "block is not {commitment:?} yet"
+            )));
+        }
+
+        let transactions = if request.include_transactions.unwrap_or(true) {
+            block.transactions.iter().collect()
+        } else {
+            vec![]
+        };
+        let accounts = if request.include_accounts == Some(true) {
+            block.accounts.iter().collect()
+        } else {
+            vec![]
+        };
+        let entries = if request.include_entries == Some(true) {
+            block.entries.iter().collect()
+        } else {
+            vec![]
+        };
+        let block = MessageBlockRef::from((block, transactions, accounts, entries));
+
+        Ok(Response::new(GetBlockResponse {
+            block: Some(block.to_proto(&[])),
+        }))
+    }
 }
 
 #[derive(Debug, Default)]
@@ -672,6 +930,9 @@ struct SlotMessages {
     entries_count: usize,
     confirmed_at: Option<usize>,
     finalized_at: Option<usize>,
+    status: Option<CommitmentLevel>,
+    parent: Option<u64>,
+    dead: bool,
 }
 
 impl SlotMessages {
@@ -715,16 +976,325 @@ impl SlotMessages {
     }
 }
 
+/// Owns unix domain socket file and removes it on drop
+#[derive(Debug)]
+struct UnixSocketGuard {
+    path: PathBuf,
+}
+
+impl Drop for UnixSocketGuard {
+    fn drop(&mut self) {
+        if let Err(error) = std::fs::remove_file(&self.path) {
+            error!("failed to remove unix socket {:?}: {error}", self.path);
+        }
+    }
+}
+
+impl UnixSocketGuard {
+    fn bind(config: &ConfigGrpcUnixSocket) -> anyhow::Result<(Self, UnixListenerStream)> {
+        // Socket file can be left from unclean shutdown
+        match std::fs::symlink_metadata(&config.path) {
+            Ok(metadata) => {
+                anyhow::ensure!(
+                    metadata.file_type().is_socket(),
+                    "unix socket path {:?} exists and is not a socket",
+                    config.path
+                );
+                std::fs::remove_file(&config.path).context("failed to remove stale unix socket")?;
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+            Err(error) => return Err(error).context("failed to check unix socket path"),
+        }
+
+        let listener = UnixListener::bind(&config.path).context("failed to bind unix socket")?;
+        let guard = Self {
+            path: config.path.clone(),
+        };
+        if let Some(mode) = config.mode {
+            std::fs::set_permissions(&config.path, Permissions::from_mode(mode))
+                .context("failed to set unix socket permissions")?;
+        }
+
+        Ok((guard, UnixListenerStream::new(listener)))
+    }
+}
+
+#[derive(Debug)]
+pub struct LagShedding {
+    config: ConfigGrpcShedding,
+    received_slot: AtomicU64,
+    is_shedding: AtomicBool,
+}
+
+impl LagShedding {
+    fn new(config: ConfigGrpcShedding) -> anyhow::Result<Arc<Self>> {
+        anyhow::ensure!(
+            config.recover_lag_slots <= config.lag_slots,
+            "`recover_lag_slots` should not be greater than `lag_slots`"
+        );
+        anyhow::ensure!(
+            config.close_window_ms <= ConfigGrpcShedding::CLOSE_WINDOW_MS_MAX,
+            "shedding `close_window_ms` should not be greater than {}",
+            ConfigGrpcShedding::CLOSE_WINDOW_MS_MAX
+        );
+        Ok(Arc::new(Self {
+            config,
+            received_slot: AtomicU64::new(0),
+            is_shedding: AtomicBool::new(false),
+        }))
+    }
+
+    /// Processed slot received by the plugin from the validator
+    pub fn update_received_slot(&self, slot: u64) {
+        self.received_slot.fetch_max(slot, Ordering::Relaxed);
+    }
+
+    fn update_processed_slot(&self, slot: u64) {
+        let lag = self
+            .received_slot
+            .load(Ordering::Relaxed)
+            .saturating_sub(slot);
+        if lag > self.config.lag_slots {
+            if !self.is_shedding.swap(true, Ordering::Relaxed) {
+                info!("shedding low priority subscriptions, lag: {lag} slots");
+            }
+        } else if lag <= self.config.recover_lag_slots
+            && self.is_shedding.swap(false, Ordering::Relaxed)
+        {
+            info!("stop shedding low priority subscriptions, lag: {lag} slots");
+        }
+    }
+
+    fn is_shedding(&self) -> bool {
+        self.is_shedding.load(Ordering::Relaxed)
+    }
+
+    /// Subscription with this x-token is low priority and going to be closed
+    fn is_shed(&self, x_token: Option<&str>) -> bool {
+        self.is_shedding() && !self.is_high_priority(x_token)
+    }
+
+    // Low priority subscriptions are closed at random moments within the window, same as on
+    // shutdown, so clients don't reconnect at the same time
+    fn get_close_delay(&self) -> Duration {
+        Duration::from_millis(rand::thread_rng().gen_range(0..=self.config.close_window_ms))
+    }
+
+    fn is_high_priority(&self, x_token: Option<&str>) -> bool {
+        x_token.map_or(false, |x_token| {
+            self.config.high_priority_x_tokens.contains(x_token)
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct AccountsLifecycleState {
+    write_version: u64,
+    owner: Pubkey,
+    lamports: u64,
+}
+
+/// Tags account updates with lifecycle, compared with the last seen state of the same pubkey
+#[derive(Debug)]
+struct AccountsLifecycle {
+    states: GenerationCache<Pubkey, AccountsLifecycleState>,
+}
+
+impl AccountsLifecycle {
+    fn new(max: usize) -> Self {
+        Self {
+            states: GenerationCache::new(max),
+        }
+    }
+
+    fn update(&mut self, account: &MessageAccountInfo) -> AccountLifecycle {
+        let state = AccountsLifecycleState {
+            write_version: account.write_version,
+            owner: account.owner,
+            lamports: account.lamports,
+        };
+        // cold cache or evicted pubkey, reported conservatively
+        let Some(previous) = self.states.replace(account.pubkey, state) else {
+            return AccountLifecycle::Modified;
+        };
+        if previous.write_version > account.write_version {
+            self.states.replace(account.pubkey, previous);
+            return AccountLifecycle::Modified;
+        }
+
+        if account.lamports == 0 {
+            if previous.lamports > 0 {
+                AccountLifecycle::Closed
+            } else {
+                AccountLifecycle::Modified
+            }
+        } else if previous.lamports == 0 {
+            AccountLifecycle::Created
+        } else if previous.owner != system_program::ID
+            && account.owner == system_program::ID
+            && account.data.is_empty()
+        {
+            AccountLifecycle::Closed
+        } else {
+            AccountLifecycle::Modified
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Subscription {
+    remote_addr: Option<SocketAddr>,
+    endpoint: String,
+    x_token: Option<String>,
+    stream_tx: SubscribeUpdateSender,
+    client_tx: mpsc::UnboundedSender<Option<Filter>>,
+    // filters count by type and commitment of the last applied filter
+    filter: StdMutex<(HashMap<String, u64>, CommitmentLevel)>,
+    replay: Option<Arc<ReplayBuffer>>,
+    // last slot committed by the client
+    committed_slot: StdMutex<Option<u64>>,
+}
+
+impl Subscription {
+    fn commit(&self, slot: u64) {
+        let mut committed_slot = self.committed_slot.lock().expect("alive mutex");
+        if let Some(replay) = &self.replay {
+            replay.update_commit(*committed_slot, Some(slot));
+        }
+        *committed_slot = Some(slot);
+    }
+
+    // commit is released once the subscription is closed
+    fn release_commit(&self) {
+        let committed_slot = self.committed_slot.lock().expect("alive mutex").take();
+        if let Some(replay) = &self.replay {
+            replay.update_commit(committed_slot, None);
+        }
+    }
+
+    fn get_committed_slot(&self) -> Option<u64> {
+        *self.committed_slot.lock().expect("alive mutex")
+    }
+
+    fn update_filter(&self, filter: &Filter) {
+        let filters = filter
+            .get_metrics()
+            .into_iter()
+            .map(|(name, count)| (name.to_owned(), count as u64))
+            .collect();
+        *self.filter.lock().expect("alive mutex") = (filters, filter.get_commitment_level());
+    }
+}
+
+/// Subscription registered by `GrpcService::start_subscription`, updates are received from
+/// `stream_rx`
+#[derive(Debug)]
+pub struct SubscriptionStarted {
+    pub id: usize,
+    subscription: Arc<Subscription>,
+    pub stream_rx: mpsc::Receiver<TonicResult<SubscribeUpdate>>,
+}
+
+impl SubscriptionStarted {
+    /// Sends new filter to the client loop, returns `false` if the loop is finished
+    pub fn update_filter(&self, filter: Filter) -> bool {
+        self.subscription.update_filter(&filter);
+        self.subscription.client_tx.send(Some(filter)).is_ok()
+    }
+
+    /// Stops the client loop
+    pub fn close(&self) {
+        let _ = self.subscription.client_tx.send(None);
+    }
+
+    /// Returns updates and sender of filters, updates are finished with the client loop
+    pub fn into_stream(
+        self,
+    ) -> (
+        mpsc::Receiver<TonicResult<SubscribeUpdate>>,
+        mpsc::UnboundedSender<Option<Filter>>,
+    ) {
+        (self.stream_rx, self.subscription.client_tx.clone())
+    }
+}
+
+// Commitment level, sequence number of the first message and messages
+pub type BroadcastMessages = (CommitmentLevel, u64, Arc<Vec<Arc<Message>>>);
+
+type Subscriptions = Arc<StdMutex<HashMap<usize, Arc<Subscription>>>>;
+
+// Distributes items of the block between chunks with the same block header
+struct BlockChunks {
+    header: SubscribeUpdateBlock,
+    max_size: usize,
+    chunks: Vec<SubscribeUpdateBlock>,
+    size: usize,
+}
+
+impl BlockChunks {
+    fn new(header: SubscribeUpdateBlock, max_size: usize) -> Self {
+        Self {
+            chunks: vec![header.clone()],
+            header,
+            max_size,
+            size: 0,
+        }
+    }
+
+    // Returns `false` if the item does not fit into an empty chunk
+    fn push(&mut self, len: usize, add: impl FnOnce(&mut SubscribeUpdateBlock)) -> bool {
+        // field key and length prefix
+        let size = 1 + encoded_len_varint(len as u64) + len;
+        if size > self.max_size {
+            return false;
+        }
+        if self.size + size > self.max_size {
+            self.chunks.push(self.header.clone());
+            self.size = 0;
+        }
+        self.size += size;
+        add(self.chunks.last_mut().expect("at least one chunk"));
+        true
+    }
+
+    fn finish(self) -> Vec<SubscribeUpdateBlock> {
+        self.chunks
+    }
+}
+
+/// Stops gRPC service, waits until subscriptions are closed
+#[derive(Debug)]
+pub struct GrpcShutdown {
+    notify: Arc<Notify>,
+    closed_rx: oneshot::Receiver<()>,
+}
+
+impl GrpcShutdown {
+    pub async fn shutdown(self) {
+        self.notify.notify_one();
+        let _ = self.closed_rx.await;
+    }
+}
+
 #[derive(Debug)]
 pub struct GrpcService {
     config_snapshot_client_channel_capacity: usize,
     config_channel_capacity: usize,
+    config_max_encoding_message_size: Option<usize>,
     config_filters: Arc<ConfigGrpcFilters>,
+    config_admin_x_token: Option<String>,
     blocks_meta: Option<BlockMetaStorage>,
     subscribe_id: AtomicUsize,
+    subscriptions: Subscriptions,
     snapshot_rx: Mutex<Option<crossbeam_channel::Receiver<Option<Message>>>>,
-    broadcast_tx: broadcast::Sender<(CommitmentLevel, Arc<Vec<Arc<Message>>>)>,
+    broadcast_tx: broadcast::Sender<BroadcastMessages>,
+    replay: Option<Arc<ReplayBuffer>>,
+    checkpoint: Option<Arc<SlotCheckpoint>>,
     debug_clients_tx: Option<mpsc::UnboundedSender<DebugClientMessage>>,
+    quotas: Option<Arc<QuotaTracker>>,
+    admission: Option<AdmissionControl>,
+    shedding: Option<Arc<LagShedding>>,
+    slot_skew: Arc<SlotSkew>,
 }
 
 impl GrpcService {
@@ -737,15 +1307,34 @@ impl GrpcService {
     ) -> anyhow::Result<(
         Option<crossbeam_channel::Sender<Option<Message>>>,
         mpsc::UnboundedSender<Arc<Message>>,
-        Arc<Notify>,
+        Option<Arc<LagShedding>>,
+        Arc<SlotSkew>,
+        GrpcShutdown,
     )> {
+        anyhow::ensure!(
+            config.address.is_some() || config.unix_socket.is_some(),
+            "at least one of `address` or `unix_socket` should be defined"
+        );
+
         // Bind service address
-        let incoming = TcpIncoming::new(
-            config.address,
-            true,                          // tcp_nodelay
-            Some(Duration::from_secs(20)), // tcp_keepalive
-        )
-        .map_err(|error| anyhow::anyhow!(error))?;
+        let incoming = config
+            .address
+            .map(|address| {
+                TcpIncoming::new(
+                    address,
+                    true,                          // tcp_nodelay
+                    Some(Duration::from_secs(20)), // tcp_keepalive
+                )
+                .map_err(|error| anyhow::anyhow!(error))
+            })
+            .transpose()?;
+
+        // Bind unix domain socket
+        let incoming_unix = config
+            .unix_socket
+            .as_ref()
+            .map(UnixSocketGuard::bind)
+            .transpose()?;
 
         // Snapshot channel
         let (snapshot_tx, snapshot_rx) = match config.snapshot_plugin_channel_capacity {
@@ -761,13 +1350,70 @@ impl GrpcService {
             (None, None)
         } else {
             let (blocks_meta, blocks_meta_tx) =
-                BlockMetaStorage::new(config.unary_concurrency_limit);
+                BlockMetaStorage::new(config.unary_concurrency_limit, config.get_block_keep_slots);
             (Some(blocks_meta), Some(blocks_meta_tx))
         };
 
         // Messages to clients combined by commitment
         let (broadcast_tx, _) = broadcast::channel(config.channel_capacity);
 
+        // Last messages to resume subscriptions
+        let replay = match config.replay_buffer_size {
+            Some(size) => {
+                anyhow::ensure!(size > 0, "`replay_buffer_size` should be positive");
+                Some(Arc::new(ReplayBuffer::new(size)))
+            }
+            None => None,
+        };
+
+        // Last slots, restored from the previous run
+        let checkpoint = match config.checkpoint {
+            Some(config_checkpoint) => {
+                let checkpoint = SlotCheckpoint::load(config_checkpoint).await?;
+                tokio::spawn(Arc::clone(&checkpoint).save_loop());
+                Some(checkpoint)
+            }
+            None => None,
+        };
+
+        // Quotas usage, restored from the previous run
+        let quotas = match config.quotas {
+            Some(config_quotas) => {
+                let quotas = QuotaTracker::load(config_quotas).await?;
+                tokio::spawn(Arc::clone(&quotas).save_loop());
+                Some(quotas)
+            }
+            None => None,
+        };
+
+        // Bounded initialization of new subscriptions
+        let admission = config.admission.map(AdmissionControl::new).transpose()?;
+
+        // Lag based shedding of low priority subscriptions
+        let shedding = config.shedding.map(LagShedding::new).transpose()?;
+        let slot_skew = SlotSkew::new(config.slot_skew);
+
+        // Account updates are tagged with lifecycle only if enabled
+        if let Some(max) = config.accounts_lifecycle_cache_max {
+            anyhow::ensure!(max > 0, "`accounts_lifecycle_cache_max` should be positive");
+        }
+        let mut config_filters = config.filters;
+        config_filters.accounts.lifecycle = config.accounts_lifecycle_cache_max.is_some();
+
+        // Closing of subscriptions spread in time on shutdown
+        if let Some(reconnect) = &config.reconnect {
+            anyhow::ensure!(
+                reconnect.close_window_ms <= ConfigGrpcReconnect::CLOSE_WINDOW_MS_MAX,
+                "`close_window_ms` should not be greater than {}",
+                ConfigGrpcReconnect::CLOSE_WINDOW_MS_MAX
+            );
+            anyhow::ensure!(
+                reconnect.retry_after_min_ms <= reconnect.retry_after_max_ms,
+                "`retry_after_min_ms` should not be greater than `retry_after_max_ms`"
+            );
+        }
+        let subscriptions = Subscriptions::default();
+
         // gRPC server builder with optional TLS
         let mut server_builder = Server::builder();
         if let Some(tls_config) = &config.tls_config {
@@ -783,17 +1429,50 @@ impl GrpcService {
 
         // Create Server
         let max_decoding_message_size = config.max_decoding_message_size;
-        let mut service = GeyserServer::new(Self {
+        let grpc = Arc::new(Self {
             config_snapshot_client_channel_capacity: config.snapshot_client_channel_capacity,
             config_channel_capacity: config.channel_capacity,
-            config_filters: Arc::new(config.filters),
+            config_max_encoding_message_size: config.max_encoding_message_size,
+            config_admin_x_token: config.admin_x_token.clone(),
+            config_filters: Arc::new(config_filters),
             blocks_meta,
             subscribe_id: AtomicUsize::new(0),
+            subscriptions: Arc::clone(&subscriptions),
             snapshot_rx: Mutex::new(snapshot_rx),
             broadcast_tx: broadcast_tx.clone(),
+            replay: replay.clone(),
+            checkpoint: checkpoint.clone(),
             debug_clients_tx,
-        })
-        .max_decoding_message_size(max_decoding_message_size);
+            quotas,
+            admission,
+            shedding: shedding.clone(),
+            slot_skew: Arc::clone(&slot_skew),
+        });
+
+        // JSON gateway subscriptions are started by gRPC service, so limits, quotas and
+        // admission are the same
+        #[cfg(feature = "json-gateway")]
+        let json_gateway = config
+            .json_gateway
+            .map(|config_json_gateway| {
+                crate::json_gateway::JsonGateway::bind(
+                    config_json_gateway,
+                    config.x_token.clone(),
+                    Arc::clone(&grpc),
+                )
+            })
+            .transpose()?;
+        #[cfg(not(feature = "json-gateway"))]
+        anyhow::ensure!(
+            config.json_gateway.is_none(),
+            "`json_gateway` requires plugin built with `json-gateway` feature"
+        );
+
+        let mut service =
+            GeyserServer::from_arc(grpc).max_decoding_message_size(max_decoding_message_size);
+        if let Some(max_encoding_message_size) = config.max_encoding_message_size {
+            service = service.max_encoding_message_size(max_encoding_message_size);
+        }
         for encoding in config.compression.accept {
             service = service.accept_compressed(encoding);
         }
@@ -801,8 +1480,27 @@ impl GrpcService {
             service = service.send_compressed(encoding);
         }
 
+        // gRPC reflection service with descriptors of geyser and health services
+        let reflection_service = if config.reflection {
+            Some(
+                tonic_reflection::server::Builder::configure()
+                    .register_encoded_file_descriptor_set(
+                        yellowstone_grpc_proto::geyser::FILE_DESCRIPTOR_SET,
+                    )
+                    .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+                    .build()
+                    .context("failed to build reflection service")?,
+            )
+        } else {
+            None
+        };
+
         // Run geyser message loop
         let (messages_tx, messages_rx) = mpsc::unbounded_channel();
+        let geyser_checkpoint = checkpoint.clone();
+        let geyser_shedding = shedding.clone();
+        let geyser_slot_skew = Arc::clone(&slot_skew);
+        let accounts_lifecycle_cache_max = config.accounts_lifecycle_cache_max;
         spawn_blocking(move || {
             Builder::new_multi_thread()
                 .thread_name_fn(crate::get_thread_name)
@@ -814,68 +1512,207 @@ impl GrpcService {
                     messages_rx,
                     blocks_meta_tx,
                     broadcast_tx,
+                    replay,
+                    geyser_checkpoint,
                     block_fail_action,
+                    geyser_shedding,
+                    geyser_slot_skew,
+                    accounts_lifecycle_cache_max,
                 ));
         });
 
         // Run Server
         let shutdown = Arc::new(Notify::new());
         let shutdown_grpc = Arc::clone(&shutdown);
+        let (closed_tx, closed_rx) = oneshot::channel();
         tokio::spawn(async move {
             // gRPC Health check service
             let (mut health_reporter, health_service) = health_reporter();
             health_reporter.set_serving::<GeyserServer<Self>>().await;
 
-            server_builder
+            let server_builder = server_builder
                 .http2_keepalive_interval(Some(Duration::from_secs(5)))
                 .layer(interceptor(move |request: Request<()>| {
                     if let Some(x_token) = &config.x_token {
                         match request.metadata().get("x-token") {
                             Some(token) if x_token == token => Ok(request),
-                            _ => Err(Status::unauthenticated("No valid auth token")),
+                            _ => Err(SubscribeRejectionReason::Unauthenticated
+                                .into_status(Code::Unauthenticated, "No valid auth token")),
                         }
                     } else {
                         Ok(request)
                     }
-                }))
-                .add_service(health_service)
-                .add_service(service)
-                .serve_with_incoming_shutdown(incoming, shutdown_grpc.notified())
-                .await
+                }));
+
+            // TCP and unix socket servers share the same shutdown signal
+            let shutdown = shutdown_grpc.notified().shared();
+            let server_tcp = async {
+                match incoming {
+                    Some(incoming) => {
+                        server_builder
+                            .clone()
+                            .add_service(health_service.clone())
+                            .add_service(service.clone())
+                            .add_optional_service(reflection_service.clone())
+                            .serve_with_incoming_shutdown(incoming, shutdown.clone())
+                            .await
+                    }
+                    None => Ok(()),
+                }
+            };
+            let server_unix = async {
+                match incoming_unix {
+                    Some((_guard, incoming)) => {
+                        server_builder
+                            .clone()
+                            .add_service(health_service.clone())
+                            .add_service(service.clone())
+                            .add_optional_service(reflection_service.clone())
+                            .serve_with_incoming_shutdown(incoming, shutdown.clone())
+                            .await
+                    }
+                    None => Ok(()),
+                }
+            };
+            let server_json = async {
+                #[cfg(feature = "json-gateway")]
+                if let Some(json_gateway) = json_gateway {
+                    json_gateway.serve(shutdown.clone()).await;
+                }
+                Ok(())
+            };
+            let subscriptions_close = async {
+                shutdown.clone().await;
+                if let Some(reconnect) = config.reconnect {
+                    Self::close_subscriptions(subscriptions, reconnect).await;
+                }
+                if let Some(checkpoint) = checkpoint {
+                    if let Err(error) = checkpoint.save().await {
+                        error!("failed to save checkpoint: {error:?}");
+                    }
+                }
+                let _ = closed_tx.send(());
+                Ok(())
+            };
+            tokio::try_join!(server_tcp, server_unix, server_json, subscriptions_close)
         });
 
-        Ok((snapshot_tx, messages_tx, shutdown))
+        Ok((
+            snapshot_tx,
+            messages_tx,
+            shedding,
+            slot_skew,
+            GrpcShutdown {
+                notify: shutdown,
+                closed_rx,
+            },
+        ))
+    }
+
+    // Subscriptions are closed at random moments within the window with random `retry-after`
+    // hint, so clients don't reconnect at the same time
+    async fn close_subscriptions(subscriptions: Subscriptions, config: ConfigGrpcReconnect) {
+        let subscriptions = subscriptions
+            .lock()
+            .expect("alive mutex")
+            .iter()
+            .map(|(id, subscription)| (*id, Arc::clone(subscription)))
+            .collect::<Vec<_>>();
+        RECONNECT_STORM_SIZE.set(subscriptions.len() as i64);
+        info!(
+            "closing {} subscriptions within {}ms",
+            subscriptions.len(),
+            config.close_window_ms
+        );
+
+        let mut closes = {
+            let mut rng = rand::thread_rng();
+            subscriptions
+                .into_iter()
+                .map(|(id, subscription)| {
+                    let close_at = Duration::from_millis(rng.gen_range(0..=config.close_window_ms));
+                    let retry_after =
+                        rng.gen_range(config.retry_after_min_ms..=config.retry_after_max_ms);
+                    (close_at, retry_after, id, subscription)
+                })
+                .collect::<Vec<_>>()
+        };
+        closes.sort_by_key(|(close_at, _retry_after, id, _subscription)| (*close_at, *id));
+
+        let start = Instant::now();
+        for (close_at, retry_after, id, subscription) in closes {
+            sleep_until(start + close_at).await;
+            info!("client #{id}: closed on shutdown, retry after {retry_after}ms");
+            let mut status = SubscribeRejectionReason::ServerShutdown
+                .into_status(Code::Unavailable, "server is shutting down");
+            status
+                .metadata_mut()
+                .insert("retry-after", MetadataValue::from(retry_after));
+            // status is delivered after already queued messages
+            let stream_tx = subscription.stream_tx.clone();
+            tokio::spawn(async move {
+                let _ = stream_tx.send(Err(status)).await;
+            });
+            let _ = subscription.client_tx.send(None);
+        }
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn geyser_loop(
         mut messages_rx: mpsc::UnboundedReceiver<Arc<Message>>,
-        blocks_meta_tx: Option<mpsc::UnboundedSender<Message>>,
-        broadcast_tx: broadcast::Sender<(CommitmentLevel, Arc<Vec<Arc<Message>>>)>,
+        blocks_meta_tx: Option<mpsc::UnboundedSender<Arc<Message>>>,
+        broadcast_tx: broadcast::Sender<BroadcastMessages>,
+        replay: Option<Arc<ReplayBuffer>>,
+        checkpoint: Option<Arc<SlotCheckpoint>>,
         block_fail_action: ConfigBlockFailAction,
+        shedding: Option<Arc<LagShedding>>,
+        slot_skew: Arc<SlotSkew>,
+        accounts_lifecycle_cache_max: Option<usize>,
     ) {
         const PROCESSED_MESSAGES_MAX: usize = 31;
         const PROCESSED_MESSAGES_SLEEP: Duration = Duration::from_millis(10);
 
+        let mut seqs = [0; 3];
         let mut messages: BTreeMap<u64, SlotMessages> = Default::default();
         let mut processed_messages = Vec::with_capacity(PROCESSED_MESSAGES_MAX);
         let mut processed_first_slot = None;
+        // every commitment stream has own order of updates, dead forks are only in processed
+        let mut lifecycles =
+            accounts_lifecycle_cache_max.map(|max| [(); 3].map(|()| AccountsLifecycle::new(max)));
         let processed_sleep = sleep(PROCESSED_MESSAGES_SLEEP);
         tokio::pin!(processed_sleep);
 
         loop {
             tokio::select! {
-                Some(message) = messages_rx.recv() => {
+                Some(mut message) = messages_rx.recv() => {
                     MESSAGE_QUEUE_SIZE.dec();
 
+                    // Processed lifecycle is tagged before message is saved or sent, plugin is the only owner here
+                    if let Some(lifecycles) = lifecycles.as_mut() {
+                        Self::update_lifecycle(&mut lifecycles[CommitmentLevel::Processed as usize], std::slice::from_mut(&mut message));
+                    }
+
                     // Update metrics
                     if let Message::Slot(slot_message) = message.as_ref() {
                         prom::update_slot_plugin_status(slot_message.status, slot_message.slot);
+                        if let Some(checkpoint) = &checkpoint {
+                            checkpoint.update(slot_message);
+                        }
+                        if slot_message.status == CommitmentLevel::Processed {
+                            if let Some(shedding) = &shedding {
+                                shedding.update_processed_slot(slot_message.slot);
+                            }
+                            // without subscribers skew shows how far the plugin is behind
+                            if broadcast_tx.receiver_count() == 0 {
+                                slot_skew.update_sent_slot(slot_message.slot);
+                            }
+                        }
                     }
 
                     // Update blocks info
                     if let Some(blocks_meta_tx) = &blocks_meta_tx {
                         if matches!(message.as_ref(), Message::Slot(_) | Message::BlockMeta(_)) {
-                            let _ = blocks_meta_tx.send(message.as_ref().clone());
+                            let _ = blocks_meta_tx.send(Arc::clone(&message));
                         }
                     }
 
@@ -887,6 +1724,29 @@ impl GrpcService {
                             processed_first_slot = Some(msg.slot);
                         }
                         Message::Slot(msg) if msg.status == CommitmentLevel::Finalized => {
+                            // Every slot between the finalized slot and the lowest known ancestor which is
+                            // not an ancestor itself is on the dead fork, slots below are left as is
+                            let mut ancestors = HashSet::new();
+                            let mut lowest_ancestor = msg.slot;
+                            let mut parent = messages.get(&msg.slot).and_then(|slot_messages| slot_messages.parent).or(msg.parent);
+                            while let Some(slot) = parent {
+                                ancestors.insert(slot);
+                                lowest_ancestor = slot;
+                                parent = messages.get(&slot).and_then(|slot_messages| slot_messages.parent);
+                            }
+                            for (slot, slot_messages) in messages.range_mut(lowest_ancestor..msg.slot) {
+                                if slot_messages.status == Some(CommitmentLevel::Processed) && !slot_messages.dead && !ancestors.contains(slot) {
+                                    slot_messages.dead = true;
+                                    processed_messages.push(Arc::new(Message::Slot(MessageSlot {
+                                        slot: *slot,
+                                        parent: None,
+                                        status: CommitmentLevel::Processed,
+                                        dead: true,
+                                        created_at: MessageCreatedAt::now(),
+                                    })));
+                                }
+                            }
+
                             // keep extra 10 slots
                             if let Some(msg_slot) = msg.slot.checked_sub(10) {
                                 loop {
@@ -941,7 +1801,12 @@ impl GrpcService {
 
                     // Update block reconstruction info
                     let slot_messages = messages.entry(message.get_slot()).or_default();
-                    if !matches!(message.as_ref(), Message::Slot(_)) {
+                    if let Message::Slot(msg) = message.as_ref() {
+                        slot_messages.status = slot_messages.status.max(Some(msg.status));
+                        if msg.parent.is_some() {
+                            slot_messages.parent = msg.parent;
+                        }
+                    } else {
                         slot_messages.messages.push(Some(Arc::clone(&message)));
 
                         // If we already build Block message, new message will be a problem
@@ -958,6 +1823,7 @@ impl GrpcService {
                         }
                     }
                     let mut sealed_block_msg = None;
+                    let mut is_outdated = false;
                     match message.as_ref() {
                         Message::BlockMeta(msg) => {
                             if slot_messages.block_meta.is_some() {
@@ -987,6 +1853,10 @@ impl GrpcService {
                                     // We can replace the message, but in this case we will lose the order
                                     slot_messages.messages[entry.1] = None;
                                     *entry = (write_version, msg_index);
+                                } else {
+                                    // Newer write already sent, write_version should never go back for clients
+                                    slot_messages.messages[msg_index] = None;
+                                    is_outdated = true;
                                 }
                             } else {
                                 slot_messages.accounts_dedup.insert(msg.account.pubkey, (write_version, msg_index));
@@ -999,8 +1869,16 @@ impl GrpcService {
                         _ => {}
                     }
 
+                    // Keep full blocks for unary `GetBlock`
+                    if let (Some(blocks_meta_tx), Some(sealed_block_msg)) = (&blocks_meta_tx, &sealed_block_msg) {
+                        let _ = blocks_meta_tx.send(Arc::clone(sealed_block_msg));
+                    }
+
                     // Send messages to filter (and to clients)
-                    let mut messages_vec = vec![message];
+                    let mut messages_vec = Vec::with_capacity(2);
+                    if !is_outdated {
+                        messages_vec.push(message);
+                    }
                     if let Some(sealed_block_msg) = sealed_block_msg {
                         messages_vec.push(sealed_block_msg);
                     }
@@ -1041,8 +1919,7 @@ impl GrpcService {
 
                             // processed
                             processed_messages.push(Arc::clone(&message));
-                            let _ =
-                                broadcast_tx.send((CommitmentLevel::Processed, processed_messages.into()));
+                            Self::broadcast(&broadcast_tx, &replay, &mut seqs, &mut lifecycles, CommitmentLevel::Processed, processed_messages);
                             processed_messages = Vec::with_capacity(PROCESSED_MESSAGES_MAX);
                             processed_sleep
                                 .as_mut()
@@ -1050,13 +1927,11 @@ impl GrpcService {
 
                             // confirmed
                             confirmed_messages.push(Arc::clone(&message));
-                            let _ =
-                                broadcast_tx.send((CommitmentLevel::Confirmed, confirmed_messages.into()));
+                            Self::broadcast(&broadcast_tx, &replay, &mut seqs, &mut lifecycles, CommitmentLevel::Confirmed, confirmed_messages);
 
                             // finalized
                             finalized_messages.push(message);
-                            let _ =
-                                broadcast_tx.send((CommitmentLevel::Finalized, finalized_messages.into()));
+                            Self::broadcast(&broadcast_tx, &replay, &mut seqs, &mut lifecycles, CommitmentLevel::Finalized, finalized_messages);
                         } else {
                             let mut confirmed_messages = vec![];
                             let mut finalized_messages = vec![];
@@ -1080,8 +1955,7 @@ impl GrpcService {
                                 || !confirmed_messages.is_empty()
                                 || !finalized_messages.is_empty()
                             {
-                                let _ = broadcast_tx
-                                    .send((CommitmentLevel::Processed, processed_messages.into()));
+                                Self::broadcast(&broadcast_tx, &replay, &mut seqs, &mut lifecycles, CommitmentLevel::Processed, processed_messages);
                                 processed_messages = Vec::with_capacity(PROCESSED_MESSAGES_MAX);
                                 processed_sleep
                                     .as_mut()
@@ -1089,20 +1963,18 @@ impl GrpcService {
                             }
 
                             if !confirmed_messages.is_empty() {
-                                let _ =
-                                    broadcast_tx.send((CommitmentLevel::Confirmed, confirmed_messages.into()));
+                                Self::broadcast(&broadcast_tx, &replay, &mut seqs, &mut lifecycles, CommitmentLevel::Confirmed, confirmed_messages);
                             }
 
                             if !finalized_messages.is_empty() {
-                                let _ =
-                                    broadcast_tx.send((CommitmentLevel::Finalized, finalized_messages.into()));
+                                Self::broadcast(&broadcast_tx, &replay, &mut seqs, &mut lifecycles, CommitmentLevel::Finalized, finalized_messages);
                             }
                         }
                     }
                 }
                 () = &mut processed_sleep => {
                     if !processed_messages.is_empty() {
-                        let _ = broadcast_tx.send((CommitmentLevel::Processed, processed_messages.into()));
+                        Self::broadcast(&broadcast_tx, &replay, &mut seqs, &mut lifecycles, CommitmentLevel::Processed, processed_messages);
                         processed_messages = Vec::with_capacity(PROCESSED_MESSAGES_MAX);
                     }
                     processed_sleep.as_mut().reset(Instant::now() + PROCESSED_MESSAGES_SLEEP);
@@ -1112,16 +1984,62 @@ impl GrpcService {
         }
     }
 
+    // Tags account updates in the order of the commitment stream, message is copied only if
+    // the tag is changed
+    fn update_lifecycle(lifecycle: &mut AccountsLifecycle, messages: &mut [Arc<Message>]) {
+        for message in messages.iter_mut() {
+            if let Message::Account(msg) = message.as_ref() {
+                let tag = lifecycle.update(&msg.account);
+                if tag != msg.lifecycle {
+                    if let Message::Account(msg) = Arc::make_mut(message) {
+                        msg.lifecycle = tag;
+                    }
+                }
+            }
+        }
+    }
+
+    // Messages are numbered for every commitment level, numbers are used in resume tokens
+    fn broadcast(
+        broadcast_tx: &broadcast::Sender<BroadcastMessages>,
+        replay: &Option<Arc<ReplayBuffer>>,
+        seqs: &mut [u64; 3],
+        lifecycles: &mut Option<[AccountsLifecycle; 3]>,
+        commitment: CommitmentLevel,
+        mut messages: Vec<Arc<Message>>,
+    ) {
+        // processed updates are tagged once received
+        if let Some(lifecycles) = lifecycles.as_mut() {
+            if commitment != CommitmentLevel::Processed {
+                Self::update_lifecycle(&mut lifecycles[commitment as usize], &mut messages);
+            }
+        }
+        let first_seq = seqs[commitment as usize];
+        seqs[commitment as usize] += messages.len() as u64;
+        // buffer is updated first, so resumed clients never miss messages
+        if let Some(replay) = replay {
+            replay.push(commitment, first_seq, &messages);
+        }
+        let _ = broadcast_tx.send((commitment, first_seq, messages.into()));
+    }
+
     #[allow(clippy::too_many_arguments)]
     async fn client_loop(
         id: usize,
         endpoint: String,
         config_filters: Arc<ConfigGrpcFilters>,
-        stream_tx: mpsc::Sender<TonicResult<SubscribeUpdate>>,
+        stream_tx: SubscribeUpdateSender,
         mut client_rx: mpsc::UnboundedReceiver<Option<Filter>>,
         mut snapshot_rx: Option<crossbeam_channel::Receiver<Option<Message>>>,
-        mut messages_rx: broadcast::Receiver<(CommitmentLevel, Arc<Vec<Arc<Message>>>)>,
+        mut messages_rx: broadcast::Receiver<BroadcastMessages>,
+        replay: Option<Arc<ReplayBuffer>>,
+        checkpoint: Option<Arc<SlotCheckpoint>>,
         debug_client_tx: Option<mpsc::UnboundedSender<DebugClientMessage>>,
+        quota: Option<(Arc<QuotaTracker>, String)>,
+        shedding: Option<Arc<LagShedding>>,
+        slot_skew: Arc<SlotSkew>,
+        max_message_size: Option<usize>,
+        mut admission_permit: Option<AdmissionPermit>,
         drop_client: impl FnOnce(),
     ) {
         let mut filter = Filter::new(
@@ -1136,6 +2054,14 @@ impl GrpcService {
                 commitment: None,
                 accounts_data_slice: Vec::new(),
                 ping: None,
+                commit: None,
+                accounts_coalesce_window_ms: None,
+                accounts_chunking: None,
+                accounts_sample_rate: None,
+                filter_ack: None,
+                resume_token: None,
+                blocks_chunking: None,
+                include_created_at: None,
             },
             &config_filters,
         )
@@ -1150,12 +2076,34 @@ impl GrpcService {
         info!("client #{id}: new");
 
         let mut is_alive = true;
+        let mut coalesce = AccountsCoalesce::default();
+        // moment when low priority subscription is closed by shedding
+        let mut shed_at = None;
+        // sequence number of the last handled message, messages from the replay buffer are
+        // handled before live messages, so duplicates are skipped
+        let mut last_seq = None;
+        let mut replay_messages = None;
+        // slot updates from the previous run are sent only once
+        let mut restored_sent = false;
+        // resume token is applied only with the first filter of the stream, filter updates with
+        // the same token would replay messages again
+        let mut is_first_filter = true;
+        // processed slots with the number of items queued before them, for the slot skew
+        let mut skew_slots = VecDeque::new();
         if let Some(snapshot_rx) = snapshot_rx.take() {
             info!("client #{id}: going to receive snapshot data");
 
             // we start with default filter, for snapshot we need wait actual filter first
             while is_alive {
-                match client_rx.recv().await {
+                let message = tokio::select! {
+                    message = client_rx.recv() => message,
+                    () = Self::admission_expired(&admission_permit) => {
+                        info!("client #{id}: admission timed out");
+                        admission_permit = None;
+                        continue;
+                    }
+                };
+                match message {
                     Some(Some(filter_new)) => {
                         if let Some(msg) = filter_new.get_pong_msg() {
                             if stream_tx.send(Ok(msg)).await.is_err() {
@@ -1168,7 +2116,15 @@ impl GrpcService {
 
                         prom::update_subscriptions(&endpoint, Some(&filter), Some(&filter_new));
                         filter = filter_new;
+                        is_first_filter = false;
                         info!("client #{id}: filter updated");
+                        if let Some(msg) = filter.get_ack_msg(max_message_size.is_some()) {
+                            if stream_tx.send(Ok(msg)).await.is_err() {
+                                error!("client #{id}: stream closed");
+                                is_alive = false;
+                            }
+                        }
+                        break;
                     }
                     Some(None) => {
                         is_alive = false;
@@ -1199,19 +2155,55 @@ impl GrpcService {
                     }
                 };
 
-                for message in filter.get_update(&message, None) {
-                    if stream_tx.send(Ok(message)).await.is_err() {
-                        error!("client #{id}: stream closed");
-                        is_alive = false;
-                        break;
+                'snapshot: for message in filter.get_update(&message, None) {
+                    let messages =
+                        match Self::limit_message_size(id, message, max_message_size, &filter) {
+                            Ok(messages) => messages,
+                            Err(status) => {
+                                let _ = stream_tx.send(Err(status)).await;
+                                is_alive = false;
+                                break 'snapshot;
+                            }
+                        };
+                    if messages.is_empty() {
+                        stream_tx.skip(1);
+                    }
+                    for message in messages {
+                        let quota_exhausted = Self::quota_add(&quota, &message);
+                        if stream_tx.send(Ok(message)).await.is_err() {
+                            error!("client #{id}: stream closed");
+                            is_alive = false;
+                            break 'snapshot;
+                        }
+                        if quota_exhausted {
+                            info!("client #{id}: quota exhausted");
+                            let _ = stream_tx.send(Err(Self::quota_status())).await;
+                            is_alive = false;
+                            break 'snapshot;
+                        }
                     }
                 }
             }
+
+            // snapshot is sent, subscription is initialized
+            admission_permit = None;
         }
 
         if is_alive {
             'outer: loop {
                 tokio::select! {
+                    () = Self::admission_expired(&admission_permit) => {
+                        info!("client #{id}: admission timed out");
+                        admission_permit = None;
+                    }
+                    () = sleep_until(coalesce.deadline().unwrap_or_else(Instant::now)), if coalesce.deadline().is_some() => {
+                        for (message, created_at) in coalesce.flush() {
+                            let latency = Some(("Account", filter.get_commitment_level(), created_at));
+                            if !Self::try_send_update(id, &stream_tx, &quota, message, latency) {
+                                break 'outer;
+                            }
+                        }
+                    }
                     message = client_rx.recv() => {
                         match message {
                             Some(Some(filter_new)) => {
@@ -1224,9 +2216,57 @@ impl GrpcService {
                                 }
 
                                 prom::update_subscriptions(&endpoint, Some(&filter), Some(&filter_new));
+                                if filter.get_commitment_level() != filter_new.get_commitment_level() {
+                                    last_seq = None;
+                                }
                                 filter = filter_new;
                                 DebugClientMessage::maybe_send(&debug_client_tx, || DebugClientMessage::UpdateFilter { id, filter: Box::new(filter.clone()) });
                                 info!("client #{id}: filter updated");
+                                if let Some(msg) = filter.get_ack_msg(max_message_size.is_some()) {
+                                    if stream_tx.send(Ok(msg)).await.is_err() {
+                                        error!("client #{id}: stream closed");
+                                        break 'outer;
+                                    }
+                                }
+                                if let (Some(checkpoint), Some(from_slot), None, false) = (&checkpoint, filter.get_slots_from_slot(), filter.get_resume_token(), restored_sent) {
+                                    restored_sent = true;
+                                    let messages = checkpoint.get_restored(from_slot);
+                                    info!("client #{id}: {} slot updates from the previous run", messages.len());
+                                    for message in messages.iter() {
+                                        for message in filter.get_update(message, Some(filter.get_commitment_level())) {
+                                            if !Self::try_send_update(id, &stream_tx, &quota, message, None) {
+                                                break 'outer;
+                                            }
+                                        }
+                                    }
+                                }
+                                if let (Some(token), true) = (filter.get_resume_token(), is_first_filter) {
+                                    let result = match &replay {
+                                        Some(replay) => replay.get_after(token).map_err(|error| match error {
+                                            ReplayError::Expired => SubscribeRejectionReason::ResumeTokenExpired.into_status(Code::OutOfRange, error.to_string()),
+                                            ReplayError::Unknown => SubscribeRejectionReason::ResumeTokenUnknown.into_status(Code::InvalidArgument, error.to_string()),
+                                        }),
+                                        None => Err(SubscribeRejectionReason::ReplayDisabled.into_status(Code::FailedPrecondition, "replay buffer is disabled")),
+                                    };
+                                    match result {
+                                        Ok((first_seq, messages)) => {
+                                            info!("client #{id}: resumed from {token}, {} messages to replay", messages.len());
+                                            last_seq = Some(token.seq);
+                                            replay_messages = Some((token.commitment, first_seq, Arc::new(messages)));
+                                        }
+                                        Err(status) => {
+                                            info!("client #{id}: failed to resume: {}", status.message());
+                                            tokio::spawn(async move {
+                                                let _ = stream_tx.send(Err(status)).await;
+                                            });
+                                            break 'outer;
+                                        }
+                                    }
+                                }
+                                // filter is applied with restored slots and replay, subscription is
+                                // initialized
+                                admission_permit = None;
+                                is_first_filter = false;
                             }
                             Some(None) => {
                                 break 'outer;
@@ -1236,49 +2276,132 @@ impl GrpcService {
                             }
                         }
                     }
-                    message = messages_rx.recv() => {
-                        let (commitment, messages) = match message {
-                            Ok((commitment, messages)) => (commitment, messages),
+                    message = async {
+                        match replay_messages.take() {
+                            Some(messages) => Ok(messages),
+                            None => messages_rx.recv().await,
+                        }
+                    } => {
+                        let (commitment, first_seq, messages) = match message {
+                            Ok((commitment, first_seq, messages)) => (commitment, first_seq, messages),
                             Err(broadcast::error::RecvError::Closed) => {
                                 break 'outer;
                             },
                             Err(broadcast::error::RecvError::Lagged(_)) => {
                                 info!("client #{id}: lagged to receive geyser messages");
                                 tokio::spawn(async move {
-                                    let _ = stream_tx.send(Err(Status::internal("lagged"))).await;
+                                    let _ = stream_tx.send(Err(Self::lagged_status())).await;
                                 });
                                 break 'outer;
                             }
                         };
 
+                        // low priority subscription is closed instead of dropping updates, updates
+                        // are sent until the close moment, close is cancelled if lag is recovered
+                        match &shedding {
+                            Some(shedding) if shedding.is_shedding() => {
+                                shed_at.get_or_insert_with(|| Instant::now() + shedding.get_close_delay());
+                            }
+                            _ => shed_at = None,
+                        }
+                        if shed_at.map_or(false, |shed_at| shed_at <= Instant::now()) {
+                            info!("client #{id}: shed");
+                            SUBSCRIPTIONS_SHED_TOTAL.inc();
+                            tokio::spawn(async move {
+                                let _ = stream_tx.send(Err(Self::shed_status())).await;
+                            });
+                            break 'outer;
+                        }
+
                         if commitment == filter.get_commitment_level() {
-                            for message in messages.iter() {
-                                for message in filter.get_update(message, Some(commitment)) {
-                                    match stream_tx.try_send(Ok(message)) {
-                                        Ok(()) => {}
-                                        Err(mpsc::error::TrySendError::Full(_)) => {
-                                            error!("client #{id}: lagged to send update");
-                                            tokio::spawn(async move {
-                                                let _ = stream_tx.send(Err(Status::internal("lagged"))).await;
-                                            });
-                                            break 'outer;
+                            for (seq, message) in (first_seq..).zip(messages.iter()) {
+                                if last_seq.map_or(false, |last_seq| seq <= last_seq) {
+                                    continue;
+                                }
+                                last_seq = Some(seq);
+
+                                // token can be used only with the replay buffer
+                                let resume_token = replay.is_some().then(|| ResumeToken { commitment, slot: message.get_slot(), seq }.to_string());
+                                // latency is observed once the update (or its last chunk) is queued
+                                let created_at = message.get_created_at().instant;
+                                let latency = Some((message.kind(), commitment, created_at));
+                                for mut message in filter.get_update(message, Some(commitment)) {
+                                    message.resume_token.clone_from(&resume_token);
+                                    // oversized updates bypass coalescing, pending state of the account is outdated
+                                    if Self::is_oversized(&message, max_message_size) {
+                                        if let Some(UpdateOneof::Account(msg)) = &message.update_oneof {
+                                            if let Some(account) = &msg.account {
+                                                coalesce.remove(&account.pubkey, &message.filters);
+                                                stream_tx.skip(coalesce.take_dropped());
+                                            }
                                         }
-                                        Err(mpsc::error::TrySendError::Closed(_)) => {
-                                            error!("client #{id}: stream closed");
-                                            break 'outer;
+                                        let messages = match Self::limit_message_size(id, message, max_message_size, &filter) {
+                                            Ok(messages) => messages,
+                                            Err(status) => {
+                                                tokio::spawn(async move {
+                                                    let _ = stream_tx.send(Err(status)).await;
+                                                });
+                                                break 'outer;
+                                            }
+                                        };
+                                        if messages.is_empty() {
+                                            stream_tx.skip(1);
+                                        }
+                                        let count = messages.len();
+                                        for (index, message) in messages.into_iter().enumerate() {
+                                            if !Self::try_send_update(id, &stream_tx, &quota, message, latency.filter(|_| index + 1 == count)) {
+                                                break 'outer;
+                                            }
+                                        }
+                                        continue;
+                                    }
+                                    let message = match filter.get_accounts_coalesce() {
+                                        Some(window) => {
+                                            let message = coalesce.push(window, message, created_at);
+                                            stream_tx.skip(coalesce.take_dropped());
+                                            match message {
+                                                Some(message) => message,
+                                                None => continue,
+                                            }
+                                        }
+                                        None => message,
+                                    };
+                                    if let Some(UpdateOneof::Slot(msg)) = &message.update_oneof {
+                                        for (message, created_at) in coalesce.flush_slot(msg.slot) {
+                                            if !Self::try_send_update(id, &stream_tx, &quota, message, Some(("Account", commitment, created_at))) {
+                                                break 'outer;
+                                            }
                                         }
                                     }
+                                    if !Self::try_send_update(id, &stream_tx, &quota, message, latency) {
+                                        break 'outer;
+                                    }
+                                }
+
+                                // stream is finished with `OK` status, nothing except slots is
+                                // subscribed
+                                if filter.is_slots_range_ended(message) {
+                                    info!("client #{id}: slots range ended");
+                                    break 'outer;
                                 }
                             }
                         }
 
-                        if commitment == CommitmentLevel::Processed && debug_client_tx.is_some() {
+                        if commitment == CommitmentLevel::Processed {
                             for message in messages.iter() {
-                                if let Message::Slot(slot_message) = message.as_ref() {
+                                if let Message::Slot(slot_message @ MessageSlot { dead: false, .. }) = message.as_ref() {
+                                    skew_slots.push_back((stream_tx.queued(), slot_message.slot));
                                     DebugClientMessage::maybe_send(&debug_client_tx, || DebugClientMessage::UpdateSlot { id, slot: slot_message.slot });
                                 }
                             }
                         }
+                        // slot is delivered once everything queued before it is taken by the transport
+                        let delivered = stream_tx.delivered();
+                        while let Some((_queued, slot)) = skew_slots.front().filter(|(queued, _slot)| *queued <= delivered) {
+                            slot_skew.update_sent_slot(*slot);
+                            stream_tx.update_sent_slot(*slot);
+                            skew_slots.pop_front();
+                        }
                     }
                 }
             }
@@ -1290,6 +2413,518 @@ impl GrpcService {
         info!("client #{id}: removed");
         drop_client();
     }
+
+    // Resolves once the admission permit should be released without the first filter
+    async fn admission_expired(permit: &Option<AdmissionPermit>) {
+        match permit {
+            Some(permit) => sleep_until(permit.deadline()).await,
+            None => std::future::pending().await,
+        }
+    }
+
+    fn is_oversized_account(message: &SubscribeUpdate, max: usize) -> bool {
+        matches!(message.update_oneof, Some(UpdateOneof::Account(_))) && message.encoded_len() > max
+    }
+
+    fn is_oversized_block(message: &SubscribeUpdate, max: usize) -> bool {
+        matches!(message.update_oneof, Some(UpdateOneof::Block(_))) && message.encoded_len() > max
+    }
+
+    fn is_oversized(message: &SubscribeUpdate, max: Option<usize>) -> bool {
+        max.map_or(false, |max| {
+            Self::is_oversized_account(message, max) || Self::is_oversized_block(message, max)
+        })
+    }
+
+    // Only account and block updates are limited: other updates are far below any sane limit
+    fn limit_message_size(
+        id: usize,
+        message: SubscribeUpdate,
+        max: Option<usize>,
+        filter: &Filter,
+    ) -> Result<Vec<SubscribeUpdate>, Status> {
+        match max {
+            Some(max) if Self::is_oversized_account(&message, max) => Ok(
+                Self::split_account_update(id, message, max, filter.get_accounts_chunking()),
+            ),
+            Some(max) if Self::is_oversized_block(&message, max) => {
+                Self::split_block_update(id, message, max, filter.get_blocks_chunking())
+            }
+            _ => Ok(vec![message]),
+        }
+    }
+
+    // Oversized account update is split to chunks if client opted in, otherwise it's dropped
+    fn split_account_update(
+        id: usize,
+        message: SubscribeUpdate,
+        max: usize,
+        chunking: bool,
+    ) -> Vec<SubscribeUpdate> {
+        let (filters, mut msg, resume_token, created_at_us) = match message {
+            SubscribeUpdate {
+                filters,
+                update_oneof: Some(UpdateOneof::Account(msg)),
+                resume_token,
+                created_at_us,
+                seq: _,
+            } => (filters, msg, resume_token, created_at_us),
+            message => return vec![message],
+        };
+        let Some(mut account) = msg.account.take() else {
+            return vec![];
+        };
+        let pubkey = bs58::encode(&account.pubkey).into_string();
+        let data = std::mem::take(&mut account.data);
+        msg.account = Some(account);
+
+        // chunk without data, with max possible varints and space for data length prefix
+        let overhead = SubscribeUpdate {
+            filters: filters.clone(),
+            update_oneof: Some(UpdateOneof::AccountChunk(SubscribeUpdateAccountChunk {
+                account: Some(msg.clone()),
+                index: u32::MAX,
+                total: u32::MAX,
+            })),
+            resume_token: resume_token.clone(),
+            created_at_us,
+            seq: 0,
+        }
+        .encoded_len()
+            + 16;
+        let chunk_size = max.saturating_sub(overhead);
+        if !chunking || chunk_size == 0 {
+            info!(
+                "client #{id}: account {pubkey} dropped, data size {} over the max message size {max}{}",
+                data.len(),
+                if chunking { "" } else { ", chunking is not enabled" }
+            );
+            ACCOUNTS_OVERSIZED_TOTAL
+                .with_label_values(&["dropped"])
+                .inc();
+            return vec![];
+        }
+
+        ACCOUNTS_OVERSIZED_TOTAL
+            .with_label_values(&["chunked"])
+            .inc();
+        let total = data.len().div_ceil(chunk_size) as u32;
+        data.chunks(chunk_size)
+            .enumerate()
+            .map(|(index, chunk)| {
+                let mut msg = msg.clone();
+                if let Some(account) = msg.account.as_mut() {
+                    account.data = chunk.to_vec();
+                }
+                SubscribeUpdate {
+                    filters: filters.clone(),
+                    update_oneof: Some(UpdateOneof::AccountChunk(SubscribeUpdateAccountChunk {
+                        account: Some(msg),
+                        index: index as u32,
+                        total,
+                    })),
+                    resume_token: resume_token.clone(),
+                    created_at_us,
+                    seq: 0,
+                }
+            })
+            .collect()
+    }
+
+    // Oversized block update is split to chunks if client opted in, otherwise the stream is
+    // closed, because the block can not be delivered
+    fn split_block_update(
+        id: usize,
+        message: SubscribeUpdate,
+        max: usize,
+        chunking: bool,
+    ) -> Result<Vec<SubscribeUpdate>, Status> {
+        let size = message.encoded_len();
+        let (filters, mut block, resume_token, created_at_us) = match message {
+            SubscribeUpdate {
+                filters,
+                update_oneof: Some(UpdateOneof::Block(block)),
+                resume_token,
+                created_at_us,
+                seq: _,
+            } => (filters, block, resume_token, created_at_us),
+            message => return Ok(vec![message]),
+        };
+        let slot = block.slot;
+        let transactions = std::mem::take(&mut block.transactions);
+        let accounts = std::mem::take(&mut block.accounts);
+        let entries = std::mem::take(&mut block.entries);
+
+        // chunk without items, with max possible varints and space for length prefixes
+        let overhead = SubscribeUpdate {
+            filters: filters.clone(),
+            update_oneof: Some(UpdateOneof::BlockChunk(SubscribeUpdateBlockChunk {
+                block: Some(block.clone()),
+                index: u32::MAX,
+                total: u32::MAX,
+            })),
+            resume_token: resume_token.clone(),
+            created_at_us,
+            seq: 0,
+        }
+        .encoded_len()
+            + 16;
+        let rewards = block.rewards.take();
+        let mut chunks = BlockChunks::new(block, max.saturating_sub(overhead));
+        let is_chunked = chunking
+            && transactions
+                .into_iter()
+                .all(|tx| chunks.push(tx.encoded_len(), |block| block.transactions.push(tx)))
+            && accounts.into_iter().all(|account| {
+                chunks.push(account.encoded_len(), |block| block.accounts.push(account))
+            })
+            && entries
+                .into_iter()
+                .all(|entry| chunks.push(entry.encoded_len(), |block| block.entries.push(entry)));
+        if !is_chunked {
+            info!(
+                "client #{id}: block {slot} size {size} over the max message size {max}{}",
+                if chunking {
+                    ", item over the limit"
+                } else {
+                    ", chunking is not enabled"
+                }
+            );
+            BLOCKS_OVERSIZED_TOTAL
+                .with_label_values(&["rejected"])
+                .inc();
+            return Err(SubscribeRejectionReason::BlockTooLarge.into_status(
+                Code::ResourceExhausted,
+                format!(
+                    "block {slot} is over the max message size {max}, enable `blocks_chunking` or subscribe to transactions, accounts and entries instead"
+                ),
+            ));
+        }
+
+        BLOCKS_OVERSIZED_TOTAL.with_label_values(&["chunked"]).inc();
+        let mut blocks = chunks.finish();
+        if let Some(first) = blocks.first_mut() {
+            first.rewards = rewards;
+        }
+        let total = blocks.len() as u32;
+        Ok(blocks
+            .into_iter()
+            .enumerate()
+            .map(|(index, block)| SubscribeUpdate {
+                filters: filters.clone(),
+                update_oneof: Some(UpdateOneof::BlockChunk(SubscribeUpdateBlockChunk {
+                    block: Some(block),
+                    index: index as u32,
+                    total,
+                })),
+                resume_token: resume_token.clone(),
+                created_at_us,
+                seq: 0,
+            })
+            .collect())
+    }
+
+    fn check_admin_x_token<T>(&self, request: &Request<T>) -> Result<(), Status> {
+        match &self.config_admin_x_token {
+            Some(admin_x_token) => match request.metadata().get("x-admin-token") {
+                Some(token) if admin_x_token == token => Ok(()),
+                _ => Err(Status::permission_denied("No valid admin token")),
+            },
+            None => Err(Status::unimplemented("admin methods disabled")),
+        }
+    }
+
+    // Returns `false` if client should be removed
+    // Latency of the message (kind, commitment, time when the message was received) is observed
+    // only if the update is queued
+    fn try_send_update(
+        id: usize,
+        stream_tx: &SubscribeUpdateSender,
+        quota: &Option<(Arc<QuotaTracker>, String)>,
+        message: SubscribeUpdate,
+        latency: Option<(&str, CommitmentLevel, Instant)>,
+    ) -> bool {
+        let quota_exhausted = Self::quota_add(quota, &message);
+        let result = stream_tx.try_send(Ok(message));
+        if let (Ok(()), Some((kind, commitment, created_at))) = (&result, latency) {
+            prom::update_message_latency(kind, commitment, created_at);
+        }
+        match result {
+            Ok(()) if quota_exhausted => {
+                info!("client #{id}: quota exhausted");
+                let stream_tx = stream_tx.clone();
+                tokio::spawn(async move {
+                    let _ = stream_tx.send(Err(Self::quota_status())).await;
+                });
+                false
+            }
+            Ok(()) => true,
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                error!("client #{id}: lagged to send update");
+                let stream_tx = stream_tx.clone();
+                tokio::spawn(async move {
+                    let _ = stream_tx.send(Err(Self::lagged_status())).await;
+                });
+                false
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                error!("client #{id}: stream closed");
+                false
+            }
+        }
+    }
+
+    // Returns `true` if quota is exhausted after the message
+    fn quota_add(quota: &Option<(Arc<QuotaTracker>, String)>, message: &SubscribeUpdate) -> bool {
+        match quota {
+            Some((quotas, x_token)) => !quotas.add(x_token, message.encoded_len() as u64),
+            None => false,
+        }
+    }
+
+    fn quota_status() -> Status {
+        SubscribeRejectionReason::QuotaExhausted
+            .into_status(Code::ResourceExhausted, "x-token quota exhausted")
+    }
+
+    fn shed_status() -> Status {
+        SubscribeRejectionReason::Shed.into_status(
+            Code::Unavailable,
+            "server falls behind, low priority subscriptions are closed",
+        )
+    }
+
+    fn lagged_status() -> Status {
+        SubscribeRejectionReason::Lagged.into_status(Code::Internal, "lagged")
+    }
+
+    pub fn get_config_filters(&self) -> &ConfigGrpcFilters {
+        &self.config_filters
+    }
+
+    /// Checks quota and admission of a new subscription, registers it in the list of
+    /// subscriptions and starts the client loop, `on_exit` is called once the loop is finished.
+    /// Shared by gRPC and JSON gateway, so both kinds of subscriptions are controlled in the
+    /// same way
+    pub async fn start_subscription(
+        &self,
+        remote_addr: Option<SocketAddr>,
+        endpoint: String,
+        x_token: Option<String>,
+        with_snapshot: bool,
+        on_exit: impl FnOnce() + Send + 'static,
+    ) -> TonicResult<SubscriptionStarted> {
+        let quota = match &self.quotas {
+            Some(quotas) => {
+                let x_token = x_token.clone().unwrap_or_default();
+                if quotas.is_exhausted(&x_token) {
+                    return Err(Self::quota_status());
+                }
+                Some((Arc::clone(quotas), x_token))
+            }
+            None => None,
+        };
+
+        // high priority subscriptions are never shed
+        let shedding = self
+            .shedding
+            .as_ref()
+            .filter(|shedding| !shedding.is_high_priority(x_token.as_deref()))
+            .cloned();
+        if shedding
+            .as_ref()
+            .map_or(false, |shedding| shedding.is_shedding())
+        {
+            SUBSCRIPTIONS_SHED_TOTAL.inc();
+            return Err(Self::shed_status());
+        }
+
+        // permit is released by the client loop once the first filter is applied (including
+        // snapshot and replay), so a burst of reconnects is not initialized at the same time
+        let admission_permit = match &self.admission {
+            Some(admission) => Some(admission.acquire().await.map_err(|error| {
+                SubscribeRejectionReason::Overloaded
+                    .into_status(Code::ResourceExhausted, error.to_string())
+            })?),
+            None => None,
+        };
+
+        let id = self.subscribe_id.fetch_add(1, Ordering::Relaxed);
+        let snapshot_rx = if with_snapshot {
+            self.snapshot_rx.lock().await.take()
+        } else {
+            None
+        };
+        let (stream_tx, stream_rx) = SubscribeUpdateSender::channel(if snapshot_rx.is_some() {
+            self.config_snapshot_client_channel_capacity
+        } else {
+            self.config_channel_capacity
+        });
+        let (client_tx, client_rx) = mpsc::unbounded_channel();
+
+        let subscription = Arc::new(Subscription {
+            remote_addr,
+            endpoint: endpoint.clone(),
+            x_token,
+            stream_tx: stream_tx.clone(),
+            client_tx,
+            filter: StdMutex::new((HashMap::new(), CommitmentLevel::Processed)),
+            replay: self.replay.clone(),
+            committed_slot: StdMutex::new(None),
+        });
+        self.subscriptions
+            .lock()
+            .expect("alive mutex")
+            .insert(id, Arc::clone(&subscription));
+        let _ = stream_tx.try_send(Ok(SubscribeUpdate {
+            filters: vec![],
+            update_oneof: Some(UpdateOneof::Subscription(SubscribeUpdateSubscription {
+                id: id as u64,
+            })),
+            resume_token: None,
+            created_at_us: None,
+            seq: 0,
+        }));
+
+        let subscriptions = Arc::clone(&self.subscriptions);
+        tokio::spawn(Self::client_loop(
+            id,
+            endpoint,
+            Arc::clone(&self.config_filters),
+            stream_tx,
+            client_rx,
+            snapshot_rx,
+            self.broadcast_tx.subscribe(),
+            self.replay.clone(),
+            self.checkpoint.clone(),
+            self.debug_clients_tx.clone(),
+            quota,
+            shedding,
+            Arc::clone(&self.slot_skew),
+            self.config_max_encoding_message_size,
+            admission_permit,
+            move || {
+                if let Some(subscription) = subscriptions.lock().expect("alive mutex").remove(&id) {
+                    subscription.release_commit();
+                }
+                on_exit();
+            },
+        ));
+
+        Ok(SubscriptionStarted {
+            id,
+            subscription,
+            stream_rx,
+        })
+    }
+}
+
+/// Sender of subscription updates, every update gets the next `seq` when it's queued. Updates
+/// dropped by the client loop (coalescing, size limit) consume numbers with `skip`,
+/// so the client can detect them by a gap
+#[derive(Debug, Clone)]
+pub struct SubscribeUpdateSender {
+    tx: mpsc::Sender<TonicResult<SubscribeUpdate>>,
+    state: Arc<StdMutex<SubscribeUpdateSenderState>>,
+}
+
+#[derive(Debug, Default)]
+struct SubscribeUpdateSenderState {
+    seq: u64,
+    // number of queued items, including statuses
+    queued: u64,
+    // processed slot delivered to the client
+    sent_slot: Option<u64>,
+}
+
+impl SubscribeUpdateSenderState {
+    fn push(&mut self, update: &mut TonicResult<SubscribeUpdate>) {
+        self.queued += 1;
+        if let Ok(update) = update {
+            self.seq += 1;
+            update.seq = self.seq;
+        }
+    }
+}
+
+impl SubscribeUpdateSender {
+    pub fn channel(buffer: usize) -> (Self, mpsc::Receiver<TonicResult<SubscribeUpdate>>) {
+        let (tx, rx) = mpsc::channel(buffer);
+        let sender = Self {
+            tx,
+            state: Arc::default(),
+        };
+        (sender, rx)
+    }
+
+    pub async fn send(
+        &self,
+        mut update: TonicResult<SubscribeUpdate>,
+    ) -> Result<(), mpsc::error::SendError<TonicResult<SubscribeUpdate>>> {
+        let Ok(permit) = self.tx.reserve().await else {
+            return Err(mpsc::error::SendError(update));
+        };
+        // number is assigned under the lock together with queueing, so `seq` follows the order
+        // of updates in the queue even with several senders
+        let mut state = self.state.lock().expect("alive mutex");
+        state.push(&mut update);
+        permit.send(update);
+        Ok(())
+    }
+
+    pub fn try_send(
+        &self,
+        mut update: TonicResult<SubscribeUpdate>,
+    ) -> Result<(), mpsc::error::TrySendError<TonicResult<SubscribeUpdate>>> {
+        let mut state = self.state.lock().expect("alive mutex");
+        let permit = match self.tx.try_reserve() {
+            Ok(permit) => permit,
+            Err(mpsc::error::TrySendError::Full(())) => {
+                return Err(mpsc::error::TrySendError::Full(update))
+            }
+            Err(mpsc::error::TrySendError::Closed(())) => {
+                return Err(mpsc::error::TrySendError::Closed(update))
+            }
+        };
+        state.push(&mut update);
+        permit.send(update);
+        Ok(())
+    }
+
+    /// Consumes numbers of updates dropped by the server
+    pub fn skip(&self, count: usize) {
+        if count > 0 {
+            self.state.lock().expect("alive mutex").seq += count as u64;
+        }
+    }
+
+    /// Number of items queued since the subscription was started
+    pub fn queued(&self) -> u64 {
+        self.state.lock().expect("alive mutex").queued
+    }
+
+    pub fn update_sent_slot(&self, slot: u64) {
+        let mut state = self.state.lock().expect("alive mutex");
+        state.sent_slot = state.sent_slot.max(Some(slot));
+    }
+
+    pub fn get_sent_slot(&self) -> Option<u64> {
+        self.state.lock().expect("alive mutex").sent_slot
+    }
+
+    /// Number of items taken from the queue by the transport
+    pub fn delivered(&self) -> u64 {
+        let in_queue = self.max_capacity() - self.capacity();
+        self.queued().saturating_sub(in_queue as u64)
+    }
+
+    pub fn max_capacity(&self) -> usize {
+        self.tx.max_capacity()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.tx.capacity()
+    }
 }
 
 #[tonic::async_trait]
@@ -1300,16 +2935,34 @@ impl Geyser for GrpcService {
         &self,
         mut request: Request<Streaming<SubscribeRequest>>,
     ) -> TonicResult<Response<Self::SubscribeStream>> {
-        let id = self.subscribe_id.fetch_add(1, Ordering::Relaxed);
-        let snapshot_rx = self.snapshot_rx.lock().await.take();
-        let (stream_tx, stream_rx) = mpsc::channel(if snapshot_rx.is_some() {
-            self.config_snapshot_client_channel_capacity
-        } else {
-            self.config_channel_capacity
-        });
-        let (client_tx, client_rx) = mpsc::unbounded_channel();
+        let x_token = request
+            .metadata()
+            .get("x-token")
+            .and_then(|h| h.to_str().ok().map(|s| s.to_string()));
+        let endpoint = request
+            .metadata()
+            .get("x-endpoint")
+            .and_then(|h| h.to_str().ok().map(|s| s.to_string()))
+            .unwrap_or_else(|| "".to_owned());
+
         let notify_exit1 = Arc::new(Notify::new());
         let notify_exit2 = Arc::new(Notify::new());
+        let SubscriptionStarted {
+            id,
+            subscription,
+            stream_rx,
+        } = self
+            .start_subscription(request.remote_addr(), endpoint, x_token, true, {
+                let notify_exit1 = Arc::clone(&notify_exit1);
+                let notify_exit2 = Arc::clone(&notify_exit2);
+                move || {
+                    notify_exit1.notify_one();
+                    notify_exit2.notify_one();
+                }
+            })
+            .await?;
+        let stream_tx = subscription.stream_tx.clone();
+        let client_tx = subscription.client_tx.clone();
 
         let ping_stream_tx = stream_tx.clone();
         let ping_client_tx = client_tx.clone();
@@ -1321,6 +2974,9 @@ impl Geyser for GrpcService {
             let ping_msg = SubscribeUpdate {
                 filters: vec![],
                 update_oneof: Some(UpdateOneof::Ping(SubscribeUpdatePing {})),
+                resume_token: None,
+                created_at_us: None,
+                seq: 0,
             };
 
             loop {
@@ -1342,15 +2998,10 @@ impl Geyser for GrpcService {
             }
         });
 
-        let endpoint = request
-            .metadata()
-            .get("x-endpoint")
-            .and_then(|h| h.to_str().ok().map(|s| s.to_string()))
-            .unwrap_or_else(|| "".to_owned());
-
         let config_filters = Arc::clone(&self.config_filters);
         let incoming_stream_tx = stream_tx.clone();
         let incoming_client_tx = client_tx;
+        let incoming_debug_clients_tx = self.debug_clients_tx.clone();
         let incoming_exit = Arc::clone(&notify_exit2);
         tokio::spawn(async move {
             let exit = incoming_exit.notified();
@@ -1362,17 +3013,42 @@ impl Geyser for GrpcService {
                         break;
                     }
                     message = request.get_mut().message() => match message {
+                        Ok(Some(request)) if request.commit.is_some() => {
+                            let commit = request.commit.clone().expect("commit");
+                            // commit only acknowledges progress, filter is not replaced, filters
+                            // in the same request would be silently ignored
+                            if request != (SubscribeRequest { commit: Some(commit.clone()), ..Default::default() }) {
+                                let err = Err(SubscribeRejectionReason::InvalidFilter.into_status(
+                                    Code::InvalidArgument,
+                                    "`commit` can not be combined with other fields in the same request",
+                                ));
+                                // response is finished with the status, client loop is stopped too
+                                let _ = incoming_stream_tx.send(err).await;
+                                let _ = incoming_client_tx.send(None);
+                                break;
+                            }
+                            subscription.commit(commit.cursor);
+                            DebugClientMessage::maybe_send(&incoming_debug_clients_tx, || {
+                                DebugClientMessage::UpdateCommit { id, slot: commit.cursor }
+                            });
+                        }
                         Ok(Some(request)) => {
                             if let Err(error) = match Filter::new(&request, &config_filters) {
-                                Ok(filter) => match incoming_client_tx.send(Some(filter)) {
-                                    Ok(()) => Ok(()),
-                                    Err(error) => Err(error.to_string()),
-                                },
+                                Ok(filter) => {
+                                    if filter.get_pong_msg().is_none() {
+                                        subscription.update_filter(&filter);
+                                    }
+                                    match incoming_client_tx.send(Some(filter)) {
+                                        Ok(()) => Ok(()),
+                                        Err(error) => Err(error.to_string()),
+                                    }
+                                }
                                 Err(error) => Err(error.to_string()),
                             } {
-                                let err = Err(Status::invalid_argument(format!(
-                                    "failed to create filter: {error}"
-                                )));
+                                let err = Err(SubscribeRejectionReason::InvalidFilter.into_status(
+                                    Code::InvalidArgument,
+                                    format!("failed to create filter: {error}"),
+                                ));
                                 if incoming_stream_tx.send(err).await.is_err() {
                                     let _ = incoming_client_tx.send(None);
                                 }
@@ -1390,21 +3066,6 @@ impl Geyser for GrpcService {
             }
         });
 
-        tokio::spawn(Self::client_loop(
-            id,
-            endpoint,
-            Arc::clone(&self.config_filters),
-            stream_tx,
-            client_rx,
-            snapshot_rx,
-            self.broadcast_tx.subscribe(),
-            self.debug_clients_tx.clone(),
-            move || {
-                notify_exit1.notify_one();
-                notify_exit2.notify_one();
-            },
-        ));
-
         Ok(Response::new(ReceiverStream::new(stream_rx)))
     }
 
@@ -1494,7 +3155,1597 @@ impl Geyser for GrpcService {
         _request: Request<GetVersionRequest>,
     ) -> Result<Response<GetVersionResponse>, Status> {
         Ok(Response::new(GetVersionResponse {
-            version: serde_json::to_string(&GrpcVersionInfo::default()).unwrap(),
+            version: serde_json::to_string(&GrpcVersionInfo::new(self.slot_skew.get())).unwrap(),
+        }))
+    }
+
+    async fn get_block(
+        &self,
+        request: Request<GetBlockRequest>,
+    ) -> Result<Response<GetBlockResponse>, Status> {
+        if let Some(blocks_meta) = &self.blocks_meta {
+            blocks_meta
+                .get_full_block(request.get_ref(), &self.config_filters.blocks)
+                .await
+        } else {
+            Err(Status::unimplemented("method disabled"))
+        }
+    }
+
+    async fn drop_subscription(
+        &self,
+        request: Request<DropSubscriptionRequest>,
+    ) -> Result<Response<DropSubscriptionResponse>, Status> {
+        self.check_admin_x_token(&request)?;
+
+        let request = request.get_ref();
+        let id = request.id as usize;
+        let subscription = self
+            .subscriptions
+            .lock()
+            .expect("alive mutex")
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| Status::not_found("subscription not found"))?;
+
+        let reason = request
+            .reason
+            .clone()
+            .unwrap_or_else(|| "dropped by admin".to_owned());
+        info!("client #{id}: dropped by admin, reason: {reason}");
+        // status is delivered after already queued messages
+        let stream_tx = subscription.stream_tx.clone();
+        tokio::spawn(async move {
+            let status =
+                SubscribeRejectionReason::DroppedByAdmin.into_status(Code::Aborted, reason);
+            let _ = stream_tx.send(Err(status)).await;
+        });
+        let _ = subscription.client_tx.send(None);
+
+        Ok(Response::new(DropSubscriptionResponse {}))
+    }
+
+    async fn list_subscriptions(
+        &self,
+        request: Request<ListSubscriptionsRequest>,
+    ) -> Result<Response<ListSubscriptionsResponse>, Status> {
+        self.check_admin_x_token(&request)?;
+
+        let received_slot = self.slot_skew.get_received_slot();
+        let mut subscriptions = self
+            .subscriptions
+            .lock()
+            .expect("alive mutex")
+            .iter()
+            .map(|(id, subscription)| {
+                let (filters, commitment) =
+                    subscription.filter.lock().expect("alive mutex").clone();
+                let committed_slot = subscription.get_committed_slot();
+                SubscriptionInfo {
+                    id: *id as u64,
+                    remote_addr: subscription
+                        .remote_addr
+                        .map(|addr| addr.to_string())
+                        .unwrap_or_default(),
+                    endpoint: subscription.endpoint.clone(),
+                    filters,
+                    commitment: commitment as i32,
+                    queued_messages: (subscription.stream_tx.max_capacity()
+                        - subscription.stream_tx.capacity())
+                        as u64,
+                    quota_usage_bytes: self.quotas.as_ref().map(|quotas| {
+                        quotas
+                            .get_usage(subscription.x_token.as_deref().unwrap_or_default())
+                            .bytes
+                    }),
+                    lag_slots: subscription
+                        .stream_tx
+                        .get_sent_slot()
+                        .map(|slot| received_slot.saturating_sub(slot)),
+                    committed_slot,
+                    commit_lag_slots: committed_slot.map(|slot| received_slot.saturating_sub(slot)),
+                    shed: self.shedding.as_ref().map_or(false, |shedding| {
+                        shedding.is_shed(subscription.x_token.as_deref())
+                    }),
+                }
+            })
+            .collect::<Vec<_>>();
+        subscriptions.sort_by_key(|subscription| subscription.id);
+
+        Ok(Response::new(ListSubscriptionsResponse { subscriptions }))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use {
+        super::{
+            AccountsLifecycle, BlockMetaStorage, BroadcastMessages, GrpcService, GrpcShutdown,
+            LagShedding, Message, MessageAccount, MessageAccountInfo, MessageBlock,
+            MessageCreatedAt, MessageSlot, SubscribeUpdateSender, UnixSocketGuard,
+        },
+        crate::{
+            config::{
+                ConfigBlockFailAction, ConfigGrpc, ConfigGrpcFilters, ConfigGrpcFiltersBlocks,
+                ConfigGrpcReconnect, ConfigGrpcShedding, ConfigGrpcUnixSocket,
+            },
+            filters::Filter,
+            prom::RECONNECT_STORM_SIZE,
+            replay::ReplayBuffer,
+            skew::SlotSkew,
+        },
+        serde_json::{json, Value},
+        solana_sdk::{pubkey::Pubkey, system_program},
+        std::{
+            collections::{HashMap, HashSet},
+            future::Future,
+            os::unix::fs::{FileTypeExt, PermissionsExt},
+            path::PathBuf,
+            sync::{
+                atomic::{AtomicUsize, Ordering},
+                Arc,
+            },
+            time::Duration,
+        },
+        tokio::{
+            net::UnixStream,
+            runtime::Runtime,
+            sync::{broadcast, mpsc},
+            time::{sleep, timeout},
+        },
+        tokio_stream::{wrappers::ReceiverStream, StreamExt},
+        tonic::{
+            transport::{Channel, Endpoint, Uri},
+            Code, Status,
+        },
+        tonic_reflection::pb::{
+            server_reflection_client::ServerReflectionClient,
+            server_reflection_request::MessageRequest, server_reflection_response::MessageResponse,
+            ServerReflectionRequest,
+        },
+        tower::service_fn,
+        yellowstone_grpc_proto::{
+            prelude::{
+                geyser_client::GeyserClient, subscribe_update::UpdateOneof, AccountLifecycle,
+                CommitmentLevel, DropSubscriptionRequest, GetBlockRequest,
+                ListSubscriptionsRequest, Reward, Rewards, SlotStatus as SlotStatusProto,
+                SubscribeRejectionReason, SubscribeRequest, SubscribeRequestCommit,
+                SubscribeRequestFilterAccounts, SubscribeRequestFilterSlots, SubscribeUpdate,
+                SubscribeUpdateAccount, SubscribeUpdateAccountInfo, SubscribeUpdateBlock,
+                SubscribeUpdateEntry, SubscribeUpdateSlot, SubscribeUpdateTransactionInfo,
+            },
+            prost::Message as _,
+        },
+    };
+
+    /// Service started with `GrpcService::create` on a new unix socket
+    pub(crate) struct TestServer {
+        pub(crate) path: PathBuf,
+        pub(crate) channel: Channel,
+        pub(crate) client: GeyserClient<Channel>,
+        pub(crate) messages_tx: mpsc::UnboundedSender<Arc<Message>>,
+        pub(crate) slot_skew: Arc<SlotSkew>,
+        pub(crate) shutdown: GrpcShutdown,
+    }
+
+    /// Runs the test with the service on a new runtime
+    pub(crate) fn block_on_test_server<F: Future>(future: F) -> F::Output {
+        // geyser loop runs in blocking task and never finish, so we can not wait runtime shutdown
+        let runtime = Runtime::new().unwrap();
+        let output = runtime.block_on(future);
+        runtime.shutdown_background();
+        output
+    }
+
+    /// Starts the service, `config` is merged with the unix socket path
+    pub(crate) async fn start_test_server(mut config: Value) -> anyhow::Result<TestServer> {
+        static SOCKET_ID: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "yellowstone-grpc-geyser-{}-{}.sock",
+            std::process::id(),
+            SOCKET_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        config["unix_socket"]["path"] = json!(path);
+        let config: ConfigGrpc = serde_json::from_value(config)?;
+        let (_snapshot_tx, messages_tx, _shedding, slot_skew, shutdown) =
+            GrpcService::create(config, ConfigBlockFailAction::Log, None, false).await?;
+
+        let socket_path = path.clone();
+        let channel = Endpoint::from_static("http://[::]:10000")
+            .connect_with_connector(service_fn(move |_: Uri| {
+                UnixStream::connect(socket_path.clone())
+            }))
+            .await?;
+        Ok(TestServer {
+            path,
+            client: GeyserClient::new(channel.clone()),
+            channel,
+            messages_tx,
+            slot_skew,
+            shutdown,
+        })
+    }
+
+    fn spawn_geyser_loop(
+        broadcast_tx: broadcast::Sender<BroadcastMessages>,
+        replay: Option<Arc<ReplayBuffer>>,
+        shedding: Option<Arc<LagShedding>>,
+        accounts_lifecycle_cache_max: Option<usize>,
+    ) -> mpsc::UnboundedSender<Arc<Message>> {
+        let (messages_tx, messages_rx) = mpsc::unbounded_channel();
+        tokio::spawn(GrpcService::geyser_loop(
+            messages_rx,
+            None,
+            broadcast_tx,
+            replay,
+            None,
+            ConfigBlockFailAction::Log,
+            shedding,
+            SlotSkew::new(None),
+            accounts_lifecycle_cache_max,
+        ));
+        messages_tx
+    }
+
+    #[test]
+    fn test_subscribe_unix_socket() {
+        block_on_test_server(subscribe_unix_socket());
+    }
+
+    async fn subscribe_unix_socket() {
+        let TestServer {
+            path,
+            mut client,
+            messages_tx,
+            ..
+        } = start_test_server(json!({"unix_socket": {"mode": "600"}}))
+            .await
+            .unwrap();
+        assert!(path.exists());
+
+        let request = SubscribeRequest {
+            slots: HashMap::from([("client".to_owned(), SubscribeRequestFilterSlots::default())]),
+            ..Default::default()
+        };
+        let mut stream = client
+            .subscribe(tokio_stream::iter([request]).chain(tokio_stream::pending()))
+            .await
+            .unwrap()
+            .into_inner();
+
+        // filter is applied asynchronously, so keep sending slots until first one received
+        let slot = timeout(Duration::from_secs(10), async {
+            loop {
+                let message = Message::Slot(MessageSlot {
+                    slot: 42,
+                    parent: None,
+                    status: CommitmentLevel::Processed,
+                    dead: false,
+                    created_at: MessageCreatedAt::now(),
+                });
+                messages_tx.send(Arc::new(message)).unwrap();
+                if let Ok(message) = timeout(Duration::from_millis(100), stream.message()).await {
+                    if let Some(UpdateOneof::Slot(slot)) = message.unwrap().unwrap().update_oneof {
+                        break slot.slot;
+                    }
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(slot, 42);
+    }
+
+    #[test]
+    fn test_admin_subscriptions() {
+        block_on_test_server(admin_subscriptions());
+    }
+
+    #[test]
+    fn test_reflection() {
+        // assert outside of runtime: panic inside would block on geyser loop thread on drop
+        let services = block_on_test_server(reflection());
+        assert_eq!(
+            services,
+            HashSet::from([
+                "geyser.Geyser".to_owned(),
+                "grpc.health.v1.Health".to_owned(),
+                "grpc.reflection.v1alpha.ServerReflection".to_owned(),
+            ])
+        );
+    }
+
+    async fn reflection() -> HashSet<String> {
+        let TestServer { channel, .. } = start_test_server(json!({"reflection": true}))
+            .await
+            .unwrap();
+
+        let response = timeout(Duration::from_secs(10), async {
+            let request = ServerReflectionRequest {
+                host: String::new(),
+                message_request: Some(MessageRequest::ListServices(String::new())),
+            };
+            // keep request stream open, otherwise the call can end before the response
+            ServerReflectionClient::new(channel)
+                .server_reflection_info(
+                    tokio_stream::iter([request]).chain(tokio_stream::pending()),
+                )
+                .await
+                .unwrap()
+                .into_inner()
+                .message()
+                .await
+                .unwrap()
+                .unwrap()
+        })
+        .await
+        .expect("reflection response in time");
+        match response.message_response {
+            Some(MessageResponse::ListServicesResponse(response)) => response
+                .service
+                .into_iter()
+                .map(|service| service.name)
+                .collect(),
+            _ => panic!("expected list of services"),
+        }
+    }
+
+    #[test]
+    fn test_reconnect_jitter() {
+        block_on_test_server(reconnect_jitter());
+    }
+
+    async fn reconnect_jitter() {
+        const CLIENTS: usize = 20;
+
+        // unload should not stall the validator
+        let close_window_ms = ConfigGrpcReconnect::CLOSE_WINDOW_MS_MAX + 1;
+        assert!(
+            start_test_server(json!({"reconnect": {"close_window_ms": close_window_ms}}))
+                .await
+                .is_err()
+        );
+        let TestServer {
+            client, shutdown, ..
+        } = start_test_server(json!({"reconnect": {
+            "close_window_ms": 500,
+            "retry_after_min_ms": 1000,
+            "retry_after_max_ms": 5000
+        }}))
+        .await
+        .unwrap();
+
+        let mut streams = Vec::with_capacity(CLIENTS);
+        for _ in 0..CLIENTS {
+            let request = SubscribeRequest {
+                slots: HashMap::from([(
+                    "client".to_owned(),
+                    SubscribeRequestFilterSlots::default(),
+                )]),
+                ..Default::default()
+            };
+            let mut stream = client
+                .clone()
+                .subscribe(tokio_stream::iter([request]).chain(tokio_stream::pending()))
+                .await
+                .unwrap()
+                .into_inner();
+            assert!(matches!(
+                stream.message().await.unwrap().unwrap().update_oneof,
+                Some(UpdateOneof::Subscription(_))
+            ));
+            streams.push(stream);
+        }
+
+        timeout(Duration::from_secs(10), shutdown.shutdown())
+            .await
+            .unwrap();
+        assert_eq!(RECONNECT_STORM_SIZE.get(), CLIENTS as i64);
+
+        let mut retry_after = Vec::with_capacity(CLIENTS);
+        for mut stream in streams {
+            let status = loop {
+                match stream.message().await {
+                    Ok(Some(_message)) => continue,
+                    Ok(None) => panic!("expected status"),
+                    Err(status) => break status,
+                }
+            };
+            assert_eq!(status.code(), Code::Unavailable);
+            assert_eq!(
+                SubscribeRejectionReason::from_status(&status),
+                Some(SubscribeRejectionReason::ServerShutdown)
+            );
+            let value = status
+                .metadata()
+                .get("retry-after")
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .parse::<u64>()
+                .unwrap();
+            assert!((1000..=5000).contains(&value), "{value}");
+            retry_after.push(value);
+        }
+        retry_after.sort();
+        retry_after.dedup();
+        assert!(retry_after.len() > 1, "retry hints are not spread");
+    }
+
+    #[test]
+    fn test_subscribe_rejection_reasons() {
+        block_on_test_server(subscribe_rejection_reasons());
+    }
+
+    async fn subscribe_rejection_reasons() {
+        let TestServer { mut client, .. } = start_test_server(json!({"x_token": "secret"}))
+            .await
+            .unwrap();
+        let subscribe = |request: SubscribeRequest, x_token: Option<&str>| {
+            let mut request =
+                tonic::Request::new(tokio_stream::iter([request]).chain(tokio_stream::pending()));
+            if let Some(x_token) = x_token {
+                request
+                    .metadata_mut()
+                    .insert("x-token", x_token.parse().unwrap());
+            }
+            request
+        };
+        let slots = SubscribeRequest {
+            slots: HashMap::from([("client".to_owned(), SubscribeRequestFilterSlots::default())]),
+            ..Default::default()
+        };
+
+        let status = client
+            .subscribe(subscribe(slots.clone(), Some("other")))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unauthenticated);
+        assert_eq!(
+            SubscribeRejectionReason::from_status(&status),
+            Some(SubscribeRejectionReason::Unauthenticated)
+        );
+
+        let requests = [
+            (
+                SubscribeRequest {
+                    accounts: HashMap::from([(
+                        "client".to_owned(),
+                        SubscribeRequestFilterAccounts {
+                            owner: vec!["invalid".to_owned()],
+                            ..Default::default()
+                        },
+                    )]),
+                    ..Default::default()
+                },
+                Code::InvalidArgument,
+                SubscribeRejectionReason::InvalidFilter,
+            ),
+            (
+                SubscribeRequest {
+                    commit: Some(SubscribeRequestCommit { cursor: 1 }),
+                    ..slots.clone()
+                },
+                Code::InvalidArgument,
+                SubscribeRejectionReason::InvalidFilter,
+            ),
+            (
+                SubscribeRequest {
+                    resume_token: Some("0:1:0".to_owned()),
+                    ..slots
+                },
+                Code::FailedPrecondition,
+                SubscribeRejectionReason::ReplayDisabled,
+            ),
+        ];
+        for (request, code, reason) in requests {
+            let mut stream = client
+                .subscribe(subscribe(request, Some("secret")))
+                .await
+                .unwrap()
+                .into_inner();
+            let status = timeout(Duration::from_secs(10), async {
+                loop {
+                    if let Err(status) = stream.message().await {
+                        break status;
+                    }
+                }
+            })
+            .await
+            .unwrap();
+            assert_eq!(status.code(), code);
+            assert_eq!(SubscribeRejectionReason::from_status(&status), Some(reason));
+        }
+
+        let (stream_tx, mut stream_rx) = SubscribeUpdateSender::channel(1);
+        let message = SubscribeUpdate::default();
+        assert!(GrpcService::try_send_update(
+            0,
+            &stream_tx,
+            &None,
+            message.clone(),
+            None
+        ));
+        assert!(!GrpcService::try_send_update(
+            0, &stream_tx, &None, message, None
+        ));
+        assert!(stream_rx.recv().await.unwrap().is_ok());
+        let status = stream_rx.recv().await.unwrap().unwrap_err();
+        assert_eq!(
+            SubscribeRejectionReason::from_status(&status),
+            Some(SubscribeRejectionReason::Lagged)
+        );
+
+        assert_eq!(
+            SubscribeRejectionReason::from_status(&GrpcService::quota_status()),
+            Some(SubscribeRejectionReason::QuotaExhausted)
+        );
+    }
+
+    #[test]
+    fn test_subscribe_admission() {
+        let results = block_on_test_server(subscribe_admission());
+        assert_eq!(
+            results,
+            vec![
+                Ok(()),
+                Err((
+                    Code::ResourceExhausted,
+                    Some(SubscribeRejectionReason::Overloaded)
+                )),
+                Ok(()),
+                Ok(()),
+            ]
+        );
+    }
+
+    async fn subscribe_admission() -> Vec<Result<(), (Code, Option<SubscribeRejectionReason>)>> {
+        let TestServer { client, .. } = start_test_server(json!({"admission": {
+            "max_concurrent": 1,
+            "queue_timeout_ms": 100,
+            "init_timeout_ms": 500
+        }}))
+        .await
+        .unwrap();
+        let subscribe = |request: Option<SubscribeRequest>| {
+            let mut client = client.clone();
+            async move {
+                let request =
+                    tonic::Request::new(tokio_stream::iter(request).chain(tokio_stream::pending()));
+                client
+                    .subscribe(request)
+                    .await
+                    .map(|response| response.into_inner())
+                    .map_err(|status| {
+                        let reason = SubscribeRejectionReason::from_status(&status);
+                        (status.code(), reason)
+                    })
+            }
+        };
+        let slots = SubscribeRequest {
+            slots: HashMap::from([("client".to_owned(), SubscribeRequestFilterSlots::default())]),
+            ..Default::default()
+        };
+
+        let mut results = vec![];
+        // subscription without filter keeps the permit, next one is rejected
+        let idle = subscribe(None).await;
+        results.push(idle.as_ref().map(drop).map_err(Clone::clone));
+        results.push(subscribe(Some(slots.clone())).await.map(drop));
+        // permit of idle subscription is released after `init_timeout_ms`
+        sleep(Duration::from_millis(600)).await;
+        let first = subscribe(Some(slots.clone())).await;
+        results.push(first.as_ref().map(drop).map_err(Clone::clone));
+        // permit is released once the filter is applied
+        results.push(subscribe(Some(slots)).await.map(drop));
+        results
+    }
+
+    fn admin_request<T>(message: T) -> tonic::Request<T> {
+        let mut request = tonic::Request::new(message);
+        request
+            .metadata_mut()
+            .insert("x-admin-token", "admin".parse().unwrap());
+        request
+    }
+
+    async fn admin_subscriptions() {
+        let TestServer {
+            mut client,
+            messages_tx,
+            slot_skew,
+            ..
+        } = start_test_server(json!({"admin_x_token": "admin"}))
+            .await
+            .unwrap();
+
+        let request = SubscribeRequest {
+            slots: HashMap::from([("client".to_owned(), SubscribeRequestFilterSlots::default())]),
+            ..Default::default()
+        };
+        let (requests_tx, requests_rx) = mpsc::channel(4);
+        requests_tx.send(request).await.unwrap();
+        let mut stream = client
+            .subscribe(ReceiverStream::new(requests_rx))
+            .await
+            .unwrap()
+            .into_inner();
+        let id = match stream.message().await.unwrap().unwrap().update_oneof {
+            Some(UpdateOneof::Subscription(msg)) => msg.id,
+            _ => panic!("expected subscription id"),
+        };
+
+        let error = client
+            .list_subscriptions(ListSubscriptionsRequest {})
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), Code::PermissionDenied);
+
+        // filter is applied asynchronously
+        let subscription = timeout(Duration::from_secs(10), async {
+            loop {
+                let response = client
+                    .list_subscriptions(admin_request(ListSubscriptionsRequest {}))
+                    .await
+                    .unwrap()
+                    .into_inner();
+                assert_eq!(response.subscriptions.len(), 1);
+                let subscription = response.subscriptions[0].clone();
+                if subscription.filters.get("slots") == Some(&1) {
+                    break subscription;
+                }
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(subscription.id, id);
+        assert_eq!(subscription.commitment, CommitmentLevel::Processed as i32);
+        assert_eq!(subscription.lag_slots, None);
+
+        // slot is delivered once the next batch is handled, lag is counted from the received slot
+        slot_skew.update_received_slot(12);
+        for slot in [10, 11] {
+            messages_tx
+                .send(create_slot_message(slot, CommitmentLevel::Processed))
+                .unwrap();
+            loop {
+                if let Some(UpdateOneof::Slot(_)) =
+                    stream.message().await.unwrap().unwrap().update_oneof
+                {
+                    break;
+                }
+            }
+        }
+        let lag_slots = timeout(Duration::from_secs(10), async {
+            loop {
+                let response = client
+                    .list_subscriptions(admin_request(ListSubscriptionsRequest {}))
+                    .await
+                    .unwrap()
+                    .into_inner();
+                if let Some(lag_slots) = response.subscriptions[0].lag_slots {
+                    break lag_slots;
+                }
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert!((1..=2).contains(&lag_slots), "{lag_slots}");
+
+        // commit is recorded per subscription
+        let commit = SubscribeRequest {
+            commit: Some(SubscribeRequestCommit { cursor: 9 }),
+            ..Default::default()
+        };
+        requests_tx.send(commit).await.unwrap();
+        let subscription = timeout(Duration::from_secs(10), async {
+            loop {
+                let response = client
+                    .list_subscriptions(admin_request(ListSubscriptionsRequest {}))
+                    .await
+                    .unwrap()
+                    .into_inner();
+                if response.subscriptions[0].committed_slot.is_some() {
+                    break response.subscriptions[0].clone();
+                }
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(subscription.committed_slot, Some(9));
+        assert_eq!(subscription.commit_lag_slots, Some(3));
+
+        client
+            .drop_subscription(admin_request(DropSubscriptionRequest {
+                id,
+                reason: Some("test".to_owned()),
+            }))
+            .await
+            .unwrap();
+        let error = timeout(Duration::from_secs(10), async {
+            loop {
+                if let Err(error) = stream.message().await {
+                    break error;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(error.code(), Code::Aborted);
+        assert_eq!(error.message(), "test");
+        assert_eq!(
+            SubscribeRejectionReason::from_status(&error),
+            Some(SubscribeRejectionReason::DroppedByAdmin)
+        );
+
+        let error = client
+            .drop_subscription(admin_request(DropSubscriptionRequest { id, reason: None }))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), Code::NotFound);
+    }
+
+    fn create_account_message(pubkey: Pubkey, slot: u64, write_version: u64) -> Arc<Message> {
+        Arc::new(Message::Account(MessageAccount {
+            account: MessageAccountInfo {
+                pubkey,
+                lamports: write_version,
+                owner: Pubkey::new_unique(),
+                executable: false,
+                rent_epoch: 0,
+                data: vec![],
+                write_version,
+                txn_signature: None,
+            },
+            slot,
+            is_startup: false,
+            lifecycle: AccountLifecycle::Modified,
+            created_at: MessageCreatedAt::now(),
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_account_write_version_never_regress() {
+        let (broadcast_tx, mut broadcast_rx) = broadcast::channel(16);
+        let messages_tx = spawn_geyser_loop(broadcast_tx, None, None, Some(100));
+
+        let pubkey = Pubkey::new_unique();
+        let pubkey_other = Pubkey::new_unique();
+        for (pubkey, slot, write_version) in [
+            (pubkey, 1, 2),
+            (pubkey, 1, 1),
+            (pubkey, 1, 2),
+            (pubkey_other, 1, 1),
+            (pubkey, 1, 3),
+            (pubkey, 2, 1),
+        ] {
+            messages_tx
+                .send(create_account_message(pubkey, slot, write_version))
+                .unwrap();
+        }
+
+        let mut received = vec![];
+        while received.len() < 4 {
+            let (commitment, _seq, messages) = timeout(Duration::from_secs(1), broadcast_rx.recv())
+                .await
+                .expect("processed messages")
+                .unwrap();
+            assert_eq!(commitment, CommitmentLevel::Processed);
+            for message in messages.iter() {
+                if let Message::Account(msg) = message.as_ref() {
+                    received.push((msg.account.pubkey, msg.slot, msg.account.write_version));
+                }
+            }
+        }
+        assert_eq!(
+            received,
+            vec![
+                (pubkey, 1, 2),
+                (pubkey_other, 1, 1),
+                (pubkey, 1, 3),
+                (pubkey, 2, 1)
+            ]
+        );
+    }
+
+    fn create_block_message(slot: u64) -> Arc<Message> {
+        Arc::new(Message::Block(MessageBlock {
+            parent_slot: slot.saturating_sub(1),
+            slot,
+            parent_blockhash: String::new(),
+            blockhash: format!("blockhash-{slot}"),
+            rewards: vec![],
+            block_time: None,
+            block_height: None,
+            executed_transaction_count: 0,
+            transactions: vec![],
+            updated_account_count: 0,
+            accounts: vec![],
+            entries_count: 0,
+            entries: vec![],
+            created_at: MessageCreatedAt::now(),
+        }))
+    }
+
+    fn create_slot_message(slot: u64, status: CommitmentLevel) -> Arc<Message> {
+        Arc::new(Message::Slot(MessageSlot {
+            slot,
+            parent: None,
+            status,
+            dead: false,
+            created_at: MessageCreatedAt::now(),
         }))
     }
+
+    #[tokio::test]
+    async fn test_get_block() {
+        // disabled by default, full blocks are not kept
+        let (storage, _tx) = BlockMetaStorage::new(16, None);
+        let request = GetBlockRequest {
+            slot: 10,
+            ..Default::default()
+        };
+        let limits = ConfigGrpcFiltersBlocks::default();
+        let status = storage.get_full_block(&request, &limits).await.unwrap_err();
+        assert_eq!(status.code(), Code::Unimplemented);
+
+        let (storage, tx) = BlockMetaStorage::new(16, Some(2));
+        let get_block = |slot: u64, commitment: CommitmentLevel| {
+            let request = GetBlockRequest {
+                slot,
+                commitment: Some(commitment as i32),
+                ..Default::default()
+            };
+            let storage = &storage;
+            let limits = &limits;
+            async move {
+                // storage is updated in background task
+                timeout(Duration::from_secs(1), async {
+                    loop {
+                        let result = storage.get_full_block(&request, limits).await;
+                        if !matches!(&result, Err(status) if status.code() == Code::NotFound) {
+                            break result;
+                        }
+                        tokio::time::sleep(Duration::from_millis(1)).await;
+                    }
+                })
+                .await
+            }
+        };
+
+        tx.send(create_block_message(10)).unwrap();
+        let block = get_block(10, CommitmentLevel::Processed)
+            .await
+            .expect("block stored")
+            .unwrap()
+            .into_inner()
+            .block
+            .unwrap();
+        assert_eq!(block.slot, 10);
+        assert_eq!(block.blockhash, "blockhash-10");
+
+        let status = get_block(10, CommitmentLevel::Confirmed)
+            .await
+            .expect("block stored")
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unavailable);
+
+        tx.send(create_slot_message(10, CommitmentLevel::Confirmed))
+            .unwrap();
+        timeout(Duration::from_secs(1), async {
+            while get_block(10, CommitmentLevel::Confirmed)
+                .await
+                .expect("block stored")
+                .is_err()
+            {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .expect("block confirmed");
+
+        // evicted once finalized slot passed `get_block_keep_slots`
+        tx.send(create_slot_message(13, CommitmentLevel::Finalized))
+            .unwrap();
+        timeout(Duration::from_secs(1), async {
+            while !storage.inner.read().await.blocks_full.is_empty() {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .expect("block evicted");
+        let request = GetBlockRequest {
+            slot: 10,
+            ..Default::default()
+        };
+        let status = storage.get_full_block(&request, &limits).await.unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+
+        let limits = ConfigGrpcFiltersBlocks {
+            include_accounts: false,
+            ..Default::default()
+        };
+        let request = GetBlockRequest {
+            slot: 10,
+            include_accounts: Some(true),
+            ..Default::default()
+        };
+        let status = storage.get_full_block(&request, &limits).await.unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_dead_slot_only_processed() {
+        let (broadcast_tx, mut broadcast_rx) = broadcast::channel(64);
+        let messages_tx = spawn_geyser_loop(broadcast_tx, None, None, Some(100));
+
+        // slot 2 is on the fork which is never finalized, parent of slot 5 is not known
+        for (slot, parent, status) in [
+            (1, Some(0), CommitmentLevel::Processed),
+            (2, Some(1), CommitmentLevel::Processed),
+            (3, Some(1), CommitmentLevel::Processed),
+            (4, Some(3), CommitmentLevel::Processed),
+            (5, None, CommitmentLevel::Processed),
+            (6, Some(5), CommitmentLevel::Processed),
+            (1, None, CommitmentLevel::Confirmed),
+            (1, None, CommitmentLevel::Finalized),
+            (3, None, CommitmentLevel::Confirmed),
+            (3, None, CommitmentLevel::Finalized),
+            (6, None, CommitmentLevel::Confirmed),
+            (6, None, CommitmentLevel::Finalized),
+        ] {
+            let message = MessageSlot {
+                slot,
+                parent,
+                status,
+                dead: false,
+                created_at: MessageCreatedAt::now(),
+            };
+            messages_tx.send(Arc::new(Message::Slot(message))).unwrap();
+        }
+
+        let mut dead = vec![];
+        'outer: loop {
+            let (commitment, _seq, messages) = timeout(Duration::from_secs(1), broadcast_rx.recv())
+                .await
+                .expect("slot messages")
+                .unwrap();
+            for message in messages.iter() {
+                if let Message::Slot(msg) = message.as_ref() {
+                    if msg.dead {
+                        dead.push((commitment, Arc::clone(message)));
+                    }
+                    if commitment == CommitmentLevel::Finalized
+                        && msg.slot == 6
+                        && msg.status == CommitmentLevel::Finalized
+                    {
+                        break 'outer;
+                    }
+                }
+            }
+        }
+        assert_eq!(dead.len(), 1);
+        let (commitment, message) = dead.remove(0);
+        assert_eq!(commitment, CommitmentLevel::Processed);
+        assert_eq!(message.get_slot(), 2);
+
+        // sent only to subscriptions with `include_dead`
+        let limit = ConfigGrpcFilters::default();
+        for include_dead in [false, true] {
+            let request = SubscribeRequest {
+                slots: HashMap::from([(
+                    "".to_owned(),
+                    SubscribeRequestFilterSlots {
+                        include_dead: Some(include_dead),
+                        ..Default::default()
+                    },
+                )]),
+                ..Default::default()
+            };
+            let filter = Filter::new(&request, &limit).unwrap();
+            let updates = filter
+                .get_update(&message, Some(CommitmentLevel::Processed))
+                .collect::<Vec<_>>();
+            match updates.as_slice() {
+                [] => assert!(!include_dead),
+                [SubscribeUpdate {
+                    update_oneof: Some(UpdateOneof::Slot(msg)),
+                    ..
+                }] => assert!(
+                    include_dead
+                        && msg.slot_status == SlotStatusProto::SlotDead as i32
+                        && msg.status == CommitmentLevel::Processed as i32
+                        && msg.slot == 2
+                ),
+                updates => panic!("unexpected updates: {updates:?}"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_accounts_lifecycle_commitment() {
+        let pubkey = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let create_account = |slot, write_version, lamports| {
+            Arc::new(Message::Account(MessageAccount {
+                account: MessageAccountInfo {
+                    pubkey,
+                    lamports,
+                    owner,
+                    executable: false,
+                    rent_epoch: 0,
+                    data: vec![1],
+                    write_version,
+                    txn_signature: None,
+                },
+                slot,
+                is_startup: false,
+                lifecycle: AccountLifecycle::Modified,
+                created_at: MessageCreatedAt::now(),
+            }))
+        };
+
+        for (cache_max, processed_expected, confirmed_expected) in [
+            (
+                Some(100),
+                vec![
+                    AccountLifecycle::Modified,
+                    AccountLifecycle::Closed,
+                    AccountLifecycle::Created,
+                ],
+                vec![AccountLifecycle::Modified, AccountLifecycle::Modified],
+            ),
+            (
+                None,
+                vec![AccountLifecycle::Modified; 3],
+                vec![AccountLifecycle::Modified; 2],
+            ),
+        ] {
+            let (broadcast_tx, mut broadcast_rx) = broadcast::channel(64);
+            let messages_tx = spawn_geyser_loop(broadcast_tx, None, None, cache_max);
+
+            // account is closed on slot 11, which is never confirmed
+            for message in [
+                create_account(10, 1, 10),
+                create_slot_message(10, CommitmentLevel::Processed),
+                create_slot_message(10, CommitmentLevel::Confirmed),
+                create_account(11, 2, 0),
+                create_slot_message(11, CommitmentLevel::Processed),
+                create_account(12, 3, 10),
+                create_slot_message(12, CommitmentLevel::Processed),
+                create_slot_message(12, CommitmentLevel::Confirmed),
+            ] {
+                messages_tx.send(message).unwrap();
+            }
+
+            let mut processed = vec![];
+            let mut confirmed = vec![];
+            'outer: loop {
+                let (commitment, _seq, messages) =
+                    timeout(Duration::from_secs(1), broadcast_rx.recv())
+                        .await
+                        .expect("messages")
+                        .unwrap();
+                for message in messages.iter() {
+                    match (commitment, message.as_ref()) {
+                        (CommitmentLevel::Processed, Message::Account(msg)) => {
+                            processed.push(msg.lifecycle)
+                        }
+                        (CommitmentLevel::Confirmed, Message::Account(msg)) => {
+                            confirmed.push(msg.lifecycle)
+                        }
+                        (CommitmentLevel::Confirmed, Message::Slot(msg))
+                            if msg.slot == 12 && msg.status == CommitmentLevel::Confirmed =>
+                        {
+                            break 'outer
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            assert_eq!(processed, processed_expected, "{cache_max:?}");
+            assert_eq!(confirmed, confirmed_expected, "{cache_max:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resume_mid_slot() {
+        let (broadcast_tx, _) = broadcast::channel(16);
+        let replay = Arc::new(ReplayBuffer::new(100));
+        let config_filters = Arc::new(ConfigGrpcFilters::default());
+
+        let create_filter = |resume_token: Option<String>| {
+            let request = SubscribeRequest {
+                accounts: HashMap::from([(
+                    "".to_owned(),
+                    SubscribeRequestFilterAccounts::default(),
+                )]),
+                filter_ack: Some(false),
+                resume_token,
+                ..Default::default()
+            };
+            Filter::new(&request, &config_filters).unwrap()
+        };
+        let spawn_client = |resume_token: Option<String>| {
+            let (stream_tx, stream_rx) = SubscribeUpdateSender::channel(16);
+            let (client_tx, client_rx) = mpsc::unbounded_channel();
+            tokio::spawn(GrpcService::client_loop(
+                0,
+                "".to_owned(),
+                Arc::clone(&config_filters),
+                stream_tx,
+                client_rx,
+                None,
+                broadcast_tx.subscribe(),
+                Some(Arc::clone(&replay)),
+                None,
+                None,
+                None,
+                None,
+                SlotSkew::new(None),
+                None,
+                None,
+                || {},
+            ));
+            client_tx.send(Some(create_filter(resume_token))).unwrap();
+            (stream_rx, client_tx)
+        };
+
+        async fn recv(rx: &mut mpsc::Receiver<tonic::Result<SubscribeUpdate>>) -> (Pubkey, String) {
+            let message = timeout(Duration::from_secs(1), rx.recv())
+                .await
+                .expect("update")
+                .unwrap()
+                .unwrap();
+            match message.update_oneof {
+                Some(UpdateOneof::Account(msg)) => (
+                    Pubkey::try_from(msg.account.unwrap().pubkey).unwrap(),
+                    message.resume_token.unwrap(),
+                ),
+                update => panic!("unexpected update: {update:?}"),
+            }
+        }
+
+        let mut client = spawn_client(None);
+        // wait filter before first messages
+        sleep(Duration::from_millis(50)).await;
+        let messages_tx = spawn_geyser_loop(
+            broadcast_tx.clone(),
+            Some(Arc::clone(&replay)),
+            None,
+            Some(100),
+        );
+
+        let pubkeys = (0..8).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        for pubkey in &pubkeys[0..5] {
+            messages_tx
+                .send(create_account_message(*pubkey, 1, 1))
+                .unwrap();
+        }
+        let mut tokens = vec![];
+        for pubkey in &pubkeys[0..5] {
+            let (received, token) = recv(&mut client.0).await;
+            assert_eq!(received, *pubkey);
+            tokens.push(token);
+        }
+
+        // client handled only two updates of the slot before disconnect
+        drop(client);
+        for pubkey in &pubkeys[5..7] {
+            messages_tx
+                .send(create_account_message(*pubkey, 1, 1))
+                .unwrap();
+        }
+        sleep(Duration::from_millis(50)).await;
+
+        let mut client = spawn_client(Some(tokens[1].clone()));
+        for pubkey in &pubkeys[2..7] {
+            assert_eq!(recv(&mut client.0).await.0, *pubkey);
+        }
+        messages_tx
+            .send(create_account_message(pubkeys[7], 1, 1))
+            .unwrap();
+        assert_eq!(recv(&mut client.0).await.0, pubkeys[7]);
+        assert!(timeout(Duration::from_millis(50), client.0.recv())
+            .await
+            .is_err());
+
+        // token is applied only with the first filter, updates are not replayed again
+        client
+            .1
+            .send(Some(create_filter(Some(tokens[1].clone()))))
+            .unwrap();
+        sleep(Duration::from_millis(50)).await;
+        let pubkey = Pubkey::new_unique();
+        messages_tx
+            .send(create_account_message(pubkey, 1, 1))
+            .unwrap();
+        assert_eq!(recv(&mut client.0).await.0, pubkey);
+        assert!(timeout(Duration::from_millis(50), client.0.recv())
+            .await
+            .is_err());
+
+        // token was not issued by the buffer
+        let mut client = spawn_client(Some("0:2:1".to_owned()));
+        let error = timeout(Duration::from_secs(1), client.0.recv())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(error.code(), Code::InvalidArgument);
+        assert_eq!(
+            SubscribeRejectionReason::from_status(&error),
+            Some(SubscribeRejectionReason::ResumeTokenUnknown)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shedding_low_priority() {
+        let shedding = LagShedding::new(ConfigGrpcShedding {
+            lag_slots: 10,
+            recover_lag_slots: 2,
+            high_priority_x_tokens: HashSet::from(["token".to_owned()]),
+            close_window_ms: 0,
+        })
+        .unwrap();
+        assert!(LagShedding::new(ConfigGrpcShedding {
+            lag_slots: 10,
+            recover_lag_slots: 2,
+            high_priority_x_tokens: HashSet::new(),
+            close_window_ms: ConfigGrpcShedding::CLOSE_WINDOW_MS_MAX + 1,
+        })
+        .is_err());
+        let (broadcast_tx, _) = broadcast::channel(16);
+        let config_filters = Arc::new(ConfigGrpcFilters::default());
+        let request = SubscribeRequest {
+            slots: HashMap::from([("".to_owned(), SubscribeRequestFilterSlots::default())]),
+            accounts: HashMap::from([("".to_owned(), SubscribeRequestFilterAccounts::default())]),
+            commitment: Some(CommitmentLevel::Processed as i32),
+            filter_ack: Some(false),
+            ..Default::default()
+        };
+
+        // high priority client without shedding and low priority client
+        let mut clients = vec![];
+        for (id, client_shedding) in [(0, None), (1, Some(Arc::clone(&shedding)))] {
+            let (stream_tx, stream_rx) = SubscribeUpdateSender::channel(16);
+            let (client_tx, client_rx) = mpsc::unbounded_channel();
+            let (debug_client_tx, mut debug_client_rx) = mpsc::unbounded_channel();
+            tokio::spawn(GrpcService::client_loop(
+                id,
+                "".to_owned(),
+                Arc::clone(&config_filters),
+                stream_tx,
+                client_rx,
+                None,
+                broadcast_tx.subscribe(),
+                None,
+                None,
+                Some(debug_client_tx),
+                None,
+                client_shedding,
+                SlotSkew::new(None),
+                None,
+                None,
+                || {},
+            ));
+            client_tx
+                .send(Some(Filter::new(&request, &config_filters).unwrap()))
+                .unwrap();
+            // default filter and requested filter
+            for _ in 0..2 {
+                debug_client_rx.recv().await.unwrap();
+            }
+            clients.push((stream_rx, client_tx));
+        }
+        let messages_tx =
+            spawn_geyser_loop(broadcast_tx, None, Some(Arc::clone(&shedding)), Some(100));
+
+        async fn recv(rx: &mut mpsc::Receiver<tonic::Result<SubscribeUpdate>>) -> (&str, u64) {
+            let message = timeout(Duration::from_secs(1), rx.recv())
+                .await
+                .expect("update")
+                .unwrap()
+                .unwrap();
+            // replay buffer is disabled
+            assert!(message.resume_token.is_none());
+            match message.update_oneof {
+                Some(UpdateOneof::Slot(msg)) => ("slot", msg.slot),
+                Some(UpdateOneof::Account(msg)) => ("account", msg.slot),
+                update => panic!("unexpected update: {update:?}"),
+            }
+        }
+
+        // lag is 19 slots, low priority client is closed
+        let pubkey = Pubkey::new_unique();
+        shedding.update_received_slot(20);
+        messages_tx
+            .send(create_slot_message(1, CommitmentLevel::Processed))
+            .unwrap();
+        messages_tx
+            .send(create_account_message(pubkey, 1, 1))
+            .unwrap();
+        assert_eq!(recv(&mut clients[0].0).await, ("slot", 1));
+        assert_eq!(recv(&mut clients[0].0).await, ("account", 1));
+        assert!(shedding.is_shed(None));
+        assert!(!shedding.is_shed(Some("token")));
+        let status = timeout(Duration::from_secs(1), clients[1].0.recv())
+            .await
+            .expect("status")
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unavailable);
+        assert_eq!(
+            SubscribeRejectionReason::from_status(&status),
+            Some(SubscribeRejectionReason::Shed)
+        );
+        assert!(clients[1].0.recv().await.is_none());
+
+        // lag is 1 slot, high priority client is never affected
+        shedding.update_received_slot(21);
+        messages_tx
+            .send(create_slot_message(20, CommitmentLevel::Processed))
+            .unwrap();
+        messages_tx
+            .send(create_account_message(pubkey, 20, 2))
+            .unwrap();
+        assert_eq!(recv(&mut clients[0].0).await, ("slot", 20));
+        assert_eq!(recv(&mut clients[0].0).await, ("account", 20));
+        assert!(!shedding.is_shedding());
+        assert!(!shedding.is_shed(None));
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_update_seq() {
+        let (stream_tx, mut stream_rx) = SubscribeUpdateSender::channel(8);
+        let create_update = |slot| SubscribeUpdate {
+            update_oneof: Some(UpdateOneof::Slot(SubscribeUpdateSlot {
+                slot,
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+
+        // updates from different senders share one counter, value set by the sender is replaced
+        stream_tx.send(Ok(create_update(1))).await.unwrap();
+        stream_tx.clone().try_send(Ok(create_update(2))).unwrap();
+        stream_tx
+            .send(Ok(SubscribeUpdate {
+                seq: 42,
+                ..create_update(3)
+            }))
+            .await
+            .unwrap();
+        // dropped updates consume numbers, errors do not
+        stream_tx.skip(2);
+        stream_tx.send(Ok(create_update(4))).await.unwrap();
+        stream_tx
+            .send(Err(Status::internal("error")))
+            .await
+            .unwrap();
+        stream_tx.send(Ok(create_update(5))).await.unwrap();
+        assert_eq!(stream_tx.queued(), 6);
+        assert_eq!(stream_tx.delivered(), 0);
+        let mut updates = vec![stream_rx.recv().await.unwrap()];
+        assert_eq!(stream_tx.delivered(), 1);
+        drop(stream_tx);
+
+        while let Some(update) = stream_rx.recv().await {
+            updates.push(update);
+        }
+        let seqs = updates
+            .into_iter()
+            .filter_map(|update| update.ok().map(|update| update.seq))
+            .collect::<Vec<_>>();
+        assert_eq!(seqs, vec![1, 2, 3, 6, 7]);
+    }
+
+    #[tokio::test]
+    async fn test_unix_socket_guard() {
+        let path = std::env::temp_dir().join(format!(
+            "yellowstone-grpc-geyser-guard-{}.sock",
+            std::process::id()
+        ));
+        let config = ConfigGrpcUnixSocket {
+            path: path.clone(),
+            mode: Some(0o600),
+        };
+
+        // not a socket
+        std::fs::write(&path, b"").unwrap();
+        assert!(UnixSocketGuard::bind(&config).is_err());
+        std::fs::remove_file(&path).unwrap();
+
+        // stale socket replaced, permissions applied
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        let (guard, _incoming) = UnixSocketGuard::bind(&config).unwrap();
+        let metadata = std::fs::metadata(&path).unwrap();
+        assert!(metadata.file_type().is_socket());
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+
+        // removed on drop
+        drop(guard);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_accounts_lifecycle() {
+        let mut lifecycle = AccountsLifecycle::new(100);
+        let pubkey = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+        let mut update = |write_version, owner, lamports, data: Vec<u8>| {
+            lifecycle.update(&MessageAccountInfo {
+                pubkey,
+                lamports,
+                owner,
+                executable: false,
+                rent_epoch: 0,
+                data,
+                write_version,
+                txn_signature: None,
+            })
+        };
+
+        // first update of the pubkey is not known to be created
+        assert_eq!(update(1, program, 10, vec![1]), AccountLifecycle::Modified);
+        assert_eq!(update(2, program, 20, vec![1]), AccountLifecycle::Modified);
+        assert_eq!(update(3, program, 0, vec![]), AccountLifecycle::Closed);
+        assert_eq!(
+            update(4, system_program::ID, 0, vec![]),
+            AccountLifecycle::Modified
+        );
+        assert_eq!(update(5, program, 10, vec![1]), AccountLifecycle::Created);
+        // older write does not replace the known state
+        assert_eq!(update(4, program, 0, vec![]), AccountLifecycle::Modified);
+        assert_eq!(
+            update(6, system_program::ID, 10, vec![]),
+            AccountLifecycle::Closed
+        );
+        assert_eq!(
+            update(7, system_program::ID, 20, vec![]),
+            AccountLifecycle::Modified
+        );
+    }
+
+    #[test]
+    fn test_split_account_update() {
+        let data = (0..1000).map(|i| i as u8).collect::<Vec<_>>();
+        let message = SubscribeUpdate {
+            filters: vec!["accounts".to_owned()],
+            update_oneof: Some(UpdateOneof::Account(SubscribeUpdateAccount {
+                account: Some(SubscribeUpdateAccountInfo {
+                    pubkey: Pubkey::new_unique().to_bytes().to_vec(),
+                    data: data.clone(),
+                    ..Default::default()
+                }),
+                slot: 100,
+                is_startup: false,
+                lifecycle: AccountLifecycle::Modified as i32,
+            })),
+            resume_token: None,
+            created_at_us: None,
+            seq: 0,
+        };
+        let max = 300;
+        assert!(GrpcService::is_oversized_account(&message, max));
+        assert!(!GrpcService::is_oversized_account(&message, 2000));
+
+        // dropped without opt-in
+        assert!(GrpcService::split_account_update(0, message.clone(), max, false).is_empty());
+
+        let chunks = GrpcService::split_account_update(0, message, max, true);
+        assert!(chunks.len() > 1);
+        let mut data_chunks = vec![];
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert!(chunk.encoded_len() <= max);
+            assert_eq!(chunk.filters, vec!["accounts"]);
+            match &chunk.update_oneof {
+                Some(UpdateOneof::AccountChunk(msg)) => {
+                    assert_eq!(msg.index as usize, i);
+                    assert_eq!(msg.total as usize, chunks.len());
+                    let account = msg.account.as_ref().unwrap();
+                    assert_eq!(account.slot, 100);
+                    data_chunks.extend_from_slice(&account.account.as_ref().unwrap().data);
+                }
+                _ => panic!("expected account chunk"),
+            }
+        }
+        assert_eq!(data_chunks, data);
+    }
+
+    #[test]
+    fn test_split_block_update() {
+        let transactions = (0..50u8)
+            .map(|i| SubscribeUpdateTransactionInfo {
+                signature: vec![i; 64],
+                index: i as u64,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let accounts = (0..20u8)
+            .map(|i| SubscribeUpdateAccountInfo {
+                pubkey: vec![i; 32],
+                data: vec![i; 100],
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let entries = (0..10u64)
+            .map(|i| SubscribeUpdateEntry {
+                slot: 100,
+                index: i,
+                hash: vec![0; 32],
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let block = SubscribeUpdateBlock {
+            slot: 100,
+            blockhash: Pubkey::new_unique().to_string(),
+            rewards: Some(Rewards {
+                rewards: vec![Reward {
+                    pubkey: Pubkey::new_unique().to_string(),
+                    lamports: 42,
+                    ..Default::default()
+                }],
+                num_partitions: None,
+            }),
+            parent_slot: 99,
+            executed_transaction_count: transactions.len() as u64,
+            transactions: transactions.clone(),
+            updated_account_count: accounts.len() as u64,
+            accounts: accounts.clone(),
+            entries_count: entries.len() as u64,
+            entries: entries.clone(),
+            ..Default::default()
+        };
+        let message = SubscribeUpdate {
+            filters: vec!["blocks".to_owned()],
+            update_oneof: Some(UpdateOneof::Block(block.clone())),
+            resume_token: Some("1:100:5".to_owned()),
+            created_at_us: None,
+            seq: 0,
+        };
+        let max = 1000;
+        assert!(GrpcService::is_oversized_block(&message, max));
+        assert!(!GrpcService::is_oversized_block(&message, 100_000));
+
+        // rejected without opt-in or if one item is over the limit
+        let status = GrpcService::split_block_update(0, message.clone(), max, false).unwrap_err();
+        assert_eq!(status.code(), Code::ResourceExhausted);
+        assert_eq!(
+            SubscribeRejectionReason::from_status(&status),
+            Some(SubscribeRejectionReason::BlockTooLarge)
+        );
+        let status = GrpcService::split_block_update(0, message.clone(), 150, true).unwrap_err();
+        assert_eq!(status.code(), Code::ResourceExhausted);
+
+        let chunks = GrpcService::split_block_update(0, message, max, true).unwrap();
+        assert!(chunks.len() > 1);
+        let mut restored = SubscribeUpdateBlock::default();
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert!(chunk.encoded_len() <= max);
+            assert_eq!(chunk.filters, vec!["blocks"]);
+            assert_eq!(chunk.resume_token.as_deref(), Some("1:100:5"));
+            match &chunk.update_oneof {
+                Some(UpdateOneof::BlockChunk(msg)) => {
+                    assert_eq!(msg.index as usize, i);
+                    assert_eq!(msg.total as usize, chunks.len());
+                    let mut block = msg.block.clone().unwrap();
+                    assert_eq!(block.slot, 100);
+                    assert_eq!(block.rewards.is_some(), i == 0);
+                    if i == 0 {
+                        restored = block;
+                    } else {
+                        restored.transactions.append(&mut block.transactions);
+                        restored.accounts.append(&mut block.accounts);
+                        restored.entries.append(&mut block.entries);
+                    }
+                }
+                _ => panic!("expected block chunk"),
+            }
+        }
+        assert_eq!(restored, block);
+    }
 }
@@ -0,0 +1,663 @@
+use {
+    crate::{
+        config::{ConfigGrpcJsonGateway, ConfigGrpcJsonGatewayBytesEncoding},
+        filters::Filter,
+        grpc::GrpcService,
+    },
+    base64::{engine::general_purpose::STANDARD as base64_engine, Engine},
+    http_body::{LengthLimitError, Limited},
+    hyper::{
+        body::{self, Bytes},
+        header::{HeaderValue, CONTENT_LENGTH, CONTENT_TYPE},
+        server::{
+            conn::{AddrIncoming, AddrStream},
+            Builder,
+        },
+        service::{make_service_fn, service_fn},
+        Body, Method, Request, Response, Server, StatusCode,
+    },
+    log::{error, info},
+    prost_types::{
+        field_descriptor_proto::{Label, Type},
+        DescriptorProto, FieldDescriptorProto, FileDescriptorSet,
+    },
+    serde_json::{json, Value},
+    std::{collections::HashMap, future::Future, net::SocketAddr, sync::Arc},
+    tokio::sync::mpsc,
+    tonic::{Code, Result as TonicResult, Status},
+    yellowstone_grpc_proto::{
+        prelude::{SubscribeRequest, SubscribeUpdate},
+        prost::Message as _,
+    },
+};
+
+lazy_static::lazy_static! {
+    /// Descriptors of messages by full name (`.geyser.SubscribeUpdate`), used to encode
+    /// fields by their proto types
+    static ref MESSAGES: HashMap<String, DescriptorProto> = {
+        let set = FileDescriptorSet::decode(yellowstone_grpc_proto::geyser::FILE_DESCRIPTOR_SET)
+            .expect("valid file descriptor set");
+        let mut messages = HashMap::new();
+        for file in set.file {
+            let package = file.package.map(|package| format!(".{package}")).unwrap_or_default();
+            add_messages(&mut messages, &package, file.message_type);
+        }
+        messages
+    };
+}
+
+fn add_messages(
+    messages: &mut HashMap<String, DescriptorProto>,
+    prefix: &str,
+    descriptors: Vec<DescriptorProto>,
+) {
+    for mut descriptor in descriptors {
+        let name = format!("{prefix}.{}", descriptor.name());
+        add_messages(messages, &name, std::mem::take(&mut descriptor.nested_type));
+        messages.insert(name, descriptor);
+    }
+}
+
+struct JsonGatewayState {
+    x_token: Option<String>,
+    bytes_encoding: ConfigGrpcJsonGatewayBytesEncoding,
+    grpc: Arc<GrpcService>,
+}
+
+/// Streams `SubscribeUpdate` as newline delimited JSON over HTTP/1.1, subscriptions are
+/// started by gRPC service, so they are filtered and controlled in the same way
+pub struct JsonGateway {
+    server: Builder<AddrIncoming>,
+    state: Arc<JsonGatewayState>,
+}
+
+impl JsonGateway {
+    pub fn bind(
+        config: ConfigGrpcJsonGateway,
+        x_token: Option<String>,
+        grpc: Arc<GrpcService>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            server: Server::try_bind(&config.address)?,
+            state: Arc::new(JsonGatewayState {
+                x_token,
+                bytes_encoding: config.bytes_encoding,
+                grpc,
+            }),
+        })
+    }
+
+    pub async fn serve(self, shutdown: impl Future<Output = ()>) {
+        let state = self.state;
+        let make_service = make_service_fn(move |stream: &AddrStream| {
+            let state = Arc::clone(&state);
+            let remote_addr = stream.remote_addr();
+            async move {
+                Ok::<_, hyper::Error>(service_fn(move |req: Request<Body>| {
+                    Self::handle(Arc::clone(&state), remote_addr, req)
+                }))
+            }
+        });
+        if let Err(error) = self
+            .server
+            .serve(make_service)
+            .with_graceful_shutdown(shutdown)
+            .await
+        {
+            error!("json gateway failed: {error}");
+        }
+    }
+
+    const REQUEST_BODY_MAX: usize = 1024 * 1024;
+
+    async fn handle(
+        state: Arc<JsonGatewayState>,
+        remote_addr: SocketAddr,
+        req: Request<Body>,
+    ) -> Result<Response<Body>, hyper::Error> {
+        if req.method() != Method::POST || req.uri().path() != "/subscribe" {
+            return Ok(Self::response(StatusCode::NOT_FOUND, ""));
+        }
+        let x_token = Self::get_header(&req, "x-token");
+        if let Some(expected) = &state.x_token {
+            if x_token.as_ref() != Some(expected) {
+                return Ok(Self::response(
+                    StatusCode::UNAUTHORIZED,
+                    "No valid auth token",
+                ));
+            }
+        }
+        let endpoint = Self::get_header(&req, "x-endpoint").unwrap_or_default();
+
+        // endpoint can be public, request is never buffered over the limit
+        let content_length = Self::get_header(&req, CONTENT_LENGTH.as_str())
+            .and_then(|value| value.parse::<usize>().ok());
+        if content_length.map_or(false, |length| length > Self::REQUEST_BODY_MAX) {
+            return Ok(Self::response(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "request body is too large",
+            ));
+        }
+        let body = match body::to_bytes(Limited::new(req.into_body(), Self::REQUEST_BODY_MAX)).await
+        {
+            Ok(body) => body,
+            Err(error) if error.is::<LengthLimitError>() => {
+                return Ok(Self::response(
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                    "request body is too large",
+                ))
+            }
+            Err(error) => {
+                return Ok(Self::response(
+                    StatusCode::BAD_REQUEST,
+                    &format!("failed to read request: {error}"),
+                ))
+            }
+        };
+        let request = match serde_json::from_slice::<SubscribeRequest>(&body) {
+            Ok(request) => request,
+            Err(error) => {
+                return Ok(Self::response(
+                    StatusCode::BAD_REQUEST,
+                    &format!("failed to parse request: {error}"),
+                ))
+            }
+        };
+
+        let started = match state
+            .grpc
+            .start_subscription(Some(remote_addr), endpoint, x_token, false, || {})
+            .await
+        {
+            Ok(started) => started,
+            Err(status) => {
+                let code = if status.code() == Code::ResourceExhausted {
+                    StatusCode::TOO_MANY_REQUESTS
+                } else {
+                    StatusCode::SERVICE_UNAVAILABLE
+                };
+                return Ok(Self::response(code, status.message()));
+            }
+        };
+        let filter = match Filter::new(&request, state.grpc.get_config_filters()) {
+            Ok(filter) => filter,
+            Err(error) => {
+                started.close();
+                return Ok(Self::response(
+                    StatusCode::BAD_REQUEST,
+                    &format!("failed to create filter: {error}"),
+                ));
+            }
+        };
+        info!("client #{}: json gateway subscription", started.id);
+        started.update_filter(filter);
+        let (stream_rx, client_tx) = started.into_stream();
+
+        let (body_tx, body) = Body::channel();
+        tokio::spawn(Self::send_loop(
+            stream_rx,
+            client_tx,
+            body_tx,
+            state.bytes_encoding,
+        ));
+
+        let mut response = Response::new(body);
+        response.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/x-ndjson"),
+        );
+        Ok(response)
+    }
+
+    fn get_header(req: &Request<Body>, name: &str) -> Option<String> {
+        req.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_owned())
+    }
+
+    fn response(status: StatusCode, message: &str) -> Response<Body> {
+        let mut response = Response::new(Body::from(message.to_owned()));
+        *response.status_mut() = status;
+        response
+    }
+
+    // Status which closes the subscription is sent as the last line
+    async fn send_loop(
+        mut stream_rx: mpsc::Receiver<TonicResult<SubscribeUpdate>>,
+        client_tx: mpsc::UnboundedSender<Option<Filter>>,
+        mut body_tx: body::Sender,
+        bytes_encoding: ConfigGrpcJsonGatewayBytesEncoding,
+    ) {
+        while let Some(item) = stream_rx.recv().await {
+            let line = match item {
+                Ok(update) => encode_update(&update, bytes_encoding),
+                Err(status) => encode_status(&status),
+            };
+            if body_tx.send_data(line).await.is_err() {
+                break;
+            }
+        }
+        // response is closed by the client, stop the client loop
+        let _ = client_tx.send(None);
+    }
+}
+
+/// Encodes status as one line of JSON: `{"error": {"code": 8, "message": "..."}}`
+pub fn encode_status(status: &Status) -> Bytes {
+    let value = json!({
+        "error": {
+            "code": status.code() as i32,
+            "message": status.message(),
+        }
+    });
+    let mut line = serde_json::to_vec(&value).expect("json serializable value");
+    line.push(b'\n');
+    line.into()
+}
+
+/// Encodes update as one line of JSON, 64-bit integers are strings as in proto3 JSON mapping
+pub fn encode_update(
+    update: &SubscribeUpdate,
+    bytes_encoding: ConfigGrpcJsonGatewayBytesEncoding,
+) -> Bytes {
+    let mut value = serde_json::to_value(update).expect("json serializable update");
+    encode_message(&mut value, ".geyser.SubscribeUpdate", bytes_encoding);
+    let mut line = serde_json::to_vec(&value).expect("json serializable value");
+    line.push(b'\n');
+    line.into()
+}
+
+// Serde keeps binary fields as arrays of numbers and 64-bit integers as numbers, fields are
+// converted by types from the message descriptor
+fn encode_message(
+    value: &mut Value,
+    type_name: &str,
+    bytes_encoding: ConfigGrpcJsonGatewayBytesEncoding,
+) {
+    let (Value::Object(map), Some(descriptor)) = (value, MESSAGES.get(type_name)) else {
+        return;
+    };
+    for (key, value) in map.iter_mut() {
+        if let Some(field) = descriptor.field.iter().find(|field| field.name() == key) {
+            encode_field(value, field, bytes_encoding);
+            continue;
+        }
+
+        // oneof is an object with the name of the set field as the only key
+        let Some(index) = descriptor
+            .oneof_decl
+            .iter()
+            .position(|oneof| oneof.name() == key)
+        else {
+            continue;
+        };
+        if let Value::Object(oneof) = value {
+            for (key, value) in oneof.iter_mut() {
+                if let Some(field) = descriptor
+                    .field
+                    .iter()
+                    .find(|field| field.oneof_index == Some(index as i32) && field.name() == key)
+                {
+                    encode_field(value, field, bytes_encoding);
+                }
+            }
+        }
+    }
+}
+
+fn encode_field(
+    value: &mut Value,
+    field: &FieldDescriptorProto,
+    bytes_encoding: ConfigGrpcJsonGatewayBytesEncoding,
+) {
+    match value {
+        Value::Array(items) if field.label() == Label::Repeated => {
+            for item in items.iter_mut() {
+                encode_value(item, field, bytes_encoding);
+            }
+        }
+        value => encode_value(value, field, bytes_encoding),
+    }
+}
+
+fn encode_value(
+    value: &mut Value,
+    field: &FieldDescriptorProto,
+    bytes_encoding: ConfigGrpcJsonGatewayBytesEncoding,
+) {
+    match field.r#type() {
+        Type::Message => encode_message(value, field.type_name(), bytes_encoding),
+        Type::Bytes if bytes_encoding == ConfigGrpcJsonGatewayBytesEncoding::Base64 => {
+            if let Some(encoded) = encode_bytes(value) {
+                *value = encoded;
+            }
+        }
+        Type::Int64 | Type::Uint64 | Type::Sint64 | Type::Fixed64 | Type::Sfixed64 => {
+            if let Value::Number(number) = value {
+                *value = Value::String(number.to_string());
+            }
+        }
+        _ => {}
+    }
+}
+
+fn encode_bytes(value: &Value) -> Option<Value> {
+    let Value::Array(items) = value else {
+        return None;
+    };
+    items
+        .iter()
+        .map(|item| item.as_u64().and_then(|byte| u8::try_from(byte).ok()))
+        .collect::<Option<Vec<u8>>>()
+        .map(|bytes| Value::String(base64_engine.encode(bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{encode_update, JsonGateway},
+        crate::{
+            config::ConfigGrpcJsonGatewayBytesEncoding,
+            grpc::tests::{block_on_test_server, start_test_server, TestServer},
+        },
+        hyper::Body,
+        serde_json::{json, Value},
+        std::{net::SocketAddr, time::Duration},
+        tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::TcpStream,
+            sync::mpsc,
+            time::{sleep, timeout},
+        },
+        tonic::{Code, Status},
+        yellowstone_grpc_proto::prelude::{
+            subscribe_update::UpdateOneof, AccountLifecycle, CompiledInstruction,
+            ListSubscriptionsRequest, Message, MessageHeader, SubscribeUpdate,
+            SubscribeUpdateAccount, SubscribeUpdateAccountInfo, SubscribeUpdateBlock,
+            SubscribeUpdateSlot, SubscribeUpdateTransactionInfo, Transaction,
+        },
+    };
+
+    fn decode(
+        update: &SubscribeUpdate,
+        bytes_encoding: ConfigGrpcJsonGatewayBytesEncoding,
+    ) -> Value {
+        let line = encode_update(update, bytes_encoding);
+        assert_eq!(line.last(), Some(&b'\n'));
+        serde_json::from_slice(&line).unwrap()
+    }
+
+    #[test]
+    fn test_encode_account() {
+        let update = SubscribeUpdate {
+            filters: vec!["client".to_owned()],
+            update_oneof: Some(UpdateOneof::Account(SubscribeUpdateAccount {
+                account: Some(SubscribeUpdateAccountInfo {
+                    pubkey: vec![1, 2, 3],
+                    lamports: 42,
+                    owner: vec![4, 5, 6],
+                    executable: false,
+                    rent_epoch: 0,
+                    data: vec![255, 0],
+                    write_version: 1,
+                    txn_signature: None,
+                }),
+                slot: 100,
+                is_startup: false,
+                lifecycle: AccountLifecycle::Created as i32,
+            })),
+            resume_token: Some("0:100:0".to_owned()),
+            created_at_us: None,
+            seq: 0,
+        };
+
+        let value = decode(&update, ConfigGrpcJsonGatewayBytesEncoding::Base64);
+        assert_eq!(value["filters"], json!(["client"]));
+        assert_eq!(value["resume_token"], json!("0:100:0"));
+        let account = &value["update_oneof"]["account"]["account"];
+        assert_eq!(account["pubkey"], json!("AQID"));
+        assert_eq!(account["owner"], json!("BAUG"));
+        assert_eq!(account["data"], json!("/wA="));
+        // 64-bit integers are strings
+        assert_eq!(account["lamports"], json!("42"));
+        assert_eq!(value["update_oneof"]["account"]["slot"], json!("100"));
+        assert_eq!(value["update_oneof"]["account"]["lifecycle"], json!(1));
+        assert_eq!(account["txn_signature"], Value::Null);
+
+        let value = decode(&update, ConfigGrpcJsonGatewayBytesEncoding::Bytes);
+        let account = &value["update_oneof"]["account"]["account"];
+        assert_eq!(account["pubkey"], json!([1, 2, 3]));
+        assert_eq!(account["data"], json!([255, 0]));
+        assert_eq!(account["write_version"], json!("1"));
+    }
+
+    #[test]
+    fn test_encode_block() {
+        let transaction = SubscribeUpdateTransactionInfo {
+            signature: vec![1; 4],
+            transaction: Some(Transaction {
+                signatures: vec![vec![1; 4], vec![2; 4]],
+                message: Some(Message {
+                    header: Some(MessageHeader::default()),
+                    account_keys: vec![vec![3; 4]],
+                    recent_blockhash: vec![4; 4],
+                    instructions: vec![CompiledInstruction {
+                        program_id_index: 0,
+                        accounts: vec![0, 1],
+                        data: vec![],
+                    }],
+                    ..Default::default()
+                }),
+            }),
+            ..Default::default()
+        };
+        let update = SubscribeUpdate {
+            filters: vec![],
+            update_oneof: Some(UpdateOneof::Block(SubscribeUpdateBlock {
+                slot: 100,
+                transactions: vec![transaction.clone()],
+                accounts: vec![],
+                ..Default::default()
+            })),
+            resume_token: None,
+            created_at_us: None,
+            seq: 0,
+        };
+
+        let value = decode(&update, ConfigGrpcJsonGatewayBytesEncoding::Base64);
+        let block = &value["update_oneof"]["block"];
+        // empty list of account updates is not binary data
+        assert_eq!(block["accounts"], json!([]));
+        let tx = &block["transactions"][0];
+        assert_eq!(tx["signature"], json!("AQEBAQ=="));
+        assert_eq!(
+            tx["transaction"]["signatures"],
+            json!(["AQEBAQ==", "AgICAg=="])
+        );
+        let message = &tx["transaction"]["message"];
+        assert_eq!(message["account_keys"], json!(["AwMDAw=="]));
+        assert_eq!(message["recent_blockhash"], json!("BAQEBA=="));
+        assert_eq!(
+            message["instructions"],
+            json!([{"program_id_index": 0, "accounts": "AAE=", "data": ""}])
+        );
+
+        assert_eq!(block["slot"], json!("100"));
+        assert_eq!(tx["index"], json!("0"));
+
+        let value = decode(&update, ConfigGrpcJsonGatewayBytesEncoding::Bytes);
+        let message = &value["update_oneof"]["block"]["transactions"][0]["transaction"]["message"];
+        assert_eq!(message["recent_blockhash"], json!([4, 4, 4, 4]));
+        assert_eq!(
+            message["instructions"],
+            json!([{"program_id_index": 0, "accounts": [0, 1], "data": []}])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_loop() {
+        let (stream_tx, stream_rx) = mpsc::channel(4);
+        let (client_tx, mut client_rx) = mpsc::unbounded_channel();
+        let (body_tx, body) = Body::channel();
+        tokio::spawn(JsonGateway::send_loop(
+            stream_rx,
+            client_tx,
+            body_tx,
+            ConfigGrpcJsonGatewayBytesEncoding::Base64,
+        ));
+
+        let update = SubscribeUpdate {
+            filters: vec!["client".to_owned()],
+            update_oneof: Some(UpdateOneof::Slot(SubscribeUpdateSlot {
+                slot: 2,
+                ..Default::default()
+            })),
+            resume_token: None,
+            created_at_us: None,
+            seq: 1,
+        };
+        stream_tx.send(Ok(update)).await.unwrap();
+        stream_tx
+            .send(Err(Status::resource_exhausted("quota exhausted")))
+            .await
+            .unwrap();
+        drop(stream_tx);
+
+        let body = hyper::body::to_bytes(body).await.unwrap();
+        let lines = body
+            .split(|byte| *byte == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice::<Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["seq"], json!("1"));
+        assert_eq!(lines[0]["filters"], json!(["client"]));
+        assert_eq!(lines[0]["update_oneof"]["slot"]["slot"], json!("2"));
+        assert_eq!(
+            lines[1],
+            json!({"error": {"code": Code::ResourceExhausted as i32, "message": "quota exhausted"}})
+        );
+        // client loop is stopped with the response
+        assert!(matches!(client_rx.recv().await, Some(None)));
+    }
+
+    #[test]
+    fn test_subscribe() {
+        block_on_test_server(subscribe());
+    }
+
+    async fn post_subscribe(address: SocketAddr, x_token: Option<&str>, body: &str) -> String {
+        let x_token = x_token
+            .map(|x_token| format!("x-token: {x_token}\r\n"))
+            .unwrap_or_default();
+        let request = format!("{x_token}content-length: {}\r\n\r\n{body}", body.len());
+        post_subscribe_raw(address, &request).await
+    }
+
+    // `request` is headers and body after the request line
+    async fn post_subscribe_raw(address: SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        let request = format!("POST /subscribe HTTP/1.1\r\nhost: localhost\r\n{request}");
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        // status line and the first update (or error message) are enough
+        let mut response = vec![0; 4096];
+        let size = stream.read(&mut response).await.unwrap();
+        String::from_utf8_lossy(&response[..size]).into_owned()
+    }
+
+    async fn subscribe() {
+        let quotas_path = std::env::temp_dir().join(format!(
+            "yellowstone-grpc-geyser-json-gateway-quotas-{}.json",
+            std::process::id()
+        ));
+        // one period since unix epoch, usage of `limited` is over the quota
+        std::fs::write(
+            &quotas_path,
+            r#"{"limited": {"period_start": 0, "bytes": 100}}"#,
+        )
+        .unwrap();
+        let address = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let TestServer { mut client, .. } = start_test_server(json!({
+            "admin_x_token": "admin",
+            "json_gateway": {"address": address},
+            "quotas": {"path": quotas_path, "bytes": 10, "period": 4000000000u64}
+        }))
+        .await
+        .unwrap();
+
+        // body over the limit is rejected by the header or while it's read
+        let max = JsonGateway::REQUEST_BODY_MAX;
+        for request in [
+            format!("content-length: {}\r\n\r\n{{}}", max + 1),
+            format!(
+                "transfer-encoding: chunked\r\n\r\n{:x}\r\n{}\r\n0\r\n\r\n",
+                max + 1,
+                " ".repeat(max + 1)
+            ),
+        ] {
+            let response = timeout(
+                Duration::from_secs(10),
+                post_subscribe_raw(address, &request),
+            )
+            .await
+            .unwrap();
+            assert!(
+                response.starts_with("HTTP/1.1 413 Payload Too Large"),
+                "{response}"
+            );
+        }
+
+        let slots = r#"{"slots": {"client": {}}}"#;
+        let response = timeout(
+            Duration::from_secs(10),
+            post_subscribe(address, Some("limited"), slots),
+        )
+        .await
+        .unwrap();
+        assert!(
+            response.starts_with("HTTP/1.1 429 Too Many Requests"),
+            "{response}"
+        );
+
+        let response = timeout(
+            Duration::from_secs(10),
+            post_subscribe(address, None, slots),
+        )
+        .await
+        .unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+        assert!(
+            response.contains(r#""subscription":{"id":"0"}"#),
+            "{response}"
+        );
+
+        // subscription is visible to admin methods
+        let subscription = timeout(Duration::from_secs(10), async {
+            loop {
+                let mut request = tonic::Request::new(ListSubscriptionsRequest {});
+                request
+                    .metadata_mut()
+                    .insert("x-admin-token", "admin".parse().unwrap());
+                let response = client
+                    .list_subscriptions(request)
+                    .await
+                    .unwrap()
+                    .into_inner();
+                if let Some(subscription) = response.subscriptions.first() {
+                    break subscription.clone();
+                }
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(subscription.id, 0);
+        assert_eq!(subscription.filters.get("slots"), Some(&1));
+        std::fs::remove_file(&quotas_path).unwrap();
+    }
+}
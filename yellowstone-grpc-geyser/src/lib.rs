@@ -2,11 +2,20 @@
 #![deny(clippy::missing_const_for_fn)]
 #![deny(clippy::trivially_copy_pass_by_ref)]
 
+pub mod admission;
+pub mod cache;
+pub mod checkpoint;
+pub mod coalesce;
 pub mod config;
 pub mod filters;
 pub mod grpc;
+#[cfg(feature = "json-gateway")]
+pub mod json_gateway;
 pub mod plugin;
 pub mod prom;
+pub mod quota;
+pub mod replay;
+pub mod skew;
 pub mod version;
 
 pub fn get_thread_name() -> String {
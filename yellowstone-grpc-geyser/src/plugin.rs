@@ -1,18 +1,23 @@
 use {
     crate::{
         config::Config,
-        grpc::{GrpcService, Message},
+        grpc::{GrpcService, GrpcShutdown, LagShedding, Message},
         prom::{self, PrometheusService, MESSAGE_QUEUE_SIZE},
+        skew::SlotSkew,
     },
     agave_geyser_plugin_interface::geyser_plugin_interface::{
         GeyserPlugin, GeyserPluginError, ReplicaAccountInfoVersions, ReplicaBlockInfoVersions,
         ReplicaEntryInfoVersions, ReplicaTransactionInfoVersions, Result as PluginResult,
         SlotStatus,
     },
-    std::{concat, env, sync::Arc, time::Duration},
+    std::{
+        concat, env,
+        sync::Arc,
+        time::{Duration, Instant},
+    },
     tokio::{
         runtime::{Builder, Runtime},
-        sync::{mpsc, Notify},
+        sync::mpsc,
     },
 };
 
@@ -21,7 +26,9 @@ pub struct PluginInner {
     runtime: Runtime,
     snapshot_channel: Option<crossbeam_channel::Sender<Option<Message>>>,
     grpc_channel: mpsc::UnboundedSender<Arc<Message>>,
-    grpc_shutdown: Arc<Notify>,
+    grpc_shedding: Option<Arc<LagShedding>>,
+    grpc_slot_skew: Arc<SlotSkew>,
+    grpc_shutdown: GrpcShutdown,
     prometheus: PrometheusService,
 }
 
@@ -66,10 +73,17 @@ impl GeyserPlugin for Plugin {
             .build()
             .map_err(|error| GeyserPluginError::Custom(Box::new(error)))?;
 
-        let (snapshot_channel, grpc_channel, grpc_shutdown, prometheus) =
-            runtime.block_on(async move {
-                let (debug_client_tx, debug_client_rx) = mpsc::unbounded_channel();
-                let (snapshot_channel, grpc_channel, grpc_shutdown) = GrpcService::create(
+        let (
+            snapshot_channel,
+            grpc_channel,
+            grpc_shedding,
+            grpc_slot_skew,
+            grpc_shutdown,
+            prometheus,
+        ) = runtime.block_on(async move {
+            let (debug_client_tx, debug_client_rx) = mpsc::unbounded_channel();
+            let (snapshot_channel, grpc_channel, grpc_shedding, grpc_slot_skew, grpc_shutdown) =
+                GrpcService::create(
                     config.grpc,
                     config.block_fail_action,
                     config.debug_clients_http.then_some(debug_client_tx),
@@ -77,23 +91,27 @@ impl GeyserPlugin for Plugin {
                 )
                 .await
                 .map_err(|error| GeyserPluginError::Custom(format!("{error:?}").into()))?;
-                let prometheus = PrometheusService::new(
-                    config.prometheus,
-                    config.debug_clients_http.then_some(debug_client_rx),
-                )
-                .map_err(|error| GeyserPluginError::Custom(Box::new(error)))?;
-                Ok::<_, GeyserPluginError>((
-                    snapshot_channel,
-                    grpc_channel,
-                    grpc_shutdown,
-                    prometheus,
-                ))
-            })?;
+            let prometheus = PrometheusService::new(
+                config.prometheus,
+                config.debug_clients_http.then_some(debug_client_rx),
+            )
+            .map_err(|error| GeyserPluginError::Custom(Box::new(error)))?;
+            Ok::<_, GeyserPluginError>((
+                snapshot_channel,
+                grpc_channel,
+                grpc_shedding,
+                grpc_slot_skew,
+                grpc_shutdown,
+                prometheus,
+            ))
+        })?;
 
         self.inner = Some(PluginInner {
             runtime,
             snapshot_channel,
             grpc_channel,
+            grpc_shedding,
+            grpc_slot_skew,
             grpc_shutdown,
             prometheus,
         });
@@ -103,7 +121,7 @@ impl GeyserPlugin for Plugin {
 
     fn on_unload(&mut self) {
         if let Some(inner) = self.inner.take() {
-            inner.grpc_shutdown.notify_one();
+            inner.runtime.block_on(inner.grpc_shutdown.shutdown());
             drop(inner.grpc_channel);
             inner.prometheus.shutdown();
             inner.runtime.shutdown_timeout(Duration::from_secs(30));
@@ -162,6 +180,14 @@ impl GeyserPlugin for Plugin {
         status: SlotStatus,
     ) -> PluginResult<()> {
         self.with_inner(|inner| {
+            if status == SlotStatus::Processed {
+                if let Some(shedding) = &inner.grpc_shedding {
+                    shedding.update_received_slot(slot);
+                }
+                inner.grpc_slot_skew.update_received_slot(slot);
+                // checked on the validator thread, so stalled geyser or client loops are reported
+                inner.grpc_slot_skew.check(Instant::now());
+            }
             let message = Message::Slot((slot, parent, status).into());
             inner.send_message(message);
             prom::update_slot_status(status, slot);
@@ -8,7 +8,10 @@ use {
         Body, Request, Response, Server, StatusCode,
     },
     log::error,
-    prometheus::{IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder},
+    prometheus::{
+        HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+        Registry, TextEncoder,
+    },
     solana_sdk::clock::Slot,
     std::{
         collections::{hash_map::Entry as HashMapEntry, HashMap},
@@ -17,6 +20,7 @@ use {
     tokio::{
         sync::{mpsc, oneshot},
         task::JoinHandle,
+        time::Instant,
     },
     yellowstone_grpc_proto::prelude::CommitmentLevel,
 };
@@ -48,10 +52,56 @@ lazy_static::lazy_static! {
         "message_queue_size", "Size of geyser message queue"
     ).unwrap();
 
+    static ref MESSAGE_LATENCY_SECONDS: HistogramVec = HistogramVec::new(
+        HistogramOpts::new("message_latency_seconds", "Time from receiving the message from Geyser to sending the update to the client")
+            .buckets(vec![0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]),
+        &["message", "commitment"]
+    ).unwrap();
+
     pub static ref CONNECTIONS_TOTAL: IntGauge = IntGauge::new(
         "connections_total", "Total number of connections to gRPC service"
     ).unwrap();
 
+    pub static ref SUBSCRIPTIONS_ADMISSION_QUEUE: IntGauge = IntGauge::new(
+        "subscriptions_admission_queue", "Number of new subscriptions waiting for admission"
+    ).unwrap();
+
+    pub static ref SUBSCRIPTIONS_ADMISSION_REJECTED_TOTAL: IntCounterVec = IntCounterVec::new(
+        Opts::new("subscriptions_admission_rejected_total", "Total number of new subscriptions rejected by admission control"),
+        &["reason"]
+    ).unwrap();
+
+    pub static ref SLOT_SKEW: IntGauge = IntGauge::new(
+        "slot_skew", "Processed slots between the validator and the fastest subscriber"
+    ).unwrap();
+
+    pub static ref SUBSCRIPTIONS_SHED_TOTAL: IntCounter = IntCounter::new(
+        "subscriptions_shed_total", "Total number of low priority subscriptions closed or rejected due to plugin lag"
+    ).unwrap();
+
+    pub static ref RECONNECT_STORM_SIZE: IntGauge = IntGauge::new(
+        "reconnect_storm_size", "Number of subscriptions closed with retry hint on the last plugin unload"
+    ).unwrap();
+
+    pub static ref ACCOUNTS_COALESCED_TOTAL: IntCounter = IntCounter::new(
+        "accounts_coalesced_total", "Total number of account updates superseded by coalescing"
+    ).unwrap();
+
+    pub static ref ACCOUNTS_OVERSIZED_TOTAL: IntCounterVec = IntCounterVec::new(
+        Opts::new("accounts_oversized_total", "Total number of account updates over the max message size"),
+        &["action"]
+    ).unwrap();
+
+    pub static ref BLOCKS_OVERSIZED_TOTAL: IntCounterVec = IntCounterVec::new(
+        Opts::new("blocks_oversized_total", "Total number of block updates over the max message size"),
+        &["action"]
+    ).unwrap();
+
+    pub static ref QUOTA_USAGE_BYTES: IntGaugeVec = IntGaugeVec::new(
+        Opts::new("quota_usage_bytes", "Bytes sent in the current quota period by x-token hash"),
+        &["token"]
+    ).unwrap();
+
     static ref SUBSCRIPTIONS_TOTAL: IntGaugeVec = IntGaugeVec::new(
         Opts::new("subscriptions_total", "Total number of subscriptions to gRPC service"),
         &["endpoint", "subscription"]
@@ -62,6 +112,7 @@ lazy_static::lazy_static! {
 pub enum DebugClientMessage {
     UpdateFilter { id: usize, filter: Box<Filter> },
     UpdateSlot { id: usize, slot: Slot },
+    UpdateCommit { id: usize, slot: Slot },
     Removed { id: usize },
 }
 
@@ -77,6 +128,7 @@ impl DebugClientMessage {
 struct DebugClientStatus {
     filter: Box<Filter>,
     processed_slot: Slot,
+    commit_slot: Option<Slot>,
 }
 
 #[derive(Debug)]
@@ -105,6 +157,9 @@ impl DebugClientStatuses {
         let mut clients = HashMap::<usize, DebugClientStatus>::new();
         loop {
             tokio::select! {
+                // apply pending updates first, so statuses include everything sent before request
+                biased;
+
                 Some(message) = clients_rx.recv() => match message {
                     DebugClientMessage::UpdateFilter { id, filter } => {
                         match clients.entry(id) {
@@ -115,6 +170,7 @@ impl DebugClientStatuses {
                                 entry.insert(DebugClientStatus {
                                     filter,
                                     processed_slot: 0,
+                                    commit_slot: None,
                                 });
                             }
                         }
@@ -124,13 +180,22 @@ impl DebugClientStatuses {
                             status.processed_slot = slot;
                         }
                     }
+                    DebugClientMessage::UpdateCommit { id, slot } => {
+                        if let Some(status) = clients.get_mut(&id) {
+                            status.commit_slot = status.commit_slot.max(Some(slot));
+                        }
+                    }
                     DebugClientMessage::Removed { id } => {
                         clients.remove(&id);
                     }
                 },
                 Some(tx) = requests_rx.recv() => {
                     let mut statuses: Vec<(usize, String)> = clients.iter().map(|(id, status)| {
-                        (*id, format!("client#{id:06}, {}, {:?}", status.processed_slot, status.filter))
+                        let commit = match status.commit_slot {
+                            Some(slot) => format!("commit {slot} (behind {})", status.processed_slot.saturating_sub(slot)),
+                            None => "no commit".to_owned(),
+                        };
+                        (*id, format!("client#{id:06}, {}, {commit}, {:?}", status.processed_slot, status.filter))
                     }).collect();
                     statuses.sort();
 
@@ -185,8 +250,18 @@ impl PrometheusService {
             register!(SLOT_STATUS_PLUGIN);
             register!(INVALID_FULL_BLOCKS);
             register!(MESSAGE_QUEUE_SIZE);
+            register!(MESSAGE_LATENCY_SECONDS);
             register!(CONNECTIONS_TOTAL);
             register!(SUBSCRIPTIONS_TOTAL);
+            register!(QUOTA_USAGE_BYTES);
+            register!(SUBSCRIPTIONS_SHED_TOTAL);
+            register!(SLOT_SKEW);
+            register!(SUBSCRIPTIONS_ADMISSION_QUEUE);
+            register!(SUBSCRIPTIONS_ADMISSION_REJECTED_TOTAL);
+            register!(RECONNECT_STORM_SIZE);
+            register!(ACCOUNTS_COALESCED_TOTAL);
+            register!(ACCOUNTS_OVERSIZED_TOTAL);
+            register!(BLOCKS_OVERSIZED_TOTAL);
 
             VERSION
                 .with_label_values(&[
@@ -278,6 +353,14 @@ fn not_found_handler() -> Response<Body> {
     build_http_response(StatusCode::NOT_FOUND, Body::empty())
 }
 
+const fn commitment_label(commitment: CommitmentLevel) -> &'static str {
+    match commitment {
+        CommitmentLevel::Processed => "processed",
+        CommitmentLevel::Confirmed => "confirmed",
+        CommitmentLevel::Finalized => "finalized",
+    }
+}
+
 pub fn update_slot_status(status: SlotStatus, slot: u64) {
     SLOT_STATUS
         .with_label_values(&[match status {
@@ -290,14 +373,16 @@ pub fn update_slot_status(status: SlotStatus, slot: u64) {
 
 pub fn update_slot_plugin_status(status: CommitmentLevel, slot: u64) {
     SLOT_STATUS_PLUGIN
-        .with_label_values(&[match status {
-            CommitmentLevel::Processed => "processed",
-            CommitmentLevel::Confirmed => "confirmed",
-            CommitmentLevel::Finalized => "finalized",
-        }])
+        .with_label_values(&[commitment_label(status)])
         .set(slot as i64);
 }
 
+pub fn update_message_latency(kind: &str, commitment: CommitmentLevel, created_at: Instant) {
+    MESSAGE_LATENCY_SECONDS
+        .with_label_values(&[kind, commitment_label(commitment)])
+        .observe(created_at.elapsed().as_secs_f64());
+}
+
 pub fn update_invalid_blocks(reason: impl AsRef<str>) {
     INVALID_FULL_BLOCKS
         .with_label_values(&[reason.as_ref()])
@@ -320,3 +405,39 @@ pub fn update_subscriptions(endpoint: &str, old: Option<&Filter>, new: Option<&F
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{DebugClientMessage, DebugClientStatuses},
+        crate::{config::ConfigGrpcFilters, filters::Filter},
+        tokio::sync::mpsc,
+        yellowstone_grpc_proto::prelude::SubscribeRequest,
+    };
+
+    #[tokio::test]
+    async fn test_debug_clients_commit() {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let statuses = DebugClientStatuses::new(rx);
+
+        let filter =
+            Filter::new(&SubscribeRequest::default(), &ConfigGrpcFilters::default()).unwrap();
+        tx.send(DebugClientMessage::UpdateFilter {
+            id: 1,
+            filter: Box::new(filter),
+        })
+        .unwrap();
+        tx.send(DebugClientMessage::UpdateSlot { id: 1, slot: 110 })
+            .unwrap();
+        let status = statuses.get_statuses().await.unwrap();
+        assert!(status.starts_with("client#000001, 110, no commit, "));
+
+        // commits are never moved back
+        for slot in [100, 95] {
+            tx.send(DebugClientMessage::UpdateCommit { id: 1, slot })
+                .unwrap();
+        }
+        let status = statuses.get_statuses().await.unwrap();
+        assert!(status.starts_with("client#000001, 110, commit 100 (behind 10), "));
+    }
+}
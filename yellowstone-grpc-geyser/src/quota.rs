@@ -0,0 +1,260 @@
+use {
+    crate::{config::ConfigGrpcQuotas, prom::QUOTA_USAGE_BYTES},
+    anyhow::Context,
+    log::{error, warn},
+    serde::{Deserialize, Serialize},
+    sha2::{Digest, Sha256},
+    std::{
+        collections::HashMap,
+        io,
+        sync::{Arc, Mutex},
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    },
+    tokio::{fs, time::sleep},
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaUsage {
+    /// Unix timestamp (seconds) of the current period start
+    pub period_start: u64,
+    /// Bytes sent to the token in the current period
+    pub bytes: u64,
+}
+
+#[derive(Debug, Default)]
+struct QuotaTrackerInner {
+    usage: HashMap<String, QuotaUsage>,
+    dirty: bool,
+}
+
+#[derive(Debug)]
+pub struct QuotaTracker {
+    config: ConfigGrpcQuotas,
+    inner: Mutex<QuotaTrackerInner>,
+}
+
+impl QuotaTracker {
+    pub async fn load(config: ConfigGrpcQuotas) -> anyhow::Result<Arc<Self>> {
+        anyhow::ensure!(config.period > 0, "quota `period` should be positive");
+        anyhow::ensure!(
+            config.save_interval > 0,
+            "quota `save_interval` should be positive"
+        );
+
+        // corrupted or partially written file should not prevent plugin from start
+        let usage: HashMap<String, QuotaUsage> = match fs::read(&config.path).await {
+            Ok(data) => serde_json::from_slice(&data).unwrap_or_else(|error| {
+                warn!("quota usage {:?} is discarded: {error:?}", config.path);
+                HashMap::new()
+            }),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("failed to read quota usage from {:?}", config.path))
+            }
+        };
+        for (token, usage) in usage.iter() {
+            Self::update_metric(token, usage);
+        }
+
+        Ok(Arc::new(Self {
+            config,
+            inner: Mutex::new(QuotaTrackerInner {
+                usage,
+                dirty: false,
+            }),
+        }))
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default()
+    }
+
+    // Periods are aligned to unix epoch, so every token is reset at the same time
+    const fn get_period_start(&self, now: u64) -> u64 {
+        now - now % self.config.period
+    }
+
+    // Do not expose tokens in metrics, label is stable across restarts and builds
+    fn get_token_label(token: &str) -> String {
+        const_hex::encode(&Sha256::digest(token.as_bytes())[..8])
+    }
+
+    fn update_metric(token: &str, usage: &QuotaUsage) {
+        QUOTA_USAGE_BYTES
+            .with_label_values(&[&Self::get_token_label(token)])
+            .set(usage.bytes as i64);
+    }
+
+    /// Returns current usage of the token, usage from previous periods is ignored
+    pub fn get_usage(&self, token: &str) -> QuotaUsage {
+        self.get_usage_at(token, Self::now())
+    }
+
+    fn get_usage_at(&self, token: &str, now: u64) -> QuotaUsage {
+        let period_start = self.get_period_start(now);
+        let inner = self.inner.lock().expect("alive mutex");
+        match inner.usage.get(token) {
+            Some(usage) if usage.period_start == period_start => *usage,
+            _ => QuotaUsage {
+                period_start,
+                bytes: 0,
+            },
+        }
+    }
+
+    pub fn is_exhausted(&self, token: &str) -> bool {
+        self.get_usage(token).bytes >= self.config.bytes
+    }
+
+    /// Adds sent bytes to the token usage, returns `false` if quota is exhausted
+    pub fn add(&self, token: &str, bytes: u64) -> bool {
+        self.add_at(token, bytes, Self::now())
+    }
+
+    fn add_at(&self, token: &str, bytes: u64, now: u64) -> bool {
+        let period_start = self.get_period_start(now);
+        let mut inner = self.inner.lock().expect("alive mutex");
+        inner.dirty = true;
+        let usage = inner.usage.entry(token.to_owned()).or_default();
+        if usage.period_start != period_start {
+            *usage = QuotaUsage {
+                period_start,
+                bytes: 0,
+            };
+        }
+        usage.bytes = usage.bytes.saturating_add(bytes);
+        Self::update_metric(token, usage);
+        usage.bytes < self.config.bytes
+    }
+
+    pub async fn save(&self) -> anyhow::Result<()> {
+        let data = {
+            let mut inner = self.inner.lock().expect("alive mutex");
+            if !inner.dirty {
+                return Ok(());
+            }
+            inner.dirty = false;
+            serde_json::to_vec(&inner.usage).context("failed to serialize quota usage")?
+        };
+
+        // write to temporary file first, so usage is never lost on partial write
+        let mut path_tmp = self.config.path.clone().into_os_string();
+        path_tmp.push(".tmp");
+        fs::write(&path_tmp, data)
+            .await
+            .with_context(|| format!("failed to write quota usage to {path_tmp:?}"))?;
+        fs::rename(&path_tmp, &self.config.path)
+            .await
+            .with_context(|| format!("failed to rename {path_tmp:?}"))
+    }
+
+    pub async fn save_loop(self: Arc<Self>) {
+        loop {
+            sleep(Duration::from_secs(self.config.save_interval)).await;
+            if let Err(error) = self.save().await {
+                self.inner.lock().expect("alive mutex").dirty = true;
+                error!("failed to save quota usage: {error:?}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{QuotaTracker, QuotaUsage},
+        crate::config::ConfigGrpcQuotas,
+    };
+
+    fn create_config(name: &str) -> ConfigGrpcQuotas {
+        let path = std::env::temp_dir().join(format!(
+            "yellowstone-grpc-geyser-quota-{name}-{}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        ConfigGrpcQuotas {
+            path,
+            bytes: 100,
+            period: 60,
+            save_interval: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_quota_exhausted() {
+        let tracker = QuotaTracker::load(create_config("exhausted"))
+            .await
+            .unwrap();
+        assert!(tracker.add_at("token", 60, 120));
+        assert!(tracker.add_at("other", 60, 120));
+        assert!(!tracker.add_at("token", 40, 150));
+        assert_eq!(
+            tracker.get_usage_at("token", 179),
+            QuotaUsage {
+                period_start: 120,
+                bytes: 100
+            }
+        );
+        assert_eq!(tracker.get_usage_at("other", 179).bytes, 60);
+    }
+
+    #[tokio::test]
+    async fn test_quota_period_reset() {
+        let tracker = QuotaTracker::load(create_config("reset")).await.unwrap();
+        assert!(!tracker.add_at("token", 150, 10));
+        assert_eq!(tracker.get_usage_at("token", 59).bytes, 150);
+        assert_eq!(
+            tracker.get_usage_at("token", 60),
+            QuotaUsage {
+                period_start: 60,
+                bytes: 0
+            }
+        );
+        assert!(tracker.add_at("token", 10, 61));
+        assert_eq!(tracker.get_usage_at("token", 61).bytes, 10);
+    }
+
+    #[tokio::test]
+    async fn test_quota_persisted() {
+        let config = create_config("persisted");
+        let tracker = QuotaTracker::load(config.clone()).await.unwrap();
+        assert!(tracker.add_at("token", 70, 120));
+        tracker.save().await.unwrap();
+
+        let tracker = QuotaTracker::load(config.clone()).await.unwrap();
+        assert_eq!(tracker.get_usage_at("token", 130).bytes, 70);
+        assert!(!tracker.add_at("token", 30, 130));
+        std::fs::remove_file(&config.path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_quota_invalid_config() {
+        let mut config = create_config("invalid");
+        config.period = 0;
+        assert!(QuotaTracker::load(config.clone()).await.is_err());
+        config.period = 60;
+        config.save_interval = 0;
+        assert!(QuotaTracker::load(config).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_quota_corrupted() {
+        let config = create_config("corrupted");
+        for data in [r#"{"token":{"period_start":120,"#, "[]", ""] {
+            std::fs::write(&config.path, data).unwrap();
+            let tracker = QuotaTracker::load(config.clone()).await.unwrap();
+            assert_eq!(tracker.get_usage_at("token", 130).bytes, 0);
+        }
+        std::fs::remove_file(&config.path).unwrap();
+    }
+
+    #[test]
+    fn test_quota_token_label() {
+        // `printf %s secret | sha256sum | cut -c1-16`
+        assert_eq!(QuotaTracker::get_token_label("secret"), "2bb80d537b1da3e3");
+    }
+}
@@ -0,0 +1,273 @@
+use {
+    crate::grpc::Message,
+    std::{
+        collections::{BTreeMap, VecDeque},
+        fmt,
+        str::FromStr,
+        sync::{Arc, Mutex},
+    },
+    yellowstone_grpc_proto::prelude::CommitmentLevel,
+};
+
+/// Position of the message in the stream of the commitment level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResumeToken {
+    pub commitment: CommitmentLevel,
+    pub slot: u64,
+    pub seq: u64,
+}
+
+impl fmt::Display for ResumeToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.commitment as i32, self.slot, self.seq)
+    }
+}
+
+impl FromStr for ResumeToken {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut parts = value.split(':').map(|part| part.parse::<u64>().ok());
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(Some(commitment)), Some(Some(slot)), Some(Some(seq)), None) => Ok(Self {
+                commitment: i32::try_from(commitment)
+                    .ok()
+                    .and_then(|commitment| CommitmentLevel::try_from(commitment).ok())
+                    .ok_or_else(|| anyhow::anyhow!("invalid resume token"))?,
+                slot,
+                seq,
+            }),
+            _ => anyhow::bail!("invalid resume token"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayError {
+    /// Messages after the token are already removed from the buffer
+    Expired,
+    /// Token was not issued by this buffer (e.g. plugin was restarted)
+    Unknown,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Expired => write!(f, "resume token is older than the replay buffer"),
+            Self::Unknown => write!(f, "resume token is not known"),
+        }
+    }
+}
+
+type ReplayMessages = VecDeque<(u64, Arc<Message>)>;
+
+#[derive(Debug, Default)]
+struct ReplayBufferInner {
+    messages: [ReplayMessages; 3],
+    // committed slot => number of subscriptions
+    commits: BTreeMap<u64, usize>,
+}
+
+/// Last broadcasted messages for every commitment level, with sequence numbers
+#[derive(Debug)]
+pub struct ReplayBuffer {
+    max: usize,
+    inner: Mutex<ReplayBufferInner>,
+}
+
+impl ReplayBuffer {
+    pub fn new(max: usize) -> Self {
+        Self {
+            max,
+            inner: Mutex::default(),
+        }
+    }
+
+    /// Saves messages, `first_seq` is the sequence number of the first message. Messages from
+    /// the lowest committed slot are kept over `max` (up to `2 * max`), once the commit is
+    /// released the buffer is shrunk by one extra message per saved message, so a client
+    /// which just disconnected still can resume
+    pub fn push(&self, commitment: CommitmentLevel, first_seq: u64, messages: &[Arc<Message>]) {
+        let mut inner = self.inner.lock().expect("alive mutex");
+        let committed_slot = inner.commits.keys().next().copied();
+        let buffer = &mut inner.messages[commitment as usize];
+        for (seq, message) in (first_seq..).zip(messages.iter()) {
+            let mut evict = if buffer.len() > self.max { 2 } else { 1 };
+            while evict > 0 && buffer.len() >= self.max {
+                let is_committed = buffer.front().map_or(false, |(_seq, message)| {
+                    committed_slot.map_or(false, |slot| message.get_slot() >= slot)
+                });
+                if is_committed && buffer.len() < self.max * 2 {
+                    break;
+                }
+                buffer.pop_front();
+                evict -= 1;
+            }
+            buffer.push_back((seq, Arc::clone(message)));
+        }
+    }
+
+    /// Moves commit of the subscription from `previous` slot to `slot`
+    pub fn update_commit(&self, previous: Option<u64>, slot: Option<u64>) {
+        let mut inner = self.inner.lock().expect("alive mutex");
+        if let Some(previous) = previous {
+            if let Some(count) = inner.commits.get_mut(&previous) {
+                *count -= 1;
+                if *count == 0 {
+                    inner.commits.remove(&previous);
+                }
+            }
+        }
+        if let Some(slot) = slot {
+            *inner.commits.entry(slot).or_default() += 1;
+        }
+    }
+
+    /// Returns sequence number of the first message and all messages after the token
+    pub fn get_after(&self, token: ResumeToken) -> Result<(u64, Vec<Arc<Message>>), ReplayError> {
+        let inner = self.inner.lock().expect("alive mutex");
+        let buffer = &inner.messages[token.commitment as usize];
+        let Some(first_seq) = buffer.front().map(|(seq, _message)| *seq) else {
+            return Err(ReplayError::Unknown);
+        };
+        if token.seq < first_seq {
+            return Err(ReplayError::Expired);
+        }
+
+        // slot of the message is checked, because sequence numbers start from zero on restart
+        let index = (token.seq - first_seq) as usize;
+        match buffer.get(index) {
+            Some((_seq, message)) if message.get_slot() == token.slot => Ok((
+                token.seq + 1,
+                buffer
+                    .range(index + 1..)
+                    .map(|(_seq, message)| Arc::clone(message))
+                    .collect(),
+            )),
+            _ => Err(ReplayError::Unknown),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{ReplayBuffer, ReplayError, ResumeToken},
+        crate::grpc::{Message, MessageCreatedAt, MessageSlot},
+        std::sync::Arc,
+        yellowstone_grpc_proto::prelude::CommitmentLevel,
+    };
+
+    fn create_slot_message(slot: u64) -> Arc<Message> {
+        Arc::new(Message::Slot(MessageSlot {
+            slot,
+            parent: None,
+            status: CommitmentLevel::Processed,
+            dead: false,
+            created_at: MessageCreatedAt::now(),
+        }))
+    }
+
+    #[test]
+    fn test_resume_token_parse() {
+        let token = ResumeToken {
+            commitment: CommitmentLevel::Confirmed,
+            slot: 100,
+            seq: 5,
+        };
+        assert_eq!(token.to_string(), "1:100:5");
+        assert_eq!("1:100:5".parse::<ResumeToken>().unwrap(), token);
+        for value in ["", "1:100", "1:100:5:1", "3:100:5", "1:a:5", "-1:100:5"] {
+            assert!(value.parse::<ResumeToken>().is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn test_replay_buffer() {
+        let buffer = ReplayBuffer::new(3);
+        let token = |slot, seq| ResumeToken {
+            commitment: CommitmentLevel::Processed,
+            slot,
+            seq,
+        };
+        let slots = |(first_seq, messages): (u64, Vec<Arc<Message>>)| {
+            (
+                first_seq,
+                messages
+                    .iter()
+                    .map(|message| message.get_slot())
+                    .collect::<Vec<_>>(),
+            )
+        };
+        let get_after = |token| buffer.get_after(token).map(slots);
+        assert_eq!(get_after(token(0, 0)), Err(ReplayError::Unknown));
+
+        buffer.push(
+            CommitmentLevel::Processed,
+            0,
+            &[create_slot_message(10), create_slot_message(11)],
+        );
+        assert_eq!(get_after(token(10, 0)), Ok((1, vec![11])));
+        assert_eq!(get_after(token(11, 1)), Ok((2, vec![])));
+
+        buffer.push(
+            CommitmentLevel::Processed,
+            2,
+            &[create_slot_message(12), create_slot_message(13)],
+        );
+        assert_eq!(get_after(token(10, 0)), Err(ReplayError::Expired));
+        assert_eq!(get_after(token(11, 1)), Ok((2, vec![12, 13])));
+        // other slot with the same sequence number or sequence number from the future
+        assert_eq!(get_after(token(12, 1)), Err(ReplayError::Unknown));
+        assert_eq!(get_after(token(14, 4)), Err(ReplayError::Unknown));
+        // other commitment levels have own buffers
+        let mut confirmed = token(11, 1);
+        confirmed.commitment = CommitmentLevel::Confirmed;
+        assert_eq!(get_after(confirmed), Err(ReplayError::Unknown));
+    }
+
+    #[test]
+    fn test_replay_buffer_commit() {
+        let buffer = ReplayBuffer::new(2);
+        let push = |first_seq: u64, slots: &[u64]| {
+            let messages = slots
+                .iter()
+                .map(|slot| create_slot_message(*slot))
+                .collect::<Vec<_>>();
+            buffer.push(CommitmentLevel::Processed, first_seq, &messages);
+        };
+        let get_slots = || {
+            buffer.inner.lock().unwrap().messages[CommitmentLevel::Processed as usize]
+                .iter()
+                .map(|(_seq, message)| message.get_slot())
+                .collect::<Vec<_>>()
+        };
+
+        // messages from the committed slot are kept up to twice of the size
+        buffer.update_commit(None, Some(11));
+        push(0, &[10, 11, 12]);
+        assert_eq!(get_slots(), vec![11, 12]);
+        push(3, &[13]);
+        assert_eq!(get_slots(), vec![11, 12, 13]);
+        push(4, &[14, 15]);
+        assert_eq!(get_slots(), vec![12, 13, 14, 15]);
+
+        // other subscription commit the same slot, commit is moved only by both
+        buffer.update_commit(None, Some(13));
+        buffer.update_commit(Some(11), Some(13));
+        push(6, &[16]);
+        assert_eq!(get_slots(), vec![13, 14, 15, 16]);
+        buffer.update_commit(Some(13), None);
+        push(7, &[17]);
+        assert_eq!(get_slots(), vec![14, 15, 16, 17]);
+
+        // released buffer is shrunk gradually
+        buffer.update_commit(Some(13), None);
+        push(8, &[18]);
+        assert_eq!(get_slots(), vec![16, 17, 18]);
+        push(9, &[19]);
+        assert_eq!(get_slots(), vec![18, 19]);
+        push(10, &[20]);
+        assert_eq!(get_slots(), vec![19, 20]);
+    }
+}
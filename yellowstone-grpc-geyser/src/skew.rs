@@ -0,0 +1,130 @@
+use {
+    crate::{config::ConfigGrpcSlotSkew, prom::SLOT_SKEW},
+    log::{info, warn},
+    std::{
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, Mutex,
+        },
+        time::{Duration, Instant},
+    },
+};
+
+/// Difference between the processed slot received from the validator and the processed slot
+/// delivered to the fastest subscriber
+#[derive(Debug)]
+pub struct SlotSkew {
+    config: Option<ConfigGrpcSlotSkew>,
+    received_slot: AtomicU64,
+    sent_slot: AtomicU64,
+    state: Mutex<SlotSkewState>,
+}
+
+#[derive(Debug, Default)]
+struct SlotSkewState {
+    exceeded_since: Option<Instant>,
+    warned: bool,
+}
+
+impl SlotSkew {
+    pub fn new(config: Option<ConfigGrpcSlotSkew>) -> Arc<Self> {
+        Arc::new(Self {
+            config,
+            received_slot: AtomicU64::new(0),
+            sent_slot: AtomicU64::new(0),
+            state: Mutex::default(),
+        })
+    }
+
+    /// Processed slot received by the plugin from the validator
+    pub fn update_received_slot(&self, slot: u64) {
+        self.received_slot.fetch_max(slot, Ordering::Relaxed);
+    }
+
+    /// Processed slot delivered to a subscriber
+    pub fn update_sent_slot(&self, slot: u64) {
+        self.sent_slot.fetch_max(slot, Ordering::Relaxed);
+    }
+
+    pub fn get_received_slot(&self) -> u64 {
+        self.received_slot.load(Ordering::Relaxed)
+    }
+
+    pub fn get(&self) -> u64 {
+        self.received_slot
+            .load(Ordering::Relaxed)
+            .saturating_sub(self.sent_slot.load(Ordering::Relaxed))
+    }
+
+    /// Updates metric and warns once skew stays over the threshold longer than the grace period,
+    /// returns `true` while the warning is active
+    pub fn check(&self, now: Instant) -> bool {
+        let skew = self.get();
+        SLOT_SKEW.set(skew as i64);
+
+        let Some(config) = &self.config else {
+            return false;
+        };
+        let mut state = self.state.lock().expect("alive mutex");
+        if skew > config.warn_slots {
+            let since = *state.exceeded_since.get_or_insert(now);
+            if !state.warned
+                && now.saturating_duration_since(since)
+                    >= Duration::from_millis(config.warn_grace_ms)
+            {
+                warn!("plugin falls behind the validator, slot skew: {skew} slots");
+                state.warned = true;
+            }
+        } else {
+            if state.warned {
+                info!("plugin caught up with the validator, slot skew: {skew} slots");
+            }
+            *state = SlotSkewState::default();
+        }
+        state.warned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::SlotSkew,
+        crate::config::ConfigGrpcSlotSkew,
+        std::time::{Duration, Instant},
+    };
+
+    #[test]
+    fn test_slot_skew_grace_period() {
+        let skew = SlotSkew::new(Some(ConfigGrpcSlotSkew {
+            warn_slots: 10,
+            warn_grace_ms: 1_000,
+        }));
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+
+        skew.update_received_slot(100);
+        skew.update_sent_slot(95);
+        assert_eq!(skew.get(), 5);
+        assert!(!skew.check(at(0)));
+
+        // short spikes are ignored
+        skew.update_received_slot(120);
+        assert_eq!(skew.get(), 25);
+        assert!(!skew.check(at(100)));
+        assert!(!skew.check(at(900)));
+        skew.update_sent_slot(115);
+        assert!(!skew.check(at(1_200)));
+
+        // warning is active once skew is over the threshold for the grace period
+        skew.update_received_slot(140);
+        assert!(!skew.check(at(1_300)));
+        assert!(skew.check(at(2_300)));
+        assert!(skew.check(at(3_000)));
+
+        // slots are never moved back, skew is reset once subscribers catch up
+        skew.update_sent_slot(100);
+        skew.update_sent_slot(135);
+        assert_eq!(skew.get(), 5);
+        assert!(!skew.check(at(3_100)));
+    }
+}
@@ -24,6 +24,8 @@ pub const VERSION: Version = Version {
 #[derive(Debug, Serialize)]
 pub struct GrpcVersionInfoExtra {
     hostname: Option<String>,
+    /// Current slot skew, see `SlotSkew`
+    slot_skew: u64,
 }
 
 #[derive(Debug, Serialize)]
@@ -32,14 +34,15 @@ pub struct GrpcVersionInfo {
     extra: GrpcVersionInfoExtra,
 }
 
-impl Default for GrpcVersionInfo {
-    fn default() -> Self {
+impl GrpcVersionInfo {
+    pub fn new(slot_skew: u64) -> Self {
         Self {
             version: VERSION,
             extra: GrpcVersionInfoExtra {
                 hostname: hostname::get()
                     .ok()
                     .and_then(|name| name.into_string().ok()),
+                slot_skew,
             },
         }
     }
@@ -1,5 +1,16 @@
 fn main() -> anyhow::Result<()> {
     std::env::set_var("PROTOC", protobuf_src::protoc());
-    tonic_build::compile_protos("proto/geyser.proto")?;
+
+    let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR")?);
+    let mut builder =
+        tonic_build::configure().file_descriptor_set_path(out_dir.join("geyser_descriptor.bin"));
+    if std::env::var_os("CARGO_FEATURE_SERDE").is_some() {
+        builder = builder
+            .type_attribute(".", "#[derive(serde::Serialize, serde::Deserialize)]")
+            .message_attribute(".", "#[serde(default)]")
+            .enum_attribute(".", "#[serde(rename_all = \"snake_case\")]");
+    }
+    builder.compile(&["proto/geyser.proto"], &["proto"])?;
+
     Ok(())
 }
@@ -2,6 +2,31 @@
 
 pub mod geyser {
     tonic::include_proto!("geyser");
+
+    /// Encoded descriptors of `geyser.proto` and imported files, used by gRPC reflection
+    pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("geyser_descriptor");
+
+    impl SubscribeRejectionReason {
+        /// Creates status with the reason encoded into the details
+        pub fn into_status(self, code: tonic::Code, message: impl Into<String>) -> tonic::Status {
+            let details = SubscribeRejection {
+                reason: self as i32,
+            };
+            tonic::Status::with_details(
+                code,
+                message,
+                prost::Message::encode_to_vec(&details).into(),
+            )
+        }
+
+        /// Decodes the reason from the details of the status returned by `Subscribe`
+        pub fn from_status(status: &tonic::Status) -> Option<Self> {
+            <SubscribeRejection as prost::Message>::decode(status.details())
+                .ok()
+                .and_then(|details| Self::try_from(details.reason).ok())
+                .filter(|reason| *reason != Self::Unspecified)
+        }
+    }
 }
 
 pub mod solana {
@@ -16,6 +41,89 @@ pub mod prelude {
     pub use super::{geyser::*, solana::storage::confirmed_block::*};
 }
 
+/// Named shortcuts for common accounts filters, expanded on the client side
+pub mod presets {
+    use super::geyser::{
+        subscribe_request_filter_accounts_filter::Filter as AccountsFilterDataOneof,
+        SubscribeRequestFilterAccounts, SubscribeRequestFilterAccountsFilter,
+    };
+
+    /// Prefix of accounts filter names created from presets, such filters are validated by the server
+    pub const FILTER_NAME_PREFIX: &str = "preset:";
+
+    pub const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+    pub const SPL_TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+    pub const SPL_TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+
+    /// Size of SPL Token account, mints and multisigs have other sizes
+    pub const SPL_TOKEN_ACCOUNT_SIZE: u64 = 165;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum FilterPreset {
+        /// Token accounts of SPL Token program
+        SplToken,
+        /// Token accounts of SPL Token-2022 program, size depends on extensions
+        SplToken2022,
+        /// Accounts owned by System program
+        System,
+    }
+
+    impl FilterPreset {
+        pub const ALL: [Self; 3] = [Self::SplToken, Self::SplToken2022, Self::System];
+
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::SplToken => "spl_token",
+                Self::SplToken2022 => "spl_token_2022",
+                Self::System => "system",
+            }
+        }
+
+        pub fn from_name(name: &str) -> Option<Self> {
+            Self::ALL.into_iter().find(|preset| preset.as_str() == name)
+        }
+
+        /// Name of accounts filter in `SubscribeRequest`, for example `preset:spl_token`
+        pub fn filter_name(self) -> String {
+            format!("{FILTER_NAME_PREFIX}{}", self.as_str())
+        }
+
+        pub const fn program_id(self) -> &'static str {
+            match self {
+                Self::SplToken => SPL_TOKEN_PROGRAM_ID,
+                Self::SplToken2022 => SPL_TOKEN_2022_PROGRAM_ID,
+                Self::System => SYSTEM_PROGRAM_ID,
+            }
+        }
+
+        pub fn filters(self) -> Vec<SubscribeRequestFilterAccountsFilter> {
+            let filter = match self {
+                Self::SplToken => AccountsFilterDataOneof::Datasize(SPL_TOKEN_ACCOUNT_SIZE),
+                Self::SplToken2022 => AccountsFilterDataOneof::TokenAccountState(true),
+                Self::System => return vec![],
+            };
+            vec![SubscribeRequestFilterAccountsFilter {
+                filter: Some(filter),
+            }]
+        }
+
+        pub fn to_filter(self) -> SubscribeRequestFilterAccounts {
+            SubscribeRequestFilterAccounts {
+                owner: vec![self.program_id().to_owned()],
+                filters: self.filters(),
+                ..Default::default()
+            }
+        }
+
+        /// Filter selects the same accounts as the preset, other options can differ
+        pub fn is_match(self, filter: &SubscribeRequestFilterAccounts) -> bool {
+            filter.account.is_empty()
+                && filter.owner == [self.program_id()]
+                && filter.filters == self.filters()
+        }
+    }
+}
+
 pub use {prost, tonic};
 
 pub mod convert_to {
@@ -177,9 +177,9 @@ impl ArgsAction {
                             .context("failed to get message from gRPC")?;
 
                         match &message {
-                            SubscribeUpdate { filters: _, update_oneof: Some(UpdateOneof::Ping(_)) } => prom::recv_inc(GprcMessageKind::Ping),
-                            SubscribeUpdate { filters: _, update_oneof: Some(UpdateOneof::Pong(_)) } => prom::recv_inc(GprcMessageKind::Pong),
-                            SubscribeUpdate { filters: _, update_oneof: Some(value) } => {
+                            SubscribeUpdate { filters: _, update_oneof: Some(UpdateOneof::Ping(_)), .. } => prom::recv_inc(GprcMessageKind::Ping),
+                            SubscribeUpdate { filters: _, update_oneof: Some(UpdateOneof::Pong(_)), .. } => prom::recv_inc(GprcMessageKind::Pong),
+                            SubscribeUpdate { filters: _, update_oneof: Some(value), .. } => {
                                 if let UpdateOneof::Slot(slot) = value {
                                     prom::set_slot_tip(
                                         CommitmentLevel::try_from(slot.status).expect("valid commitment"),
@@ -196,7 +196,7 @@ impl ArgsAction {
 
                                 prom::recv_inc(prom_kind);
                             },
-                            SubscribeUpdate { filters: _, update_oneof: None } => anyhow::bail!("received empty updat emessage"),
+                            SubscribeUpdate { filters: _, update_oneof: None, .. } => anyhow::bail!("received empty updat emessage"),
                         };
                     }
                 };
@@ -280,8 +280,26 @@ impl ArgsAction {
                     Some(UpdateOneof::Block(msg)) => info!("#{}, block", msg.slot),
                     Some(UpdateOneof::Ping(_)) => {}
                     Some(UpdateOneof::Pong(_)) => {}
+                    Some(UpdateOneof::Subscription(_)) => {}
+                    Some(UpdateOneof::FilterAck(_)) => {}
                     Some(UpdateOneof::BlockMeta(msg)) => info!("#{}, blockmeta", msg.slot),
                     Some(UpdateOneof::Entry(msg)) => info!("#{}, entry", msg.slot),
+                    Some(UpdateOneof::AccountChunk(msg)) => {
+                        info!(
+                            "#{}, account chunk {}/{}",
+                            msg.account.map_or(0, |msg| msg.slot),
+                            msg.index,
+                            msg.total
+                        )
+                    }
+                    Some(UpdateOneof::BlockChunk(msg)) => {
+                        info!(
+                            "#{}, block chunk {}/{}",
+                            msg.block.map_or(0, |msg| msg.slot),
+                            msg.index,
+                            msg.total
+                        )
+                    }
                     None => {}
                 },
                 Err(error) => {
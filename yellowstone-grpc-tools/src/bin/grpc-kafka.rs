@@ -276,8 +276,16 @@ impl ArgsAction {
                         UpdateOneof::Block(msg) => msg.slot,
                         UpdateOneof::Ping(_) => continue,
                         UpdateOneof::Pong(_) => continue,
+                        UpdateOneof::Subscription(_) => continue,
+                        UpdateOneof::FilterAck(_) => continue,
                         UpdateOneof::BlockMeta(msg) => msg.slot,
                         UpdateOneof::Entry(msg) => msg.slot,
+                        UpdateOneof::AccountChunk(msg) => {
+                            msg.account.as_ref().map_or(0, |msg| msg.slot)
+                        }
+                        UpdateOneof::BlockChunk(msg) => {
+                            msg.block.as_ref().map_or(0, |msg| msg.slot)
+                        }
                     };
                     let hash = Sha256::digest(&payload);
                     let key = format!("{slot}_{}", const_hex::encode(hash));
@@ -11,9 +11,10 @@ use {
         subscribe_request_filter_accounts_filter::Filter as AccountsFilterDataOneof,
         subscribe_request_filter_accounts_filter_memcmp::Data as AccountsFilterMemcmpOneof,
         CommitmentLevel, SubscribeRequest, SubscribeRequestAccountsDataSlice,
-        SubscribeRequestFilterAccounts, SubscribeRequestFilterAccountsFilter,
-        SubscribeRequestFilterAccountsFilterMemcmp, SubscribeRequestFilterBlocks,
-        SubscribeRequestFilterSlots, SubscribeRequestFilterTransactions,
+        SubscribeRequestFilterAccounts, SubscribeRequestFilterAccountsChangedRange,
+        SubscribeRequestFilterAccountsFilter, SubscribeRequestFilterAccountsFilterMemcmp,
+        SubscribeRequestFilterBlocks, SubscribeRequestFilterSlots,
+        SubscribeRequestFilterTransactions,
     },
 };
 
@@ -79,6 +80,14 @@ impl GrpcRequestToProto<SubscribeRequest> for ConfigGrpcRequest {
             commitment: self.commitment.map(|v| v.to_proto() as i32),
             accounts_data_slice: ConfigGrpcRequest::vec_to_proto(self.accounts_data_slice),
             ping: None,
+            commit: None,
+            accounts_coalesce_window_ms: None,
+            accounts_chunking: None,
+            accounts_sample_rate: None,
+            filter_ack: None,
+            resume_token: None,
+            blocks_chunking: None,
+            include_created_at: None,
         }
     }
 }
@@ -87,12 +96,18 @@ impl GrpcRequestToProto<SubscribeRequest> for ConfigGrpcRequest {
 #[serde(default)]
 pub struct ConfigGrpcRequestSlots {
     filter_by_commitment: Option<bool>,
+    from_slot: Option<u64>,
+    to_slot: Option<u64>,
+    include_dead: Option<bool>,
 }
 
 impl GrpcRequestToProto<SubscribeRequestFilterSlots> for ConfigGrpcRequestSlots {
     fn to_proto(self) -> SubscribeRequestFilterSlots {
         SubscribeRequestFilterSlots {
             filter_by_commitment: self.filter_by_commitment,
+            from_slot: self.from_slot,
+            to_slot: self.to_slot,
+            include_dead: self.include_dead,
         }
     }
 }
@@ -103,6 +118,11 @@ pub struct ConfigGrpcRequestAccounts {
     account: Vec<String>,
     owner: Vec<String>,
     filters: Vec<ConfigGrpcRequestAccountsFilter>,
+    first_write_only: Option<bool>,
+    include_account_data: Option<bool>,
+    owner_change_only: Option<bool>,
+    lifecycle: Vec<i32>,
+    changed_range: Option<ConfigGrpcRequestAccountsChangedRange>,
 }
 
 impl GrpcRequestToProto<SubscribeRequestFilterAccounts> for ConfigGrpcRequestAccounts {
@@ -111,6 +131,28 @@ impl GrpcRequestToProto<SubscribeRequestFilterAccounts> for ConfigGrpcRequestAcc
             account: self.account,
             owner: self.owner,
             filters: self.filters.into_iter().map(|f| f.to_proto()).collect(),
+            first_write_only: self.first_write_only,
+            include_account_data: self.include_account_data,
+            owner_change_only: self.owner_change_only,
+            lifecycle: self.lifecycle,
+            changed_range: self.changed_range.map(|range| range.to_proto()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct ConfigGrpcRequestAccountsChangedRange {
+    offset: u64,
+    length: u64,
+}
+
+impl GrpcRequestToProto<SubscribeRequestFilterAccountsChangedRange>
+    for ConfigGrpcRequestAccountsChangedRange
+{
+    fn to_proto(self) -> SubscribeRequestFilterAccountsChangedRange {
+        SubscribeRequestFilterAccountsChangedRange {
+            offset: self.offset,
+            length: self.length,
         }
     }
 }
@@ -152,6 +194,15 @@ pub struct ConfigGrpcRequestTransactions {
     pub account_include: Vec<String>,
     pub account_exclude: Vec<String>,
     pub account_required: Vec<String>,
+    pub include_transaction_message: Option<bool>,
+    pub signature_prefix: Option<Vec<u8>>,
+    pub include_meta: Option<bool>,
+    pub include_logs: Option<bool>,
+    pub include_inner_instructions: Option<bool>,
+    pub include_account_keys: Option<bool>,
+    pub invoked_programs: Vec<String>,
+    pub include_balances: Option<bool>,
+    pub include_return_data: Option<bool>,
 }
 
 impl GrpcRequestToProto<SubscribeRequestFilterTransactions> for ConfigGrpcRequestTransactions {
@@ -163,6 +214,15 @@ impl GrpcRequestToProto<SubscribeRequestFilterTransactions> for ConfigGrpcReques
             account_include: self.account_include,
             account_exclude: self.account_exclude,
             account_required: self.account_required,
+            include_transaction_message: self.include_transaction_message,
+            signature_prefix: self.signature_prefix,
+            include_meta: self.include_meta,
+            include_logs: self.include_logs,
+            include_inner_instructions: self.include_inner_instructions,
+            include_account_keys: self.include_account_keys,
+            invoked_programs: self.invoked_programs,
+            include_balances: self.include_balances,
+            include_return_data: self.include_return_data,
         }
     }
 }
@@ -174,6 +234,7 @@ pub struct ConfigGrpcRequestBlocks {
     pub include_transactions: Option<bool>,
     pub include_accounts: Option<bool>,
     pub include_entries: Option<bool>,
+    pub include_rewards: Option<bool>,
 }
 
 impl GrpcRequestToProto<SubscribeRequestFilterBlocks> for ConfigGrpcRequestBlocks {
@@ -183,6 +244,7 @@ impl GrpcRequestToProto<SubscribeRequestFilterBlocks> for ConfigGrpcRequestBlock
             include_transactions: self.include_transactions,
             include_accounts: self.include_accounts,
             include_entries: self.include_entries,
+            include_rewards: self.include_rewards,
         }
     }
 }
@@ -27,10 +27,12 @@ use {
     yellowstone_grpc_proto::prelude::{
         geyser_server::{Geyser, GeyserServer},
         subscribe_update::UpdateOneof,
-        GetBlockHeightRequest, GetBlockHeightResponse, GetLatestBlockhashRequest,
+        DropSubscriptionRequest, DropSubscriptionResponse, GetBlockHeightRequest,
+        GetBlockHeightResponse, GetBlockRequest, GetBlockResponse, GetLatestBlockhashRequest,
         GetLatestBlockhashResponse, GetSlotRequest, GetSlotResponse, GetVersionRequest,
-        GetVersionResponse, IsBlockhashValidRequest, IsBlockhashValidResponse, PingRequest,
-        PongResponse, SubscribeRequest, SubscribeUpdate, SubscribeUpdatePing,
+        GetVersionResponse, IsBlockhashValidRequest, IsBlockhashValidResponse,
+        ListSubscriptionsRequest, ListSubscriptionsResponse, PingRequest, PongResponse,
+        SubscribeRequest, SubscribeUpdate, SubscribeUpdatePing,
     },
 };
 
@@ -119,6 +121,9 @@ impl Geyser for GrpcService {
             let ping_msg = SubscribeUpdate {
                 filters: vec![],
                 update_oneof: Some(UpdateOneof::Ping(SubscribeUpdatePing {})),
+                resume_token: None,
+                created_at_us: None,
+                seq: 0,
             };
 
             loop {
@@ -245,4 +250,25 @@ impl Geyser for GrpcService {
             version: serde_json::to_string(&VERSION).unwrap(),
         }))
     }
+
+    async fn get_block(
+        &self,
+        _request: Request<GetBlockRequest>,
+    ) -> Result<Response<GetBlockResponse>, Status> {
+        Err(Status::unimplemented("not implemented in kafka reader"))
+    }
+
+    async fn drop_subscription(
+        &self,
+        _request: Request<DropSubscriptionRequest>,
+    ) -> Result<Response<DropSubscriptionResponse>, Status> {
+        Err(Status::unimplemented("not implemented in kafka reader"))
+    }
+
+    async fn list_subscriptions(
+        &self,
+        _request: Request<ListSubscriptionsRequest>,
+    ) -> Result<Response<ListSubscriptionsResponse>, Status> {
+        Err(Status::unimplemented("not implemented in kafka reader"))
+    }
 }
@@ -118,6 +118,10 @@ pub enum GprcMessageKind {
     Pong,
     BlockMeta,
     Entry,
+    AccountChunk,
+    Subscription,
+    FilterAck,
+    BlockChunk,
     Unknown,
 }
 
@@ -133,6 +137,10 @@ impl From<&UpdateOneof> for GprcMessageKind {
             UpdateOneof::Pong(_) => Self::Pong,
             UpdateOneof::BlockMeta(_) => Self::BlockMeta,
             UpdateOneof::Entry(_) => Self::Entry,
+            UpdateOneof::AccountChunk(_) => Self::AccountChunk,
+            UpdateOneof::Subscription(_) => Self::Subscription,
+            UpdateOneof::FilterAck(_) => Self::FilterAck,
+            UpdateOneof::BlockChunk(_) => Self::BlockChunk,
         }
     }
 }
@@ -149,6 +157,10 @@ impl GprcMessageKind {
             GprcMessageKind::Pong => "pong",
             GprcMessageKind::BlockMeta => "blockmeta",
             GprcMessageKind::Entry => "entry",
+            GprcMessageKind::AccountChunk => "account_chunk",
+            GprcMessageKind::Subscription => "subscription",
+            GprcMessageKind::FilterAck => "filter_ack",
+            GprcMessageKind::BlockChunk => "block_chunk",
             GprcMessageKind::Unknown => "unknown",
         }
     }